@@ -0,0 +1,12 @@
+//! Command line only build, for CI and scripts.
+//!
+//! The GUI binary is a Windows GUI application in release builds and cannot print to the console
+//! it was started from, this one is a console application on every platform.
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() -> std::process::ExitCode {
+    better_erc::cli::main()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn main() {}
@@ -0,0 +1,227 @@
+//! Headless commands, so that designs can be checked in CI without starting the GUI
+
+use clap::{CommandFactory, Parser, Subcommand};
+use ecad_file_format::load_netlist;
+use erc_core::{Baseline, Pcba, ProjectConfig, Report, Severity, Waivers};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+#[derive(Parser)]
+#[command(
+    name = "better_erc",
+    version,
+    about = "Electrical rules checker for schematics and netlists"
+)]
+pub struct Cli {
+    /// Starts the GUI when no command is given, better_erc_cli prints help instead
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Loads a netlist or schematic, runs all the checks and prints diagnostics
+    Check(CheckArgs),
+}
+
+#[derive(clap::Args)]
+pub struct CheckArgs {
+    /// Netlist or schematic files, or a project folder, format is detected from file contents
+    #[arg(required = true)]
+    pub paths: Vec<PathBuf>,
+    /// Project config, better_erc.toml from the project folder is used by default
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+    /// Waiver file, better_erc_waivers.toml from the project folder is used by default
+    #[arg(long)]
+    pub waivers: Option<PathBuf>,
+    /// Exit with code 1 if there is a diagnostic that is not waived and is at least this severe
+    #[arg(long, default_value = "error")]
+    pub fail_on: Severity,
+    /// Do not print waived diagnostics
+    #[arg(long)]
+    pub hide_waived: bool,
+    /// Also write a report, format is chosen by extension: .json, .sarif, .xml (JUnit), .md or .html
+    #[arg(long, value_name = "PATH")]
+    pub report: Vec<PathBuf>,
+    /// Diagnostics of a previous run, only new diagnostics are printed and can fail the check
+    #[arg(long, value_name = "PATH")]
+    pub baseline: Option<PathBuf>,
+    /// Save current diagnostics as a baseline for later runs, after comparing with `--baseline`,
+    /// so both can be the same file
+    #[arg(long, value_name = "PATH")]
+    pub write_baseline: Option<PathBuf>,
+}
+
+/// Entry point of the command line only binary, prints help if no command is given
+pub fn main() -> ExitCode {
+    match Cli::parse().command {
+        Some(command) => run(command),
+        None => {
+            let _ = Cli::command().print_help();
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// Exit codes: 0 - passed, 1 - failed at the severity threshold, 2 - design or config cannot be loaded
+pub fn run(command: Command) -> ExitCode {
+    match command {
+        Command::Check(args) => match check(&args) {
+            Ok(true) => ExitCode::SUCCESS,
+            Ok(false) => ExitCode::from(1),
+            Err(e) => {
+                eprintln!("error: {e:#}");
+                ExitCode::from(2)
+            }
+        },
+    }
+}
+
+/// Returns false if the check failed
+fn check(args: &CheckArgs) -> anyhow::Result<bool> {
+    let detected = load_netlist(&args.paths)?;
+    eprintln!(
+        "Loaded {} from {} file(s)",
+        detected.format,
+        detected.files.len()
+    );
+    for warning in &detected.netlist.warnings {
+        eprintln!("warning: {warning}");
+    }
+
+    let project_dir = project_dir(&args.paths[0]);
+    let config = match &args.config {
+        Some(path) => ProjectConfig::load(path)?,
+        None => ProjectConfig::load_from_dir(&project_dir)?,
+    };
+    let waivers = match &args.waivers {
+        Some(path) => Waivers::load(path)?,
+        None => Waivers::load_from_dir(&project_dir)?,
+    };
+
+    let mut pcba = Pcba::with_config(detected.netlist, config)?;
+    pcba.apply_waivers(&waivers);
+    if !args.report.is_empty() {
+        let mut report = Report::new(&pcba, args.fail_on);
+        report.source_files = detected
+            .files
+            .iter()
+            .map(|f| f.display().to_string())
+            .collect();
+        for path in &args.report {
+            report.write(path)?;
+        }
+    }
+
+    let baseline = args.baseline.as_deref().map(Baseline::load).transpose()?;
+    let comparison = baseline
+        .as_ref()
+        .map(|baseline| baseline.compare(&pcba.diagnostics, &pcba.netlist));
+    if let Some(path) = &args.write_baseline {
+        Baseline::new(&pcba.diagnostics, &pcba.netlist).save(path)?;
+    }
+    let mut diagnostics = match &comparison {
+        Some(comparison) => comparison.new.clone(),
+        None => pcba.diagnostics.iter().collect::<Vec<_>>(),
+    };
+    diagnostics.sort_by(|a, b| {
+        b.severity
+            .cmp(&a.severity)
+            .then(a.code.cmp(b.code))
+            .then(a.message.cmp(&b.message))
+    });
+    let checked = diagnostics.len();
+    let mut failed = 0;
+    let mut waived = 0;
+    for diagnostic in diagnostics {
+        if diagnostic.waived_by.is_some() {
+            waived += 1;
+            if args.hide_waived {
+                continue;
+            }
+        } else if diagnostic.severity.is_at_least(args.fail_on) {
+            failed += 1;
+        }
+        println!("{diagnostic}");
+    }
+    if let Some(comparison) = &comparison {
+        for entry in &comparison.resolved {
+            println!("resolved {}: {}", entry.key, entry.message);
+        }
+        println!(
+            "{} new, {} unchanged, {} resolved since baseline",
+            comparison.new.len(),
+            comparison.unchanged.len(),
+            comparison.resolved.len()
+        );
+    }
+    println!(
+        "{checked} diagnostic(s), {waived} waived, {failed} at or above {}",
+        args.fail_on
+    );
+    Ok(failed == 0)
+}
+
+/// Folder where project config and waivers are looked for
+fn project_dir(path: &Path) -> PathBuf {
+    if path.is_dir() {
+        path.to_path_buf()
+    } else {
+        path.parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| PathBuf::from("."))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cli, run};
+    use clap::Parser;
+    use erc_core::Baseline;
+    use erc_core::baseline::BASELINE_VERSION;
+    use std::process::ExitCode;
+
+    /// One CON004 warning, "net has a single pin"
+    const NETLIST: &str = "../ecad_file_format/test_input/netlist_kicad_warnings.net";
+
+    fn check(args: &[&str]) -> ExitCode {
+        let cli = Cli::try_parse_from(["better_erc", "check"].iter().chain(args)).unwrap();
+        run(cli.command.unwrap())
+    }
+
+    #[test]
+    fn exit_code_depends_on_fail_on_threshold() {
+        assert_eq!(check(&[NETLIST]), ExitCode::SUCCESS);
+        assert_eq!(check(&[NETLIST, "--fail-on", "error"]), ExitCode::SUCCESS);
+        assert_eq!(check(&[NETLIST, "--fail-on", "warning"]), ExitCode::from(1));
+        assert_eq!(check(&[NETLIST, "--fail-on", "info"]), ExitCode::from(1));
+    }
+
+    #[test]
+    fn load_errors_exit_with_code_2() {
+        let missing = "../ecad_file_format/test_input/missing.net";
+        assert_eq!(check(&[missing]), ExitCode::from(2));
+        // a netlist is not a valid project config
+        assert_eq!(check(&[NETLIST, "--config", NETLIST]), ExitCode::from(2));
+    }
+
+    #[test]
+    fn baseline_is_compared_before_it_is_rewritten() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("baseline.json");
+        let empty = Baseline {
+            version: BASELINE_VERSION,
+            entries: vec![],
+        };
+        empty.save(&path).unwrap();
+        let path = path.to_str().unwrap();
+        let args = [NETLIST, "--fail-on", "warning", "--baseline", path];
+
+        // the warning is new against the empty baseline, then it is in the rewritten one
+        let write = [&args[..], &["--write-baseline", path]].concat();
+        assert_eq!(check(&write), ExitCode::from(1));
+        assert_eq!(Baseline::load(path.as_ref()).unwrap().entries.len(), 1);
+        assert_eq!(check(&args), ExitCode::SUCCESS);
+    }
+}
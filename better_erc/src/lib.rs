@@ -1,5 +1,7 @@
 #![warn(clippy::all, rust_2018_idioms)]
 
+#[cfg(not(target_arch = "wasm32"))]
+pub mod cli;
 mod context;
 mod main_window;
 pub mod prelude;
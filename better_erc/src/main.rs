@@ -1,9 +1,27 @@
 #![warn(clippy::all, rust_2018_idioms)]
-#![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // hide console window on Windows in release
+// hide console window on Windows in release, commands print nothing there, better_erc_cli does
+#![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 // When compiling natively:
 #[cfg(not(target_arch = "wasm32"))]
-fn main() -> eframe::Result {
+fn main() -> std::process::ExitCode {
+    use clap::Parser;
+
+    let cli = better_erc::cli::Cli::parse();
+    if let Some(command) = cli.command {
+        return better_erc::cli::run(command);
+    }
+    match run_gui() {
+        Ok(()) => std::process::ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn run_gui() -> eframe::Result {
     env_logger::init(); // Log to stderr (if you run with `RUST_LOG=debug`).
 
     let native_options = eframe::NativeOptions {
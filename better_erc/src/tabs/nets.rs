@@ -21,7 +21,7 @@ impl TabUi for Nets {
         let s = cx.blocking_read();
         for board in &s.boards {
             ScrollArea::vertical().show(ui, |ui| {
-                for (net_name, net) in &board.netlist.nets {
+                for (net_name, net) in board.netlist.nets() {
                     ui.horizontal(|ui| {
                         ui.label(
                             RichText::new(net_name.0.as_str())
@@ -1,8 +1,9 @@
 use crate::prelude::*;
 use crate::tabs::TabUi;
-use ecad_file_format::load_altium_netlist;
+use ecad_file_format::netlist::Netlist;
 use ecad_file_format::orcad_netlist::Rule::net;
 use ecad_file_format::pcb_assembly::PcbAssembly;
+use ecad_file_format::{load_altium_netlist, load_bom, load_kicad_schematic, load_netlist};
 use rfd::FileDialog;
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -10,6 +11,9 @@ use std::sync::Arc;
 #[derive(Default, Serialize, Deserialize)]
 pub struct PcbDataImport {
     source: Source,
+    /// Optional BOM loaded together with any of the sources
+    #[serde(default)]
+    bom_path: Option<PathBuf>,
     #[serde(skip)]
     transient: Option<Transient>,
 }
@@ -20,6 +24,10 @@ struct Transient {}
 #[strum_discriminants(derive(AsRefStr, EnumIter))]
 #[strum_discriminants(name(SourceKind))]
 enum Source {
+    /// Any supported netlist or schematic, format is detected from file contents
+    AutoDetect {
+        paths: Vec<PathBuf>,
+    },
     KiCadSchematic {
         path: Option<PathBuf>,
     },
@@ -43,6 +51,7 @@ impl Default for Source {
 impl Source {
     fn turn_into(&mut self, kind: SourceKind) {
         *self = match kind {
+            SourceKind::AutoDetect => Source::AutoDetect { paths: vec![] },
             SourceKind::KiCadSchematic => Source::KiCadSchematic { path: None },
             SourceKind::AltiumNetlist => Source::AltiumNetlist {
                 edif_path: None,
@@ -81,6 +90,9 @@ impl TabUi for PcbDataImport {
         }
         let mut changed = false;
         match &mut self.source {
+            Source::AutoDetect { paths } => {
+                changed |= file_paths("Files or folder", paths, ui);
+            }
             Source::KiCadSchematic { path } => {
                 changed |= file_path("root .kicad_sch", path, ui);
             }
@@ -101,10 +113,33 @@ impl TabUi for PcbDataImport {
                 changed |= file_path("Part list", part_list_path, ui);
             }
         }
+        changed |= file_path("BOM (optional)", &mut self.bom_path, ui);
         let reload = ui.button("Reload").clicked();
         if changed || reload {
             match &self.source {
-                Source::KiCadSchematic { .. } => {}
+                Source::AutoDetect { paths } => {
+                    if !paths.is_empty() {
+                        match load_netlist(paths) {
+                            Ok(detected) => {
+                                info!("Loaded {} from {:?}", detected.format, detected.files);
+                                add_board(cx, detected.netlist, &self.bom_path);
+                            }
+                            Err(e) => {
+                                error!("{e:?}");
+                            }
+                        }
+                    }
+                }
+                Source::KiCadSchematic { path } => {
+                    if let Some(path) = path {
+                        match load_kicad_schematic(path) {
+                            Ok(netlist) => add_board(cx, netlist, &self.bom_path),
+                            Err(e) => {
+                                error!("{e:?}");
+                            }
+                        }
+                    }
+                }
                 Source::AltiumNetlist {
                     edif_path,
                     wirelist_path,
@@ -112,14 +147,7 @@ impl TabUi for PcbDataImport {
                     if let (Some(edif_path), Some(wirelist_path)) = (edif_path, wirelist_path) {
                         let netlist = load_altium_netlist(edif_path, wirelist_path);
                         match netlist {
-                            Ok(netlist) => {
-                                cx.blocking_write().boards.push(PcbAssembly {
-                                    name: Arc::new("".to_string()),
-                                    netlist,
-                                    pnp: Default::default(),
-                                    bom: (),
-                                });
-                            }
+                            Ok(netlist) => add_board(cx, netlist, &self.bom_path),
                             Err(e) => {
                                 error!("{e:?}");
                             }
@@ -132,6 +160,39 @@ impl TabUi for PcbDataImport {
     }
 }
 
+/// Adds a board with the BOM if one was chosen, a BOM that fails to load is logged and skipped
+fn add_board(cx: &mut Context, netlist: Netlist, bom_path: &Option<PathBuf>) {
+    for warning in &netlist.warnings {
+        warn!("{warning}");
+    }
+    let bom = match bom_path {
+        Some(path) => match load_bom(path) {
+            Ok(bom) => {
+                for warning in &bom.warnings {
+                    warn!("{warning}");
+                }
+                info!(
+                    "Loaded {} BOM lines from {}",
+                    bom.lines.len(),
+                    path.display()
+                );
+                bom
+            }
+            Err(e) => {
+                error!("{}: {e:?}", path.display());
+                Default::default()
+            }
+        },
+        None => Default::default(),
+    };
+    cx.blocking_write().boards.push(PcbAssembly {
+        name: Arc::new("".to_string()),
+        netlist,
+        pnp: Default::default(),
+        bom,
+    });
+}
+
 fn file_path(label: &str, path: &mut Option<PathBuf>, ui: &mut Ui) -> bool {
     let mut changed = false;
     ui.horizontal(|ui| {
@@ -150,3 +211,32 @@ fn file_path(label: &str, path: &mut Option<PathBuf>, ui: &mut Ui) -> bool {
     });
     changed
 }
+
+fn file_paths(label: &str, paths: &mut Vec<PathBuf>, ui: &mut Ui) -> bool {
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        ui.monospace(label);
+        if paths.is_empty() {
+            ui.label("-");
+        } else {
+            let paths = paths
+                .iter()
+                .map(|p| p.display().to_string())
+                .collect::<Vec<_>>();
+            ui.label(paths.join(", "));
+        }
+        if ui.button("Choose files").clicked() {
+            if let Some(p) = FileDialog::new().pick_files() {
+                *paths = p;
+                changed = true;
+            }
+        }
+        if ui.button("Choose folder").clicked() {
+            if let Some(p) = FileDialog::new().pick_folder() {
+                *paths = vec![p];
+                changed = true;
+            }
+        }
+    });
+    changed
+}
@@ -0,0 +1,140 @@
+use criterion::{Criterion, criterion_group, criterion_main};
+use ecad_file_format::netlist::{
+    Component, ConnectivityIndex, LibName, LibPart, LibPartName, Net, Netlist, Node, Pin, PinMode,
+    PinType,
+};
+use ecad_file_format::{Designator, NetName, PinId, PinName};
+use std::collections::{HashMap, HashSet};
+use std::hint::black_box;
+
+const IC_PINS: usize = 64;
+const PASSIVES_PER_IC: usize = 9;
+
+/// Board with `ic_count` 64 pin ICs and 9 resistors around each of them (3000 parts for 300 ICs).
+/// Resistors connect signals of neighbouring ICs or pull them to ground.
+fn synthetic_netlist(ic_count: usize) -> Netlist {
+    let ic_key = (LibName("Synthetic".into()), LibPartName("IC".into()));
+    let r_key = (LibName("Synthetic".into()), LibPartName("R".into()));
+    let pin = |name: String, ty: PinType| Pin {
+        name: PinName(name),
+        default_mode: PinMode {
+            ty,
+            pull_up: None,
+            pull_down: None,
+            io_standard: None,
+        },
+        alternate_modes: Default::default(),
+        bank_name: None,
+        section_name: None,
+    };
+    let ic = LibPart {
+        pins: (1..=IC_PINS)
+            .map(|p| {
+                let ty = if p <= 4 {
+                    PinType::PowerIn
+                } else {
+                    PinType::DigitalIO
+                };
+                (PinId(p.to_string()), pin(format!("P{p}"), ty))
+            })
+            .collect(),
+        ..Default::default()
+    };
+    let r = LibPart {
+        pins: (1..=2)
+            .map(|p| (PinId(p.to_string()), pin(p.to_string(), PinType::Passive)))
+            .collect(),
+        ..Default::default()
+    };
+    let lib_parts = HashMap::from([(ic_key.clone(), ic), (r_key.clone(), r)]);
+
+    let mut components = HashMap::new();
+    let mut nets: HashMap<NetName, HashSet<Node>> = HashMap::new();
+    let mut connect = |net: String, designator: &str, pin: usize| {
+        nets.entry(NetName(net)).or_default().insert(Node {
+            designator: Designator(designator.into()),
+            pin_id: PinId(pin.to_string()),
+            location: Default::default(),
+        });
+    };
+    let component = |value: &str, lib_source: &(LibName, LibPartName)| Component {
+        value: value.into(),
+        description: String::new(),
+        lib_source: lib_source.clone(),
+        fields: Default::default(),
+        sections: vec![],
+        location: Default::default(),
+    };
+    for i in 0..ic_count {
+        let designator = format!("U{i}");
+        for p in 1..=IC_PINS {
+            let net = match p {
+                1 | 2 => "+3V3".to_string(),
+                3 | 4 => "GND".to_string(),
+                _ => format!("/U{i}/SIG{p}"),
+            };
+            connect(net, &designator, p);
+        }
+        components.insert(Designator(designator), component("MCU", &ic_key));
+        for k in 0..PASSIVES_PER_IC {
+            let designator = format!("R{}", i * PASSIVES_PER_IC + k);
+            connect(format!("/U{i}/SIG{}", 5 + k), &designator, 1);
+            let other_end = if k % 3 == 0 {
+                "GND".to_string()
+            } else {
+                format!("/U{}/SIG{}", (i + 1) % ic_count, 5 + k)
+            };
+            connect(other_end, &designator, 2);
+            components.insert(Designator(designator), component("10k", &r_key));
+        }
+    }
+    let nets = nets
+        .into_iter()
+        .map(|(name, nodes)| {
+            let net = Net {
+                nodes,
+                properties: Default::default(),
+            };
+            (name, net)
+        })
+        .collect();
+    Netlist::new(lib_parts, nets, components, vec![])
+}
+
+fn connectivity(c: &mut Criterion) {
+    let netlist = synthetic_netlist(300);
+    let ic = Designator("U150".into());
+    let resistor = Designator("R1351".into());
+    let signal = NetName("/U150/SIG6".into());
+
+    c.bench_function("build connectivity index", |b| {
+        b.iter(|| ConnectivityIndex::new(black_box(netlist.nets())))
+    });
+    c.bench_function("pin_net", |b| {
+        b.iter(|| netlist.pin_net(black_box(&ic), black_box(&PinId("40".into()))))
+    });
+    c.bench_function("part_nets", |b| {
+        b.iter(|| netlist.part_nets(black_box(&ic)))
+    });
+    c.bench_function("is_connected", |b| {
+        b.iter(|| netlist.is_connected(black_box(&resistor), black_box(&signal)))
+    });
+    c.bench_function("are_parts_connected", |b| {
+        b.iter(|| netlist.are_parts_connected(black_box(&ic), black_box(&resistor)))
+    });
+    c.bench_function("find_nets_with_pin_types", |b| {
+        b.iter(|| netlist.find_nets_with_pin_types(black_box(&[PinType::PowerIn])))
+    });
+    c.bench_function("find_net_chains", |b| {
+        b.iter(|| {
+            netlist.find_net_chains(
+                black_box(&signal),
+                &[Designator::is_resistor],
+                black_box(&NetName("/U151/SIG6".into())),
+            )
+        })
+    });
+}
+
+criterion_group!(benches, connectivity);
+criterion_main!(benches);
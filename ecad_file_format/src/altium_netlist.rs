@@ -1,13 +1,14 @@
 use crate::Designator;
-use crate::edif_netlist::load_edif_netlist;
+use crate::edif_netlist::{load_edif_netlist, write_edif_netlist};
 use crate::netlist::Netlist;
-use crate::wirelist::load_wirelist_netlist;
+use crate::wirelist::{load_wirelist_netlist, write_wirelist_netlist};
 use anyhow::Result;
 use std::path::Path;
 
 pub fn load_altium_netlist(edif_path: &Path, wirelist_path: &Path) -> Result<Netlist> {
     let mut edif_netlist = load_edif_netlist(edif_path)?;
     let wirelsit_netlist = load_wirelist_netlist(wirelist_path)?;
+    edif_netlist.warnings.extend(wirelsit_netlist.warnings);
 
     for ((_, lib_part_name), wirelist_lib_part) in wirelsit_netlist.lib_parts {
         let Some(edif_component) = edif_netlist.components.get(&Designator(lib_part_name.0)) else {
@@ -26,3 +27,14 @@ pub fn load_altium_netlist(edif_path: &Path, wirelist_path: &Path) -> Result<Net
 
     Ok(edif_netlist)
 }
+
+/// Writes the same pair of files that load_altium_netlist reads: EDIF for components and
+/// connectivity and wirelist for pin names and types
+pub fn write_altium_netlist(
+    netlist: &Netlist,
+    edif_path: &Path,
+    wirelist_path: &Path,
+) -> Result<()> {
+    write_edif_netlist(netlist, edif_path)?;
+    write_wirelist_netlist(netlist, wirelist_path)
+}
@@ -0,0 +1,264 @@
+use crate::Designator;
+use crate::csv_util::{
+    BOM_DESCRIPTION_COLUMNS, BOM_DESIGNATOR_COLUMNS, BOM_DNP_COLUMNS, BOM_FITTED_COLUMNS,
+    BOM_FOOTPRINT_COLUMNS, BOM_MANUFACTURER_COLUMNS, BOM_MPN_COLUMNS, BOM_QUANTITY_COLUMNS,
+    BOM_VALUE_COLUMNS, MINIMUM_BOM_COLUMNS_REQUIRED, determine_separator, find_column_idx,
+    find_header_row, possible_bom_column_names,
+};
+use crate::parse_error::{ParseError, ParseErrorKind, Span};
+use anyhow::Result;
+use regex::Regex;
+use std::path::Path;
+use std::sync::OnceLock;
+
+#[derive(Debug, Default)]
+pub struct Bom {
+    pub lines: Vec<BomLine>,
+    /// Records that were skipped
+    pub warnings: Vec<ParseError>,
+}
+
+/// One BOM row, usually a group of identical components
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BomLine {
+    pub designators: Vec<Designator>,
+    /// Quantity as written in the BOM, or number of designators if there is no quantity column
+    pub quantity: u32,
+    pub value: Option<String>,
+    pub mpn: Option<String>,
+    pub manufacturer: Option<String>,
+    pub footprint: Option<String>,
+    pub description: Option<String>,
+    /// Do not populate / do not install
+    pub dnp: bool,
+}
+
+impl Bom {
+    pub fn line(&self, designator: &Designator) -> Option<&BomLine> {
+        self.lines
+            .iter()
+            .find(|line| line.designators.contains(designator))
+    }
+
+    pub fn designators(&self) -> impl Iterator<Item = &Designator> {
+        self.lines.iter().flat_map(|line| line.designators.iter())
+    }
+}
+
+/// Loads CSV or TSV BOM as exported by KiCad, Altium or OrCAD, header row is found by column names
+pub fn load_bom(path: &Path) -> Result<Bom> {
+    let (header_idx, header) = find_header_row(
+        MINIMUM_BOM_COLUMNS_REQUIRED,
+        &possible_bom_column_names(),
+        path,
+    )
+    .ok_or_else(|| {
+        ParseError::new(
+            path,
+            None,
+            ParseErrorKind::UnexpectedContent {
+                expected: "BOM column header".into(),
+                found: "no row with known column names".into(),
+            },
+        )
+    })?;
+    let separator =
+        determine_separator(path).ok_or_else(|| ParseError::io(path, "cannot open file"))?;
+    let reader = csv::ReaderBuilder::new()
+        .delimiter(separator)
+        .has_headers(false)
+        .flexible(true)
+        .from_path(path)
+        .map_err(|e| ParseError::io(path, e))?;
+
+    let designator_idx = find_column_idx(&header, &BOM_DESIGNATOR_COLUMNS).ok_or_else(|| {
+        ParseError::new(
+            path,
+            Some(Span::at((header_idx + 1, 1))),
+            ParseErrorKind::UnexpectedContent {
+                expected: "designator column".into(),
+                found: header.join(", "),
+            },
+        )
+    })?;
+    let quantity_idx = find_column_idx(&header, &BOM_QUANTITY_COLUMNS);
+    let value_idx = find_column_idx(&header, &BOM_VALUE_COLUMNS);
+    let mpn_idx = find_column_idx(&header, &BOM_MPN_COLUMNS);
+    let manufacturer_idx = find_column_idx(&header, &BOM_MANUFACTURER_COLUMNS);
+    let footprint_idx = find_column_idx(&header, &BOM_FOOTPRINT_COLUMNS);
+    let description_idx = find_column_idx(&header, &BOM_DESCRIPTION_COLUMNS);
+    let dnp_idx = find_column_idx(&header, &BOM_DNP_COLUMNS);
+    let fitted_idx = find_column_idx(&header, &BOM_FITTED_COLUMNS);
+
+    let mut lines = vec![];
+    let mut warnings = vec![];
+    let records = reader.into_records().skip(header_idx + 1);
+    for record in records {
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                let span = e.position().map(|p| Span::at((p.line() as usize, 1)));
+                warnings.push(ParseError::new(
+                    path,
+                    span,
+                    ParseErrorKind::UnreadableRecord(e.to_string()),
+                ));
+                continue;
+            }
+        };
+        let values = record.iter().map(|v| v.trim()).collect::<Vec<&str>>();
+        let get = |idx: Option<usize>| {
+            idx.and_then(|idx| values.get(idx))
+                .filter(|v| !v.is_empty())
+                .map(|v| v.to_string())
+        };
+        let Some(designators) = get(Some(designator_idx)) else {
+            // empty rows are not records, rows with content but without designator are reported
+            if values.iter().any(|v| !v.is_empty()) {
+                let line = record.position().map(|p| p.line() as usize).unwrap_or(0);
+                warnings.push(ParseError::new(
+                    path,
+                    Some(Span::at((line, 1))),
+                    ParseErrorKind::EmptyDesignator {
+                        value: get(value_idx).unwrap_or_default(),
+                    },
+                ));
+            }
+            continue;
+        };
+        let designators = expand_designators(&designators);
+        let quantity = match get(quantity_idx) {
+            Some(quantity) => quantity.parse::<u32>().map_err(|_| {
+                let line = record.position().map(|p| p.line() as usize).unwrap_or(0);
+                ParseError::new(
+                    path,
+                    Some(Span::at((line, 1))),
+                    ParseErrorKind::UnexpectedContent {
+                        expected: "quantity".into(),
+                        found: quantity.clone(),
+                    },
+                )
+            })?,
+            None => designators.len() as u32,
+        };
+        let dnp = get(dnp_idx).is_some_and(|v| is_dnp_flag(&v))
+            || get(fitted_idx).is_some_and(|v| v.eq_ignore_ascii_case("Not Fitted"));
+        lines.push(BomLine {
+            designators,
+            quantity,
+            value: get(value_idx),
+            mpn: get(mpn_idx),
+            manufacturer: get(manufacturer_idx),
+            footprint: get(footprint_idx),
+            description: get(description_idx),
+            dnp,
+        });
+    }
+    Ok(Bom { lines, warnings })
+}
+
+/// Splits designator list on commas, semicolons and whitespace and expands ranges like `R1-R4`
+/// or `R1-4`. Anything that does not look like a range (`U1-A`) is kept as is.
+pub fn expand_designators(list: &str) -> Vec<Designator> {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    let re = RE.get_or_init(|| Regex::new(r"^([A-Za-z_#]*)(\d+)$").unwrap());
+    let mut designators = vec![];
+    for item in list
+        .split(|c: char| c == ',' || c == ';' || c.is_whitespace())
+        .filter(|s| !s.is_empty())
+    {
+        if let Some((from, to)) = item.split_once('-') {
+            if let (Some(from), Some(to)) = (re.captures(from), re.captures(to)) {
+                let prefix = &from[1];
+                let to_prefix = &to[1];
+                if let (Ok(start), Ok(end)) = (from[2].parse::<u32>(), to[2].parse::<u32>()) {
+                    if (to_prefix.is_empty() || to_prefix == prefix) && start <= end {
+                        for n in start..=end {
+                            designators.push(Designator(format!("{prefix}{n}")));
+                        }
+                        continue;
+                    }
+                }
+            }
+        }
+        designators.push(Designator(item.to_string()));
+    }
+    designators
+}
+
+fn is_dnp_flag(value: &str) -> bool {
+    ["DNP", "DNI", "Yes", "Y", "X", "1", "True"]
+        .iter()
+        .any(|flag| value.eq_ignore_ascii_case(flag))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_expand_designator_ranges() {
+        assert_eq!(
+            expand_designators("R1-R4, R7"),
+            ["R1", "R2", "R3", "R4", "R7"].map(|d| Designator(d.into()))
+        );
+        assert_eq!(
+            expand_designators("C10-12;U1-A"),
+            ["C10", "C11", "C12", "U1-A"].map(|d| Designator(d.into()))
+        );
+    }
+
+    #[test]
+    fn can_read_bom_kicad() {
+        let bom = load_bom(Path::new("test_input/bom_kicad.csv")).unwrap();
+        assert_eq!(bom.lines.len(), 4);
+        let line = bom.line(&Designator("C5".into())).unwrap();
+        assert_eq!(line.quantity, 3);
+        assert_eq!(line.value.as_deref(), Some("100n"));
+        assert_eq!(line.mpn.as_deref(), Some("CL05B104KO5NNNC"));
+        assert!(!line.dnp);
+        assert!(bom.line(&Designator("C3".into())).unwrap().dnp);
+    }
+
+    #[test]
+    fn can_read_bom_altium() {
+        let bom = load_bom(Path::new("test_input/bom_altium.csv")).unwrap();
+        assert_eq!(bom.lines.len(), 3);
+        let line = bom.line(&Designator("R3".into())).unwrap();
+        assert_eq!(line.designators.len(), 5);
+        assert_eq!(line.quantity, 5);
+        assert_eq!(line.value.as_deref(), Some("10k"));
+        assert_eq!(line.manufacturer.as_deref(), Some("Yageo"));
+        assert_eq!(line.footprint.as_deref(), Some("RESC1005X40N"));
+    }
+
+    #[test]
+    fn can_read_bom_orcad() {
+        let bom = load_bom(Path::new("test_input/bom_orcad.txt")).unwrap();
+        assert_eq!(bom.lines.len(), 3);
+        let line = bom.line(&Designator("R11".into())).unwrap();
+        assert_eq!(line.quantity, 3);
+        assert_eq!(line.value.as_deref(), Some("1K"));
+        assert_eq!(line.mpn.as_deref(), Some("RC0402FR-071KL"));
+        assert_eq!(bom.designators().count(), 6);
+    }
+
+    #[test]
+    fn skipped_records_are_collected_as_warnings() {
+        let bom = load_bom(Path::new("test_input/bom_skipped_rows.csv")).unwrap();
+        assert_eq!(bom.lines.len(), 2);
+        let warnings = bom
+            .warnings
+            .iter()
+            .map(|w| (w.span.map(|s| s.start), w.kind.clone()))
+            .collect::<Vec<_>>();
+        assert!(matches!(
+            warnings.as_slice(),
+            [
+                (Some((3, 1)), ParseErrorKind::EmptyDesignator { value }),
+                (Some((4, 1)), ParseErrorKind::UnreadableRecord(_)),
+            ] if value == "10u"
+        ));
+        assert!(bom.warnings.iter().all(|w| !w.is_fatal()));
+    }
+}
@@ -21,6 +21,61 @@ pub const POSSIBLE_PNP_COLUMN_NAMES: [&str; 13] = [
 /// Designator, X, Y, A, Side
 pub const MINIMUM_PNP_COLUMNS_REQUIRED: usize = 5;
 
+pub const BOM_DESIGNATOR_COLUMNS: [&str; 6] = [
+    "Designator",
+    "Designators",
+    "Reference",
+    "References",
+    "Ref",
+    "RefDes",
+];
+pub const BOM_QUANTITY_COLUMNS: [&str; 4] = ["Quantity", "Qty", "QTY", "Qnty"];
+pub const BOM_VALUE_COLUMNS: [&str; 4] = ["Value", "Val", "Comment", "Part"];
+pub const BOM_MPN_COLUMNS: [&str; 8] = [
+    "MPN",
+    "Mpn",
+    "Mpn1",
+    "Manufacturer Part Number",
+    "Manufacturer_Part_Number",
+    "Manufacturer Part Number 1",
+    "Mfr. Part Number",
+    "Part Number",
+];
+pub const BOM_MANUFACTURER_COLUMNS: [&str; 6] = [
+    "Manufacturer",
+    "Manufacturer 1",
+    "Manufacturer_Name",
+    "Manufacturer Name",
+    "Mfr",
+    "MFR",
+];
+pub const BOM_FOOTPRINT_COLUMNS: [&str; 3] = ["Footprint", "PCB Footprint", "Package"];
+pub const BOM_DESCRIPTION_COLUMNS: [&str; 1] = ["Description"];
+pub const BOM_DNP_COLUMNS: [&str; 4] = ["DNP", "DNI", "Do Not Populate", "Do not populate"];
+pub const BOM_FITTED_COLUMNS: [&str; 1] = ["Fitted"];
+/// Not read, but still help to recognize the header row
+pub const BOM_OTHER_COLUMNS: [&str; 3] = ["LibRef", "Item", "Datasheet"];
+
+/// All the BOM column names, for `find_header_row`
+pub fn possible_bom_column_names() -> Vec<&'static str> {
+    [
+        &BOM_DESIGNATOR_COLUMNS[..],
+        &BOM_QUANTITY_COLUMNS,
+        &BOM_VALUE_COLUMNS,
+        &BOM_MPN_COLUMNS,
+        &BOM_MANUFACTURER_COLUMNS,
+        &BOM_FOOTPRINT_COLUMNS,
+        &BOM_DESCRIPTION_COLUMNS,
+        &BOM_DNP_COLUMNS,
+        &BOM_FITTED_COLUMNS,
+        &BOM_OTHER_COLUMNS,
+    ]
+    .concat()
+}
+
+/// Designator and at least one of quantity, value, MPN, ...
+pub const MINIMUM_BOM_COLUMNS_REQUIRED: usize = 2;
+
 // TODO: Switch to BufReader or String
 pub fn determine_separator(path: &Path) -> Option<u8> {
     let file = match File::open(path) {
@@ -84,6 +139,15 @@ pub fn find_header_row(
     None
 }
 
+/// Index of the first column whose name is one of the synonyms
+pub fn find_column_idx(columns: &[String], synonyms: &[&str]) -> Option<usize> {
+    columns
+        .iter()
+        .enumerate()
+        .find(|(_idx, c)| synonyms.contains(&c.as_str()))
+        .map(|(idx, _)| idx)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,404 @@
+use crate::altium_netlist::load_altium_netlist;
+use crate::edif_netlist::load_edif_netlist;
+use crate::kicad_netlist::load_kicad_netlist;
+use crate::kicad_schematic::load_kicad_schematic;
+use crate::netlist::Netlist;
+use crate::orcad_netlist::load_orcad_netlist_files;
+use crate::wirelist::load_wirelist_netlist;
+use anyhow::{Error, Result};
+use regex::Regex;
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use std::fs::{File, read_dir, read_to_string};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Kind of file as determined by its first bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FileKind {
+    /// `(kicad_sch ...`
+    KiCadSchematic,
+    /// `(export ...`
+    KiCadNetlist,
+    /// `(edif ...`
+    Edif,
+    /// `Wire List` banner
+    Wirelist,
+    /// `FILE_TYPE = EXPANDEDNETLIST;` (pstxnet.dat)
+    OrcadNetlist,
+    /// `FILE_TYPE = EXPANDEDPARTLIST;` (pstxprt.dat)
+    OrcadPartList,
+    /// `FILE_TYPE=LIBRARY_PARTS;` (pstchip.dat)
+    OrcadLibraryParts,
+}
+
+/// Format that load_netlist chose
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NetlistFormat {
+    KiCadSchematic,
+    KiCadNetlist,
+    /// EDIF with pin names and types from the wirelist
+    Altium,
+    /// EDIF without wirelist
+    Edif,
+    /// Wirelist without EDIF
+    Wirelist,
+    /// pstxnet.dat, pstxprt.dat and pstchip.dat
+    Orcad,
+}
+
+/// Netlist together with the format it was loaded as and all the files that were read
+#[derive(Debug)]
+pub struct DetectedNetlist {
+    pub netlist: Netlist,
+    pub format: NetlistFormat,
+    pub files: Vec<PathBuf>,
+}
+
+/// Loads netlist from a file, a set of files or a directory, choosing loader based on file contents.
+/// Files that are not netlists or schematics (BOM, PnP, ...) are skipped.
+///
+/// Companion files (wirelist for EDIF, part list and library parts for OrCAD) are taken from the
+/// given paths first and then searched for next to the main file. When several formats are present,
+/// KiCad schematic is preferred, then KiCad netlist, Altium and OrCAD.
+pub fn load_netlist<P: AsRef<Path>>(paths: &[P]) -> Result<DetectedNetlist> {
+    let mut files = vec![];
+    for path in paths {
+        let path = path.as_ref();
+        if path.is_dir() {
+            for file in dir_files(path)? {
+                if let Ok(Some(kind)) = sniff_file_kind(&file) {
+                    files.push((file, kind));
+                }
+            }
+        } else if let Some(kind) = sniff_file_kind(path)? {
+            files.push((path.to_path_buf(), kind));
+        }
+    }
+    let of_kind = |kind: FileKind| {
+        files
+            .iter()
+            .filter(|(_, k)| *k == kind)
+            .map(|(path, _)| path.clone())
+            .collect::<Vec<_>>()
+    };
+
+    let schematics = of_kind(FileKind::KiCadSchematic);
+    if !schematics.is_empty() {
+        let root = kicad_root_schematic(&schematics)?;
+        return Ok(DetectedNetlist {
+            netlist: load_kicad_schematic(&root)?,
+            format: NetlistFormat::KiCadSchematic,
+            files: vec![root],
+        });
+    }
+
+    let kicad_netlists = of_kind(FileKind::KiCadNetlist);
+    if !kicad_netlists.is_empty() {
+        let path = single(kicad_netlists, "KiCad netlist")?;
+        return Ok(DetectedNetlist {
+            netlist: load_kicad_netlist(&path)?,
+            format: NetlistFormat::KiCadNetlist,
+            files: vec![path],
+        });
+    }
+
+    let edifs = of_kind(FileKind::Edif);
+    let wirelists = of_kind(FileKind::Wirelist);
+    if !edifs.is_empty() || !wirelists.is_empty() {
+        let (edif, wirelist) = if edifs.is_empty() {
+            let wirelist = single(wirelists, "wirelist")?;
+            let edif = find_companion(&wirelist, FileKind::Edif, &files)?;
+            (edif, Some(wirelist))
+        } else {
+            let edif = single(edifs, "EDIF netlist")?;
+            let wirelist = find_companion(&edif, FileKind::Wirelist, &files)?;
+            (Some(edif), wirelist)
+        };
+        return match (edif, wirelist) {
+            (Some(edif), Some(wirelist)) => Ok(DetectedNetlist {
+                netlist: load_altium_netlist(&edif, &wirelist)?,
+                format: NetlistFormat::Altium,
+                files: vec![edif, wirelist],
+            }),
+            (Some(edif), None) => Ok(DetectedNetlist {
+                netlist: load_edif_netlist(&edif)?,
+                format: NetlistFormat::Edif,
+                files: vec![edif],
+            }),
+            (None, Some(wirelist)) => Ok(DetectedNetlist {
+                netlist: load_wirelist_netlist(&wirelist)?,
+                format: NetlistFormat::Wirelist,
+                files: vec![wirelist],
+            }),
+            (None, None) => unreachable!(),
+        };
+    }
+
+    for kind in [
+        FileKind::OrcadNetlist,
+        FileKind::OrcadPartList,
+        FileKind::OrcadLibraryParts,
+    ] {
+        let orcad_files = of_kind(kind);
+        if orcad_files.is_empty() {
+            continue;
+        }
+        let anchor = single(orcad_files, "OrCAD file")?;
+        let find = |kind: FileKind, what: &str| -> Result<PathBuf> {
+            find_companion(&anchor, kind, &files)?.ok_or_else(|| {
+                Error::msg(format!("no OrCAD {what} found for {}", anchor.display()))
+            })
+        };
+        let pstxnet = find(FileKind::OrcadNetlist, "netlist (pstxnet.dat)")?;
+        let pstxprt = find(FileKind::OrcadPartList, "part list (pstxprt.dat)")?;
+        let pstchip = find(FileKind::OrcadLibraryParts, "library parts (pstchip.dat)")?;
+        return Ok(DetectedNetlist {
+            netlist: load_orcad_netlist_files(&pstxnet, &pstxprt, &pstchip)?,
+            format: NetlistFormat::Orcad,
+            files: vec![pstxnet, pstxprt, pstchip],
+        });
+    }
+
+    let paths = paths
+        .iter()
+        .map(|p| p.as_ref().display().to_string())
+        .collect::<Vec<_>>();
+    Err(Error::msg(format!(
+        "no netlist or schematic found in {}",
+        paths.join(", ")
+    )))
+}
+
+/// Determines file kind by looking at the first few kilobytes, None if the format is not known
+pub fn sniff_file_kind(path: &Path) -> Result<Option<FileKind>> {
+    let mut head = vec![];
+    File::open(path)?.take(4096).read_to_end(&mut head)?;
+    Ok(sniff(&String::from_utf8_lossy(&head)))
+}
+
+fn sniff(head: &str) -> Option<FileKind> {
+    let head = head.trim_start_matches('\u{feff}').trim_start();
+    if head.starts_with("(kicad_sch") {
+        Some(FileKind::KiCadSchematic)
+    } else if head.starts_with("(export") {
+        Some(FileKind::KiCadNetlist)
+    } else if head.starts_with("(edif") {
+        Some(FileKind::Edif)
+    } else if head.starts_with("Wire List") {
+        Some(FileKind::Wirelist)
+    } else {
+        let first_line = head.lines().next()?;
+        let first_line = first_line
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect::<String>();
+        match first_line.strip_prefix("FILE_TYPE=")? {
+            "EXPANDEDNETLIST;" => Some(FileKind::OrcadNetlist),
+            "EXPANDEDPARTLIST;" => Some(FileKind::OrcadPartList),
+            "LIBRARY_PARTS;" => Some(FileKind::OrcadLibraryParts),
+            _ => None,
+        }
+    }
+}
+
+fn dir_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    for entry in read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file() {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn single(mut paths: Vec<PathBuf>, what: &str) -> Result<PathBuf> {
+    if paths.len() == 1 {
+        Ok(paths.remove(0))
+    } else {
+        let paths = paths
+            .iter()
+            .map(|p| p.display().to_string())
+            .collect::<Vec<_>>();
+        Err(Error::msg(format!(
+            "more than one {what} given, choose one of: {}",
+            paths.join(", ")
+        )))
+    }
+}
+
+/// Finds file of the given kind among the given files or in the same directory as anchor,
+/// preferring the one whose name shares the longest prefix with the anchor's name
+/// (`board.NET.EDF` and `board.NET`, `pstxnet.dat` and `pstxprt.dat`)
+fn find_companion(
+    anchor: &Path,
+    kind: FileKind,
+    files: &[(PathBuf, FileKind)],
+) -> Result<Option<PathBuf>> {
+    let mut candidates = files
+        .iter()
+        .filter(|(_, k)| *k == kind)
+        .map(|(path, _)| path.clone())
+        .collect::<Vec<_>>();
+    if candidates.is_empty() {
+        let dir = match anchor.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        for file in dir_files(dir)? {
+            if let Ok(Some(k)) = sniff_file_kind(&file) {
+                if k == kind {
+                    candidates.push(file);
+                }
+            }
+        }
+    }
+    let anchor_name = file_name(anchor);
+    Ok(candidates.into_iter().max_by_key(|path| {
+        anchor_name
+            .chars()
+            .zip(file_name(path).chars())
+            .take_while(|(a, b)| a.eq_ignore_ascii_case(b))
+            .count()
+    }))
+}
+
+/// Root sheet is the one not referenced by other sheets, or the one next to a .kicad_pro file
+fn kicad_root_schematic(schematics: &[PathBuf]) -> Result<PathBuf> {
+    if schematics.len() == 1 {
+        return Ok(schematics[0].clone());
+    }
+    // KiCad 6 used "Sheet file"
+    let sheet_file = Regex::new(r#"\(property\s+"Sheet ?file"\s+"([^"]+)""#)?;
+    let mut referenced = HashSet::new();
+    for schematic in schematics {
+        let contents = read_to_string(schematic)?;
+        for captures in sheet_file.captures_iter(&contents) {
+            referenced.insert(file_name(Path::new(&captures[1])));
+        }
+    }
+    let roots = schematics
+        .iter()
+        .filter(|path| !referenced.contains(&file_name(path)))
+        .cloned()
+        .collect::<Vec<_>>();
+    if roots.len() == 1 {
+        return Ok(roots[0].clone());
+    }
+    let candidates = if roots.is_empty() { schematics } else { &roots };
+    let with_project = candidates
+        .iter()
+        .filter(|path| path.with_extension("kicad_pro").exists())
+        .cloned()
+        .collect::<Vec<_>>();
+    single(
+        if with_project.is_empty() {
+            candidates.to_vec()
+        } else {
+            with_project
+        },
+        "root KiCad schematic",
+    )
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+impl Display for NetlistFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            NetlistFormat::KiCadSchematic => "KiCad schematic",
+            NetlistFormat::KiCadNetlist => "KiCad netlist",
+            NetlistFormat::Altium => "Altium EDIF + wirelist",
+            NetlistFormat::Edif => "EDIF",
+            NetlistFormat::Wirelist => "Altium wirelist",
+            NetlistFormat::Orcad => "OrCAD PST netlist",
+        };
+        write!(f, "{name}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_sniff_file_kinds() {
+        let kinds = [
+            ("test_input/netlist_kicad.net", FileKind::KiCadNetlist),
+            ("test_input/netlist_altium_edif.edf", FileKind::Edif),
+            ("test_input/netlist_altium_wirelist.net", FileKind::Wirelist),
+            (
+                "test_input/netlist_orcad_pstxnet.dat",
+                FileKind::OrcadNetlist,
+            ),
+            (
+                "test_input/netlist_orcad_pstxprt.dat",
+                FileKind::OrcadPartList,
+            ),
+            (
+                "test_input/netlist_orcad_pstchip.dat",
+                FileKind::OrcadLibraryParts,
+            ),
+            (
+                "../test_schematics/sources/i2c_segments.kicad_sch",
+                FileKind::KiCadSchematic,
+            ),
+        ];
+        for (path, kind) in kinds {
+            assert_eq!(
+                sniff_file_kind(Path::new(path)).unwrap(),
+                Some(kind),
+                "{path}"
+            );
+        }
+        assert_eq!(
+            sniff_file_kind(Path::new("test_input/pnp_kicad.csv")).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn finds_altium_wirelist_next_to_edif() {
+        let detected = load_netlist(&["test_input/netlist_altium_edif.edf"]).unwrap();
+        assert_eq!(detected.format, NetlistFormat::Altium);
+        assert_eq!(
+            detected.files,
+            [
+                PathBuf::from("test_input/netlist_altium_edif.edf"),
+                PathBuf::from("test_input/netlist_altium_wirelist.net")
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_orcad_companions_from_any_file() {
+        let detected = load_netlist(&["test_input/netlist_orcad_pstxprt.dat"]).unwrap();
+        assert_eq!(detected.format, NetlistFormat::Orcad);
+        assert_eq!(
+            detected.files,
+            [
+                PathBuf::from("test_input/netlist_orcad_pstxnet.dat"),
+                PathBuf::from("test_input/netlist_orcad_pstxprt.dat"),
+                PathBuf::from("test_input/netlist_orcad_pstchip.dat")
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_root_kicad_schematic_in_directory() {
+        let detected = load_netlist(&["../test_schematics/sources"]).unwrap();
+        assert_eq!(detected.format, NetlistFormat::KiCadSchematic);
+        assert_eq!(
+            detected.files,
+            [PathBuf::from(
+                "../test_schematics/sources/test_schematics.kicad_sch"
+            )]
+        );
+    }
+}
@@ -1,13 +1,17 @@
 use crate::netlist::{
     Component, LibName, LibPart, LibPartName, Net, Netlist, Node, Pin, PinMode, PinType,
+    SourceLocation,
 };
+use crate::parse_error::{ParseError, ParseErrorKind};
 use crate::text_util::read_with_unknown_encoding;
 use crate::{Designator, NetName, PinId, PinName};
-use anyhow::{Error, Result};
+use anyhow::Result;
 use pest::Parser;
 use pest::iterators::Pair;
 use pest_derive::Parser;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::{self, Write};
+use std::fs::write;
 use std::path::Path;
 
 #[derive(Parser)]
@@ -15,17 +19,20 @@ use std::path::Path;
 struct EdifParser;
 
 pub fn load_edif_netlist(path: &Path) -> Result<Netlist> {
-    let contents = read_with_unknown_encoding(path)?;
+    let contents = read_with_unknown_encoding(path).map_err(|e| ParseError::io(path, e))?;
 
     let mut pairs = match EdifParser::parse(Rule::file, &contents) {
         Ok(pairs) => pairs,
-        Err(e) => return Err(Error::msg(format!("{e}"))),
+        Err(e) => return Err(ParseError::from_pest(path, e).into()),
     };
+    let mut warnings = vec![];
     let mut lib_parts = HashMap::new();
     let mut nets = HashMap::new();
     let mut components = HashMap::new();
-    // let mut symbol_rename_map: HashMap<&str, &str> = HashMap::new();
-    // let mut symbol_pin_rename_map: HashMap<&str, HashMap<&str, &str>> = HashMap::new();
+    // identifiers are sanitized names, the original ones are in (rename identifier "name")
+    let mut cell_names: HashMap<&str, &str> = HashMap::new();
+    let mut port_names: HashMap<&str, HashMap<&str, &str>> = HashMap::new();
+    let mut instance_cells: HashMap<&str, &str> = HashMap::new();
     for p in pairs.next().unwrap().into_inner() {
         match p.as_rule() {
             Rule::board_name => {}
@@ -39,19 +46,28 @@ pub fn load_edif_netlist(path: &Path) -> Result<Netlist> {
                         let _technology = library.next().unwrap();
                         for cell in library {
                             let mut cell = cell.into_inner();
-                            let lib_part_name = symbol_or_rename_get(cell.next().unwrap()).0;
+                            let (cell_identifier, lib_part_name) =
+                                symbol_or_rename_get(cell.next().unwrap());
                             let _cell_type = cell.next().unwrap();
                             let view = cell.next().unwrap();
                             let mut view = view.into_inner();
                             let _view_name = view.next().unwrap();
                             let _view_type = view.next().unwrap();
                             if lib_name == "COMPONENT_LIB" {
+                                cell_names.insert(cell_identifier, lib_part_name);
+                                let ports = port_names.entry(cell_identifier).or_default();
                                 let view_interface = view.next().unwrap();
                                 let mut pins = HashMap::new();
                                 for port in view_interface.into_inner() {
                                     let mut port = port.into_inner();
-                                    let pin_id = symbol_or_rename_get(port.next().unwrap()).0;
-                                    let pin_id = pin_id.strip_prefix('&').unwrap_or(pin_id);
+                                    let (port_identifier, pin_id) =
+                                        symbol_or_rename_get(port.next().unwrap());
+                                    let pin_id = if port_identifier == pin_id {
+                                        pin_id.strip_prefix('&').unwrap_or(pin_id)
+                                    } else {
+                                        pin_id
+                                    };
+                                    ports.insert(port_identifier, pin_id);
                                     let direction = port.next().unwrap().as_str();
                                     let pin_ty = if direction == "INOUT" {
                                         PinType::DigitalIO
@@ -94,14 +110,23 @@ pub fn load_edif_netlist(path: &Path) -> Result<Netlist> {
                                 );
                             } else if lib_name == "SHEET_LIB" {
                                 let _view_interface = view.next().unwrap();
-                                let view_contents = view.next().unwrap().into_inner();
+                                // sheets without components have no contents
+                                let Some(view_contents) = view.next() else {
+                                    continue;
+                                };
+                                let view_contents = view_contents.into_inner();
                                 for instance_or_net in view_contents {
                                     if instance_or_net.as_rule() == Rule::instance {
                                         let mut instance = instance_or_net.into_inner();
                                         let designator = instance.next().unwrap().as_str();
                                         let view_ref = instance.next().unwrap();
-                                        let lib_part_name =
+                                        let cell_identifier =
                                             view_ref.into_inner().skip(1).next().unwrap().as_str();
+                                        instance_cells.insert(designator, cell_identifier);
+                                        let lib_part_name = cell_names
+                                            .get(cell_identifier)
+                                            .copied()
+                                            .unwrap_or(cell_identifier);
                                         let mut fields = HashMap::new();
                                         for property in instance {
                                             let mut property = property.into_inner();
@@ -150,21 +175,33 @@ pub fn load_edif_netlist(path: &Path) -> Result<Netlist> {
                                                 lib_source: k,
                                                 fields,
                                                 sections: vec![],
+                                                location: SourceLocation::default(),
                                             },
                                         );
                                     } else if instance_or_net.as_rule() == Rule::net {
+                                        let net_pair = instance_or_net.clone();
                                         let mut net = instance_or_net.into_inner();
                                         let net_name = symbol_or_rename_get(net.next().unwrap()).1;
                                         let joined = net.next().unwrap();
                                         let mut nodes = HashSet::new();
                                         for port_ref in joined.into_inner() {
                                             let mut port_ref = port_ref.into_inner();
-                                            let pin_id = port_ref.next().unwrap().as_str();
-                                            let pin_id = pin_id.strip_prefix('&').unwrap_or(pin_id);
+                                            let port_identifier = port_ref.next().unwrap().as_str();
                                             let designator = port_ref.next().unwrap().as_str();
+                                            let pin_id = instance_cells
+                                                .get(designator)
+                                                .and_then(|cell| port_names.get(cell))
+                                                .and_then(|ports| ports.get(port_identifier))
+                                                .copied()
+                                                .unwrap_or_else(|| {
+                                                    port_identifier
+                                                        .strip_prefix('&')
+                                                        .unwrap_or(port_identifier)
+                                                });
                                             nodes.insert(Node {
                                                 designator: Designator(designator.into()),
                                                 pin_id: PinId(pin_id.into()),
+                                                location: SourceLocation::default(),
                                             });
                                         }
                                         let mut properties = HashMap::new();
@@ -182,10 +219,24 @@ pub fn load_edif_netlist(path: &Path) -> Result<Netlist> {
                                                 .as_str();
                                             properties.insert(name.to_string(), value.to_string());
                                         }
-                                        nets.insert(
-                                            NetName(net_name.into()),
-                                            Net { nodes, properties },
-                                        );
+                                        if net_name.is_empty() {
+                                            warnings.push(ParseError::at_pair(
+                                                path,
+                                                &net_pair,
+                                                ParseErrorKind::EmptyNetName,
+                                            ));
+                                            continue;
+                                        }
+                                        let net_name = NetName(net_name.into());
+                                        if nets.contains_key(&net_name) {
+                                            warnings.push(ParseError::at_pair(
+                                                path,
+                                                &net_pair,
+                                                ParseErrorKind::DuplicateNet(net_name),
+                                            ));
+                                            continue;
+                                        }
+                                        nets.insert(net_name, Net { nodes, properties });
                                     } else {
                                         continue;
                                     }
@@ -206,11 +257,273 @@ pub fn load_edif_netlist(path: &Path) -> Result<Netlist> {
         }
     }
 
-    Ok(Netlist {
-        lib_parts,
-        nets,
-        components,
-    })
+    Ok(Netlist::new(lib_parts, nets, components, warnings))
+}
+
+/// Writes netlist in EDIF 2.0.0 format, laid out the same way as Altium's "EDIF for PCB" export:
+/// `COMPONENT_LIB` with a cell per lib part and `SHEET_LIB` with component instances and nets.
+///
+/// Names that are not valid EDIF identifiers are written with `(rename ...)`. EDIF only knows
+/// input, output and inout ports, so every other pin type is written as INOUT, same as Altium does.
+pub fn write_edif_netlist(netlist: &Netlist, path: &Path) -> Result<()> {
+    let design_name = path
+        .file_stem()
+        .map(|stem| edif_identifier(&stem.to_string_lossy()))
+        .unwrap_or_else(|| "NETLIST".into());
+
+    let mut component_pins: HashMap<&Designator, HashSet<&PinId>> = HashMap::new();
+    for net in netlist.nets().values() {
+        for node in &net.nodes {
+            component_pins
+                .entry(&node.designator)
+                .or_default()
+                .insert(&node.pin_id);
+        }
+    }
+
+    let mut cells: BTreeMap<(&str, &str), EdifCell> = BTreeMap::new();
+    for ((lib_name, lib_part_name), lib_part) in &netlist.lib_parts {
+        let cell = cells
+            .entry((lib_name.0.as_str(), lib_part_name.0.as_str()))
+            .or_default();
+        for (pin_id, pin) in &lib_part.pins {
+            cell.ports
+                .insert(pin_id.0.as_str(), edif_direction(pin.default_mode.ty));
+        }
+    }
+    for (designator, component) in &netlist.components {
+        let (lib_name, lib_part_name) = &component.lib_source;
+        let cell = cells
+            .entry((lib_name.0.as_str(), lib_part_name.0.as_str()))
+            .or_default();
+        for pin_id in component_pins.get(designator).into_iter().flatten() {
+            cell.ports.entry(pin_id.0.as_str()).or_insert("INOUT");
+        }
+    }
+    let mut used_identifiers = HashSet::new();
+    for ((_, lib_part_name), cell) in &mut cells {
+        cell.name = unique_edif_identifier(&mut used_identifiers, lib_part_name);
+        let mut used_port_identifiers = HashSet::new();
+        for pin_id in cell.ports.keys() {
+            let identifier = unique_edif_identifier(&mut used_port_identifiers, pin_id);
+            cell.port_identifiers
+                .insert(pin_id, edif_port_identifier(identifier));
+        }
+    }
+
+    let mut out = String::new();
+    writeln!(out, "(edif {design_name}")?;
+    writeln!(out, "  (edifVersion 2 0 0)")?;
+    writeln!(out, "  (edifLevel 0)")?;
+    writeln!(out, "  (keywordMap")?;
+    writeln!(out, "     (keywordLevel 0)")?;
+    writeln!(out, "  )")?;
+    writeln!(out, "  (status")?;
+    writeln!(out, "    (written")?;
+    writeln!(out, "      (program \"better_erc\"")?;
+    writeln!(out, "        (version \"{}\")", env!("CARGO_PKG_VERSION"))?;
+    writeln!(out, "      )")?;
+    writeln!(out, "    )")?;
+    writeln!(out, "  )")?;
+    writeln!(out)?;
+
+    writeln!(out, "  (library COMPONENT_LIB")?;
+    write_edif_technology(&mut out)?;
+    for ((_, lib_part_name), cell) in &cells {
+        writeln!(out, "    (cell {}", edif_rename(&cell.name, lib_part_name))?;
+        writeln!(out, "      (cellType GENERIC)")?;
+        writeln!(out, "      (view netListView")?;
+        writeln!(out, "        (viewType NETLIST)")?;
+        writeln!(out, "        (interface")?;
+        for (pin_id, direction) in &cell.ports {
+            let port = edif_rename(&cell.port_identifiers[pin_id], pin_id);
+            writeln!(out, "          (port {port} (direction {direction}))")?;
+        }
+        writeln!(out, "        )")?;
+        writeln!(out, "      )")?;
+        writeln!(out, "    )")?;
+    }
+    writeln!(out, "  )")?;
+
+    let sheet_name = format!("{design_name}_SchDoc");
+    writeln!(out, "  (library SHEET_LIB")?;
+    write_edif_technology(&mut out)?;
+    writeln!(out, "    (cell {sheet_name}")?;
+    writeln!(out, "      (cellType generic)")?;
+    writeln!(out, "      (view netListView")?;
+    writeln!(out, "        (viewType netlist)")?;
+    writeln!(out, "        (interface")?;
+    writeln!(out, "        )")?;
+    writeln!(out, "        (contents")?;
+    let mut components = netlist.components.iter().collect::<Vec<_>>();
+    components.sort_by(|a, b| a.0.0.cmp(&b.0.0));
+    for (designator, component) in components {
+        let (lib_name, lib_part_name) = &component.lib_source;
+        let cell_name = &cells[&(lib_name.0.as_str(), lib_part_name.0.as_str())].name;
+        writeln!(
+            out,
+            "          (Instance {}",
+            edif_identifier(&designator.0)
+        )?;
+        writeln!(out, "            (viewRef NetlistView")?;
+        writeln!(out, "              (cellRef {cell_name}")?;
+        writeln!(out, "                (LibraryRef COMPONENT_LIB)")?;
+        writeln!(out, "              )")?;
+        writeln!(out, "            )")?;
+        let mut properties = component
+            .fields
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect::<Vec<_>>();
+        let has_value = ["Value", "Comment"]
+            .iter()
+            .any(|name| component.fields.get(*name) == Some(&component.value));
+        if !has_value && !component.value.is_empty() {
+            properties.push(("Comment", component.value.as_str()));
+        }
+        if !component.fields.contains_key("Description") && !component.description.is_empty() {
+            properties.push(("Description", component.description.as_str()));
+        }
+        properties.sort();
+        for (name, value) in properties {
+            write_edif_property(&mut out, "            ", name, value)?;
+        }
+        writeln!(out, "          )")?;
+    }
+    let mut nets = netlist.nets().iter().collect::<Vec<_>>();
+    nets.sort_by(|a, b| a.0.0.cmp(&b.0.0));
+    let mut used_identifiers = HashSet::new();
+    for (net_name, net) in nets {
+        let identifier = unique_edif_identifier(&mut used_identifiers, &net_name.0);
+        writeln!(
+            out,
+            "          (Net {}",
+            edif_rename(&identifier, &net_name.0)
+        )?;
+        writeln!(out, "            (Joined")?;
+        let mut nodes = net.nodes.iter().collect::<Vec<_>>();
+        nodes.sort_by(|a, b| (&a.designator.0, &a.pin_id.0).cmp(&(&b.designator.0, &b.pin_id.0)));
+        for node in nodes {
+            let port_identifier = netlist
+                .components
+                .get(&node.designator)
+                .and_then(|component| {
+                    let (lib_name, lib_part_name) = &component.lib_source;
+                    cells[&(lib_name.0.as_str(), lib_part_name.0.as_str())]
+                        .port_identifiers
+                        .get(node.pin_id.0.as_str())
+                        .cloned()
+                })
+                .unwrap_or_else(|| edif_port_identifier(edif_identifier(&node.pin_id.0)));
+            writeln!(
+                out,
+                "                (PortRef {port_identifier} (InstanceRef {}))",
+                edif_identifier(&node.designator.0)
+            )?;
+        }
+        writeln!(out, "            )")?;
+        let mut properties = net.properties.iter().collect::<Vec<_>>();
+        properties.sort();
+        for (name, value) in properties {
+            write_edif_property(&mut out, "            ", name, value)?;
+        }
+        writeln!(out, "          )")?;
+    }
+    writeln!(out, "       )")?;
+    writeln!(out, "     )")?;
+    writeln!(out, "   )")?;
+    writeln!(out, "  )")?;
+    writeln!(out, "  (design {design_name}")?;
+    writeln!(out, "    (cellRef {sheet_name}")?;
+    writeln!(out, "      (libraryRef SHEET_LIB)")?;
+    writeln!(out, "    )")?;
+    writeln!(out, "  )")?;
+    writeln!(out, ")")?;
+
+    write(path, out)?;
+    Ok(())
+}
+
+/// Cell of a lib part with port directions and port identifiers by pin id
+#[derive(Default)]
+struct EdifCell<'a> {
+    name: String,
+    ports: BTreeMap<&'a str, &'static str>,
+    port_identifiers: HashMap<&'a str, String>,
+}
+
+fn write_edif_technology(out: &mut String) -> fmt::Result {
+    writeln!(out, "    (edifLevel 0)")?;
+    writeln!(out, "    (technology")?;
+    writeln!(out, "      (numberDefinition")?;
+    writeln!(out, "        (scale  1  1  (unit  distance))")?;
+    writeln!(out, "      )")?;
+    writeln!(out, "    )")
+}
+
+fn write_edif_property(out: &mut String, indent: &str, name: &str, value: &str) -> fmt::Result {
+    writeln!(
+        out,
+        "{indent}(Property {} (String \"{}\" ))",
+        edif_rename(&edif_identifier(name), name),
+        edif_string(value)
+    )
+}
+
+fn edif_direction(ty: PinType) -> &'static str {
+    match ty {
+        PinType::DigitalInput | PinType::AnalogInput => "INPUT",
+        PinType::DigitalOutput | PinType::AnalogOutput => "OUTPUT",
+        _ => "INOUT",
+    }
+}
+
+/// Replaces characters not allowed in EDIF identifiers with underscores
+fn edif_identifier(name: &str) -> String {
+    let identifier: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if identifier.is_empty() {
+        "_".into()
+    } else {
+        identifier
+    }
+}
+
+/// Port identifiers cannot start with a digit, Altium escapes them with '&' which the loader strips
+fn edif_port_identifier(identifier: String) -> String {
+    if identifier.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("&{identifier}")
+    } else {
+        identifier
+    }
+}
+
+fn unique_edif_identifier(used: &mut HashSet<String>, name: &str) -> String {
+    let identifier = edif_identifier(name);
+    let mut unique = identifier.clone();
+    let mut suffix = 1;
+    while used.contains(&unique) {
+        unique = format!("{identifier}_{suffix}");
+        suffix += 1;
+    }
+    used.insert(unique.clone());
+    unique
+}
+
+fn edif_rename(identifier: &str, name: &str) -> String {
+    let unescaped = identifier.strip_prefix('&').unwrap_or(identifier);
+    if unescaped == name {
+        identifier.to_string()
+    } else {
+        format!("(rename {identifier} \"{}\")", edif_string(name))
+    }
+}
+
+/// EDIF strings have no escape sequences, so double quotes are replaced with single ones
+fn edif_string(s: &str) -> String {
+    s.replace('"', "'")
 }
 
 fn symbol_or_rename_get(symbol_or_rename: Pair<Rule>) -> (&str, &str) {
@@ -231,6 +544,19 @@ mod tests {
     use super::*;
     use std::path::Path;
 
+    #[test]
+    fn syntax_error_is_located() {
+        let path = Path::new("test_input/netlist_edif_malformed.edf");
+        let error = load_edif_netlist(path).unwrap_err();
+        let error = error.downcast_ref::<ParseError>().unwrap();
+        assert!(error.is_fatal());
+        assert_eq!(error.span.map(|s| s.start), Some((8, 20)));
+        let ParseErrorKind::Syntax { found, .. } = &error.kind else {
+            panic!("unexpected error kind {:?}", error.kind);
+        };
+        assert_eq!(found.as_deref(), Some("(port"));
+    }
+
     #[test]
     fn can_read_edif_netlist() {
         let path = Path::new("test_input/netlist_altium_edif.edf");
@@ -240,25 +566,27 @@ mod tests {
             .lib_parts
             .get(&(
                 LibName("COMPONENT_LIB".to_string()),
-                LibPartName("CONN_2_3__6_".into()),
+                LibPartName("CONN-2-3 (6)".into()),
             ))
             .unwrap();
         assert_eq!(part.pins.iter().count(), 6);
         let pin = part.pins.get(&PinId("1".into())).unwrap();
         assert_eq!(pin.default_mode.ty, PinType::DigitalIO);
 
-        assert_eq!(netlist.nets.iter().count(), 3);
-        let net = netlist.nets.get(&NetName("NetR13_1".into())).unwrap();
+        assert_eq!(netlist.nets().iter().count(), 3);
+        let net = netlist.nets().get(&NetName("NetR13_1".into())).unwrap();
         assert_eq!(
             net.nodes,
             [
                 Node {
                     designator: Designator("R13".into()),
-                    pin_id: PinId("1".into())
+                    pin_id: PinId("1".into()),
+                    location: Default::default(),
                 },
                 Node {
                     designator: Designator("SW4".into()),
-                    pin_id: PinId("4".into())
+                    pin_id: PinId("4".into()),
+                    location: Default::default(),
                 }
             ]
             .into()
@@ -277,4 +605,112 @@ mod tests {
         //     }
         // }
     }
+
+    #[test]
+    fn first_definition_of_duplicate_net_is_kept() {
+        let path = Path::new("test_input/duplicate_net/netlist_edif.edf");
+        let netlist = load_edif_netlist(path).unwrap();
+        let net = netlist.nets().get(&NetName("A".into())).unwrap();
+        let first_node = Node {
+            designator: Designator("R1".into()),
+            pin_id: PinId("1".into()),
+            location: Default::default(),
+        };
+        assert_eq!(net.nodes, [first_node].into());
+        let kinds = netlist
+            .warnings
+            .iter()
+            .map(|w| w.kind.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(kinds, [ParseErrorKind::DuplicateNet(NetName("A".into()))]);
+    }
+
+    #[test]
+    fn edif_netlist_round_trip() {
+        let netlist = load_edif_netlist(Path::new("test_input/netlist_altium_edif.edf")).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("round_trip.edf");
+        write_edif_netlist(&netlist, &path).unwrap();
+        let written = load_edif_netlist(&path).unwrap();
+        assert_eq!(written.nets(), netlist.nets());
+        assert_eq!(written.components.len(), netlist.components.len());
+        for (designator, component) in &netlist.components {
+            let written_component = written.components.get(designator).unwrap();
+            assert_eq!(written_component.value, component.value);
+            assert_eq!(written_component.lib_source, component.lib_source);
+            assert_eq!(written_component.fields, component.fields);
+        }
+        for (lib_source, lib_part) in &netlist.lib_parts {
+            let written_lib_part = written.lib_parts.get(lib_source).unwrap();
+            for (pin_id, pin) in &lib_part.pins {
+                let written_pin = written_lib_part.pins.get(pin_id).unwrap();
+                assert_eq!(written_pin.default_mode.ty, pin.default_mode.ty);
+            }
+        }
+    }
+
+    #[test]
+    fn renamed_cells_and_ports_round_trip() {
+        use crate::test_util::{NetlistBuilder, pin};
+        let pins = ["A/B", "A-B", "1-2", "~RST"]
+            .into_iter()
+            .map(|pin_id| (PinId(pin_id.into()), pin(pin_id, PinType::DigitalIO)))
+            .collect();
+        let netlist = NetlistBuilder::new()
+            .lib_part_with(
+                "CONN-2-3 (6)",
+                LibPart {
+                    pins,
+                    ..Default::default()
+                },
+            )
+            .lib_part(
+                "74LVC1G08/A",
+                &[("A", PinType::DigitalInput), ("Y", PinType::DigitalOutput)],
+            )
+            .part("J1", "CONN", "CONN-2-3 (6)")
+            .part("U1", "74LVC1G08", "74LVC1G08/A")
+            .net("~RST", &[("J1", "~RST"), ("U1", "2")])
+            .net("A/B", &[("J1", "A/B"), ("J1", "1-2")])
+            .net("A-B", &[("J1", "A-B"), ("U1", "1")])
+            .build();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("renamed.edf");
+        write_edif_netlist(&netlist, &path).unwrap();
+        let written = load_edif_netlist(&path).unwrap();
+        assert_eq!(written.nets(), netlist.nets());
+        let lib_part_names = |netlist: &Netlist| {
+            let mut names = netlist
+                .lib_parts
+                .iter()
+                .map(|((_, name), lib_part)| {
+                    let mut pins = lib_part
+                        .pins
+                        .keys()
+                        .map(|pin_id| pin_id.0.clone())
+                        .collect::<Vec<_>>();
+                    pins.sort();
+                    (name.0.clone(), pins)
+                })
+                .collect::<Vec<_>>();
+            names.sort();
+            names
+        };
+        assert_eq!(lib_part_names(&written), lib_part_names(&netlist));
+        let component = written.components.get(&Designator("J1".into())).unwrap();
+        assert_eq!(component.lib_source.1, LibPartName("CONN-2-3 (6)".into()));
+    }
+
+    #[test]
+    fn can_write_kicad_netlist_as_edif() {
+        let netlist = crate::load_kicad_netlist(Path::new("test_input/netlist_kicad.net")).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("kicad_as.edf");
+        write_edif_netlist(&netlist, &path).unwrap();
+        let written = load_edif_netlist(&path).unwrap();
+        assert_eq!(written.nets(), netlist.nets());
+        let component = written.components.get(&Designator("C1".into())).unwrap();
+        assert_eq!(component.value, "4.7uF");
+        assert_eq!(component.description, "catch");
+    }
 }
@@ -1,29 +1,43 @@
 use crate::netlist::{
     Component, LibName, LibPart, LibPartName, Net, Netlist, Node, Pin, PinMode, PinType,
+    SourceLocation,
 };
+use crate::parse_error::{ParseError, ParseErrorKind, Span};
+use crate::text_util::sexpr_list_spans;
 use crate::{Designator, NetName, PinId, PinName};
 use anyhow::Result;
-use serde::de::SeqAccess;
+use serde::de::{Error as _, SeqAccess};
 use serde::ser::SerializeSeq;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::{HashMap, HashSet};
 use std::fmt;
-use std::fs::read_to_string;
+use std::fs::{read_to_string, write};
 use std::path::Path;
 
 pub fn load_kicad_netlist(path: &Path) -> Result<Netlist> {
-    let contents = read_to_string(path)?;
-    let netlist: KicadFileKind = serde_lexpr::from_str(&contents)?;
+    let contents = read_to_string(path).map_err(|e| ParseError::io(path, e))?;
+    let netlist: KicadFileKind = serde_lexpr::from_str(&contents).map_err(|e| {
+        ParseError::new(
+            path,
+            e.location().map(|l| Span::at((l.line(), l.column()))),
+            ParseErrorKind::Invalid(e.to_string()),
+        )
+    })?;
     let KicadFileKind::NetListExport(netlist) = netlist;
 
+    // serde_lexpr does not keep positions, warnings are located by the n-th (comp) and (net) list
+    let comp_spans = sexpr_list_spans(&contents, 2, "comp");
+    let net_spans = sexpr_list_spans(&contents, 2, "net");
+    let mut warnings = vec![];
     let mut components = HashMap::new();
-    for component in netlist.components {
+    for (component_idx, component) in netlist.components.into_iter().enumerate() {
         let ComponentKind::Component(component_entry) = component;
         let mut designator = String::new();
         let mut value = String::new();
         let mut description = String::new();
         let mut fields = HashMap::new();
         let mut lib_source = (LibName(String::new()), LibPartName(String::new()));
+        let mut location = SourceLocation::default();
         for entry in component_entry {
             match entry {
                 ComponentEntry::Ref(d) => designator = d.unwrap_or_default(),
@@ -56,13 +70,18 @@ pub fn load_kicad_netlist(path: &Path) -> Result<Netlist> {
                     let name = name.unwrap_or_default();
                     let value = value.unwrap_or_default();
                     if !name.is_empty() && !value.is_empty() {
-                        if name != "Sheetname" && name != "Sheetfile" {
+                        if name == "Sheetfile" {
+                            location.sheet_file = Some(value);
+                        } else if name != "Sheetname" {
                             fields.insert(name, value);
                         }
                     }
                 }
-                ComponentEntry::SheetPath { .. } => {}
-                ComponentEntry::Tstamps(_) => {}
+                ComponentEntry::SheetPath { names, tstamps } => {
+                    location.sheet_path = names;
+                    location.sheet_tstamps = tstamps;
+                }
+                ComponentEntry::Tstamps(t) => location.uuid = t.into_iter().next(),
                 ComponentEntry::Datasheet(d) => {
                     let d = d.unwrap_or_default();
                     if !d.is_empty() {
@@ -73,7 +92,11 @@ pub fn load_kicad_netlist(path: &Path) -> Result<Netlist> {
             }
         }
         if designator.is_empty() {
-            // TODO: kicad: emit warning on empty designator?
+            warnings.push(ParseError::new(
+                path,
+                comp_spans.get(component_idx).copied(),
+                ParseErrorKind::EmptyDesignator { value },
+            ));
             continue;
         }
         components.insert(
@@ -84,6 +107,7 @@ pub fn load_kicad_netlist(path: &Path) -> Result<Netlist> {
                 lib_source,
                 fields,
                 sections: vec![],
+                location,
             },
         );
     }
@@ -123,21 +147,7 @@ pub fn load_kicad_netlist(path: &Path) -> Result<Netlist> {
                     for p in pin_kinds {
                         let PinKind::Pin { num, name, r#type } = p;
                         let ty = if let Some(ty) = r#type {
-                            match ty.as_str() {
-                                "input" => PinType::DigitalInput,
-                                "output" => PinType::DigitalOutput,
-                                "bidirectional" => PinType::DigitalIO,
-                                "tri_state" => PinType::TriState,
-                                "passive" => PinType::Passive,
-                                "free" => PinType::Unconnected,
-                                "unspecified" => PinType::Unspecified,
-                                "power_in" => PinType::PowerIn,
-                                "power_out" => PinType::PowerOut,
-                                "open_collector" => PinType::OpenCollector,
-                                "open_emitter" => PinType::OpenEmitter,
-                                "no_connect" => PinType::Unconnected,
-                                _ => PinType::Unspecified,
-                            }
+                            kicad_pin_type(ty.as_str())
                         } else {
                             PinType::Unspecified
                         };
@@ -173,9 +183,11 @@ pub fn load_kicad_netlist(path: &Path) -> Result<Netlist> {
     }
 
     let mut nets = HashMap::new();
-    for net_kind in netlist.nets {
+    for (net_idx, net_kind) in netlist.nets.into_iter().enumerate() {
         let NetKind::Net(net_pieces) = net_kind;
+        let span = net_spans.get(net_idx).copied();
         let mut net_name = String::new();
+        let mut incomplete_nodes = false;
         let mut net = Net {
             nodes: HashSet::new(),
             properties: HashMap::new(),
@@ -192,29 +204,268 @@ pub fn load_kicad_netlist(path: &Path) -> Result<Netlist> {
                     pinfunction: _,
                     pintype: _,
                 } => {
-                    // TODO: emit warning if empty ref or pin
+                    let designator = r#ref.unwrap_or_default();
+                    let pin_id = pin.unwrap_or_default();
+                    if designator.is_empty() || pin_id.is_empty() {
+                        incomplete_nodes = true;
+                        continue;
+                    }
                     net.nodes.insert(Node {
-                        designator: Designator(r#ref.unwrap_or_default()),
-                        pin_id: PinId(pin.unwrap_or_default()),
+                        designator: Designator(designator),
+                        pin_id: PinId(pin_id),
+                        location: SourceLocation::default(),
                     });
                 }
             }
         }
-        // TODO: emit warnings
         if net_name.is_empty() {
+            warnings.push(ParseError::new(path, span, ParseErrorKind::EmptyNetName));
             continue;
         }
         let net_name = NetName(net_name);
+        if incomplete_nodes {
+            warnings.push(ParseError::new(
+                path,
+                span,
+                ParseErrorKind::IncompleteNode {
+                    net: net_name.clone(),
+                },
+            ));
+        }
         if nets.contains_key(&net_name) {
+            warnings.push(ParseError::new(
+                path,
+                span,
+                ParseErrorKind::DuplicateNet(net_name),
+            ));
             continue;
         }
         nets.insert(net_name, net);
     }
-    Ok(Netlist {
-        lib_parts,
-        nets,
+    Ok(Netlist::new(lib_parts, nets, components, warnings))
+}
+
+/// Maps KiCad electrical pin type (as used in both netlists and schematics) to PinType
+pub(crate) fn kicad_pin_type(ty: &str) -> PinType {
+    match ty {
+        "input" => PinType::DigitalInput,
+        "output" => PinType::DigitalOutput,
+        "bidirectional" => PinType::DigitalIO,
+        "tri_state" => PinType::TriState,
+        "passive" => PinType::Passive,
+        "free" => PinType::Unconnected,
+        "unspecified" => PinType::Unspecified,
+        "power_in" => PinType::PowerIn,
+        "power_out" => PinType::PowerOut,
+        "open_collector" => PinType::OpenCollector,
+        "open_emitter" => PinType::OpenEmitter,
+        "no_connect" => PinType::Unconnected,
+        _ => PinType::Unspecified,
+    }
+}
+
+/// Maps PinType back to the closest KiCad electrical pin type
+pub(crate) fn kicad_pin_type_name(ty: PinType) -> &'static str {
+    match ty {
+        PinType::DigitalInput | PinType::AnalogInput => "input",
+        PinType::DigitalOutput | PinType::AnalogOutput => "output",
+        PinType::DigitalIO | PinType::AnalogIO => "bidirectional",
+        PinType::TriState => "tri_state",
+        PinType::Passive => "passive",
+        PinType::Unconnected => "no_connect",
+        PinType::Unspecified => "unspecified",
+        PinType::PowerIn | PinType::PowerIO | PinType::PowerUnspecified => "power_in",
+        PinType::PowerOut => "power_out",
+        PinType::OpenCollector => "open_collector",
+        PinType::OpenEmitter => "open_emitter",
+    }
+}
+
+/// Writes netlist in KiCad s-expression `(export (version "E") ...)` format.
+///
+/// Components, lib parts and nets are sorted by name so that output is stable between runs.
+/// Component sections are not representable in KiCad netlists and are dropped.
+pub fn write_kicad_netlist(netlist: &Netlist, path: &Path) -> Result<()> {
+    let export = KicadFileKind::NetListExport(to_kicad_export(netlist));
+    let mut contents = serde_lexpr::to_string(&export)?;
+    contents.push('\n');
+    write(path, contents)?;
+    Ok(())
+}
+
+fn to_kicad_export(netlist: &Netlist) -> KicadNetListExport {
+    let mut design = vec![DesignPiece::Tool(Some(format!(
+        "better_erc {}",
+        env!("CARGO_PKG_VERSION")
+    )))];
+    let mut sheets = netlist
+        .components
+        .values()
+        .filter_map(|c| {
+            c.location
+                .sheet_path
+                .as_ref()
+                .map(|path| (path, &c.location.sheet_tstamps, &c.location.sheet_file))
+        })
+        .collect::<Vec<_>>();
+    sheets.sort_by(|a, b| a.0.cmp(b.0));
+    sheets.dedup_by(|a, b| a.0 == b.0);
+    for (number, (path, tstamps, file)) in sheets.into_iter().enumerate() {
+        design.push(DesignPiece::Sheet(Sheet {
+            number: Some((number + 1).to_string()),
+            name: Some(path.clone()),
+            tstamps: tstamps.iter().cloned().collect(),
+            title_block: file
+                .iter()
+                .map(|f| TitleBlockEntry::Source(Some(f.clone())))
+                .collect(),
+        }));
+    }
+
+    let mut components = netlist.components.iter().collect::<Vec<_>>();
+    components.sort_by(|a, b| a.0.0.cmp(&b.0.0));
+    let components = components
+        .into_iter()
+        .map(|(designator, component)| {
+            let mut entries = vec![
+                ComponentEntry::Ref(Some(designator.0.clone())),
+                ComponentEntry::Value(Some(component.value.clone())),
+            ];
+            if let Some(footprint) = component.fields.get("Footprint") {
+                entries.push(ComponentEntry::Footprint(Some(footprint.clone())));
+            }
+            if let Some(datasheet) = component.fields.get("Datasheet") {
+                entries.push(ComponentEntry::Datasheet(Some(datasheet.clone())));
+            }
+            if !component.description.is_empty() {
+                entries.push(ComponentEntry::Description(Some(
+                    component.description.clone(),
+                )));
+            }
+            let mut fields = component
+                .fields
+                .iter()
+                .filter(|(name, _)| *name != "Footprint" && *name != "Datasheet")
+                .map(|(name, value)| ComponentField(name.clone(), Some(value.clone())))
+                .collect::<Vec<_>>();
+            fields.sort_by(|a, b| a.0.cmp(&b.0));
+            entries.push(ComponentEntry::Fields(fields));
+            entries.push(ComponentEntry::LibSource {
+                lib: Some(component.lib_source.0.0.clone()),
+                part: Some(component.lib_source.1.0.clone()),
+                description: Some(component.description.clone()),
+            });
+            let location = &component.location;
+            if let Some(sheet_path) = &location.sheet_path {
+                let sheet_name = sheet_path
+                    .trim_end_matches('/')
+                    .rsplit('/')
+                    .next()
+                    .filter(|name| !name.is_empty())
+                    .unwrap_or("Root");
+                entries.push(ComponentEntry::Property {
+                    name: Some("Sheetname".into()),
+                    value: Some(sheet_name.into()),
+                });
+            }
+            if let Some(sheet_file) = &location.sheet_file {
+                entries.push(ComponentEntry::Property {
+                    name: Some("Sheetfile".into()),
+                    value: Some(sheet_file.clone()),
+                });
+            }
+            if location.sheet_path.is_some() || location.sheet_tstamps.is_some() {
+                entries.push(ComponentEntry::SheetPath {
+                    names: location.sheet_path.clone(),
+                    tstamps: location.sheet_tstamps.clone(),
+                });
+            }
+            if let Some(uuid) = &location.uuid {
+                entries.push(ComponentEntry::Tstamps(vec![uuid.clone()]));
+            }
+            ComponentKind::Component(entries)
+        })
+        .collect();
+
+    let mut lib_parts = netlist.lib_parts.iter().collect::<Vec<_>>();
+    lib_parts.sort_by(|a, b| (&a.0.0.0, &a.0.1.0).cmp(&(&b.0.0.0, &b.0.1.0)));
+    let libparts = lib_parts
+        .into_iter()
+        .map(|((lib_name, lib_part_name), lib_part)| {
+            let mut fields = lib_part
+                .fields
+                .iter()
+                .map(|(name, value)| ComponentField(name.clone(), Some(value.clone())))
+                .collect::<Vec<_>>();
+            fields.sort_by(|a, b| a.0.cmp(&b.0));
+            let mut pins = lib_part.pins.iter().collect::<Vec<_>>();
+            pins.sort_by(|a, b| a.0.0.cmp(&b.0.0));
+            LibPartKind::LibPart(vec![
+                LibPartPiece::Lib(Some(lib_name.0.clone())),
+                LibPartPiece::Part(Some(lib_part_name.0.clone())),
+                LibPartPiece::Description(Some(lib_part.description.clone())),
+                LibPartPiece::Footprints(
+                    lib_part
+                        .footprints
+                        .iter()
+                        .map(|f| FootprintKind::Footprint(Some(f.clone())))
+                        .collect(),
+                ),
+                LibPartPiece::Fields(fields),
+                LibPartPiece::Pins(
+                    pins.into_iter()
+                        .map(|(pin_id, pin)| PinKind::Pin {
+                            num: Some(pin_id.0.clone()),
+                            name: Some(pin.name.0.clone()),
+                            r#type: Some(kicad_pin_type_name(pin.default_mode.ty).into()),
+                        })
+                        .collect(),
+                ),
+            ])
+        })
+        .collect();
+
+    let mut nets = netlist.nets().iter().collect::<Vec<_>>();
+    nets.sort_by(|a, b| a.0.0.cmp(&b.0.0));
+    let nets = nets
+        .into_iter()
+        .enumerate()
+        .map(|(code, (net_name, net))| {
+            let mut nodes = net.nodes.iter().collect::<Vec<_>>();
+            nodes.sort_by(|a, b| {
+                (&a.designator.0, &a.pin_id.0).cmp(&(&b.designator.0, &b.pin_id.0))
+            });
+            let mut pieces = vec![
+                NetPieceKind::Code(Some((code + 1).to_string())),
+                NetPieceKind::Name(Some(net_name.0.clone())),
+            ];
+            for node in nodes {
+                let pin = netlist
+                    .components
+                    .get(&node.designator)
+                    .and_then(|c| netlist.lib_parts.get(&c.lib_source))
+                    .and_then(|lib_part| lib_part.pins.get(&node.pin_id));
+                pieces.push(NetPieceKind::Node {
+                    r#ref: Some(node.designator.0.clone()),
+                    pin: Some(node.pin_id.0.clone()),
+                    pinfunction: pin
+                        .filter(|pin| !pin.name.0.is_empty())
+                        .map(|pin| pin.name.0.clone()),
+                    pintype: pin.map(|pin| kicad_pin_type_name(pin.default_mode.ty).into()),
+                });
+            }
+            NetKind::Net(pieces)
+        })
+        .collect();
+
+    KicadNetListExport {
+        version: Some("E".into()),
+        design,
         components,
-    })
+        libparts,
+        libraries: vec![],
+        nets,
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -289,8 +540,8 @@ enum ComponentEntry {
     },
     #[serde(rename = "sheetpath")]
     SheetPath {
-        name: Option<String>,
-        tstamps: Vec<String>,
+        names: Option<String>,
+        tstamps: Option<String>,
     },
     Tstamps(Vec<String>),
     Datasheet(Option<String>),
@@ -316,11 +567,16 @@ impl<'de> serde::de::Visitor<'de> for ComponentFieldKindVisitor {
     where
         A: SeqAccess<'de>,
     {
-        let _ = seq.next_element::<ComponentFieldPlainSymbol>()?.unwrap();
-        let name = seq.next_element::<ComponentFieldName>()?.unwrap();
+        let _ = seq
+            .next_element::<ComponentFieldPlainSymbol>()?
+            .ok_or_else(|| A::Error::invalid_length(0, &self))?;
+        let name = seq
+            .next_element::<ComponentFieldName>()?
+            .ok_or_else(|| A::Error::invalid_length(1, &self))?;
         let value = seq.next_element::<String>()?;
         let ComponentFieldName::Name(name) = name;
-        Ok(ComponentField(name.unwrap(), value))
+        let name = name.ok_or_else(|| A::Error::custom("field without name"))?;
+        Ok(ComponentField(name, value))
     }
 }
 
@@ -425,8 +681,9 @@ enum NetPieceKind {
 
 #[cfg(test)]
 mod tests {
-    use super::{DesignPiece, KicadFileKind, load_kicad_netlist};
-    use crate::netlist::{Net, Node};
+    use super::{DesignPiece, KicadFileKind, load_kicad_netlist, write_kicad_netlist};
+    use crate::netlist::{Net, Node, SourceLocation};
+    use crate::parse_error::{ParseError, ParseErrorKind};
     use crate::{Designator, NetName, PinId};
     use std::fs::read_to_string;
     use std::path::PathBuf;
@@ -447,27 +704,119 @@ mod tests {
     #[test]
     fn can_read_netlist_kicad() {
         let netlist = load_kicad_netlist(&PathBuf::from("test_input/netlist_kicad.net")).unwrap();
-        assert_eq!(netlist.nets.len(), 4);
+        assert_eq!(netlist.nets().len(), 4);
         assert_eq!(
-            netlist.nets.get(&NetName("/Eth/RXD0".into())),
+            netlist.nets().get(&NetName("/Eth/RXD0".into())),
             Some(&Net {
                 nodes: [
                     Node {
                         designator: Designator("R21".to_string()),
-                        pin_id: PinId("2".to_string())
+                        pin_id: PinId("2".to_string()),
+                        location: Default::default(),
                     },
                     Node {
                         designator: Designator("R29".to_string()),
-                        pin_id: PinId("2".to_string())
+                        pin_id: PinId("2".to_string()),
+                        location: Default::default(),
                     },
                     Node {
                         designator: Designator("U2".to_string()),
-                        pin_id: PinId("11".to_string())
+                        pin_id: PinId("11".to_string()),
+                        location: Default::default(),
                     }
                 ]
                 .into(),
                 properties: Default::default(),
             })
-        )
+        );
+        let component = netlist.components.get(&Designator("B1".into())).unwrap();
+        assert_eq!(
+            component.location,
+            SourceLocation {
+                sheet_path: Some("/MCU_H7/".into()),
+                sheet_tstamps: Some("/c16e79c7-955f-4686-8fe9-acff02e57751/".into()),
+                sheet_file: Some("p2_mcu_h7.kicad_sch".into()),
+                uuid: Some("f824eb5c-eeb0-4d20-81c9-527c8d59e79e".into()),
+                position: None,
+            }
+        );
+    }
+
+    #[test]
+    fn recoverable_problems_are_collected_as_warnings() {
+        let netlist =
+            load_kicad_netlist(&PathBuf::from("test_input/netlist_kicad_warnings.net")).unwrap();
+        assert_eq!(netlist.components.len(), 1);
+        assert_eq!(netlist.nets().len(), 1);
+        let net = netlist.nets().get(&NetName("/A".into())).unwrap();
+        let first_node = Node {
+            designator: Designator("R1".into()),
+            pin_id: PinId("1".into()),
+            location: Default::default(),
+        };
+        assert_eq!(net.nodes, [first_node].into());
+        let kinds = netlist
+            .warnings
+            .iter()
+            .map(|w| w.kind.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            [
+                ParseErrorKind::EmptyDesignator {
+                    value: "100n".into()
+                },
+                ParseErrorKind::IncompleteNode {
+                    net: NetName("/A".into())
+                },
+                ParseErrorKind::EmptyNetName,
+                ParseErrorKind::DuplicateNet(NetName("/A".into())),
+            ]
+        );
+        assert!(netlist.warnings.iter().all(|w| !w.is_fatal()));
+        let starts = netlist
+            .warnings
+            .iter()
+            .map(|w| w.span.map(|s| s.start))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            starts,
+            [Some((9, 5)), Some((15, 5)), Some((18, 5)), Some((20, 5))]
+        );
+        assert_eq!(netlist.warnings[0].span.unwrap().end, (11, 82));
+    }
+
+    #[test]
+    fn malformed_netlist_is_an_error() {
+        let path = PathBuf::from("test_input/netlist_kicad_malformed.net");
+        let error = load_kicad_netlist(&path).unwrap_err();
+        let error = error.downcast_ref::<ParseError>().unwrap();
+        assert!(error.is_fatal());
+        assert_eq!(error.path, path);
+        assert!(matches!(error.kind, ParseErrorKind::Invalid(_)));
+    }
+
+    #[test]
+    fn kicad_netlist_round_trip() {
+        let netlist = load_kicad_netlist(&PathBuf::from("test_input/netlist_kicad.net")).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("round_trip_kicad.net");
+        write_kicad_netlist(&netlist, &path).unwrap();
+        let written = load_kicad_netlist(&path).unwrap();
+        assert_eq!(written.nets(), netlist.nets());
+        assert_eq!(written.components.len(), netlist.components.len());
+        for (designator, component) in &netlist.components {
+            let written_component = written.components.get(designator).unwrap();
+            assert_eq!(written_component.value, component.value);
+            assert_eq!(written_component.description, component.description);
+            assert_eq!(written_component.fields, component.fields);
+            assert_eq!(written_component.location, component.location);
+        }
+        assert_eq!(written.lib_parts.len(), netlist.lib_parts.len());
+        for (lib_source, lib_part) in &netlist.lib_parts {
+            let written_lib_part = written.lib_parts.get(lib_source).unwrap();
+            assert_eq!(written_lib_part.footprints, lib_part.footprints);
+            assert_eq!(written_lib_part.pins.len(), lib_part.pins.len());
+        }
     }
 }
@@ -0,0 +1,1059 @@
+use crate::kicad_netlist::kicad_pin_type;
+use crate::netlist::{
+    Component, ComponentSection, LibName, LibPart, LibPartName, Net, Netlist, Node, Pin, PinMode,
+    PinType, SourceLocation,
+};
+use crate::parse_error::{ParseError, ParseErrorKind, Span};
+use crate::text_util::sexpr_list_spans;
+use crate::{Designator, NetName, PinId, PinName};
+use anyhow::Result;
+use lexpr::Value;
+use std::collections::{HashMap, HashSet};
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+
+/// Loads root .kicad_sch file and all the hierarchical sheets it references, resolves connectivity
+/// (wires, junctions, local, global and hierarchical labels, sheet pins and power symbols) and produces
+/// the same Netlist as would be loaded from a kicad-cli exported netlist.
+///
+/// Buses and bus entries are not supported yet, nets connected only through them will end up split.
+pub fn load_kicad_schematic(root_path: &Path) -> Result<Netlist> {
+    let mut files = HashMap::new();
+    let mut instances = Vec::new();
+    collect_sheet_instances(root_path, None, &mut files, &mut instances)?;
+
+    let mut warnings = vec![];
+    let mut connectivity = Connectivity::default();
+    let mut components: HashMap<Designator, Component> = HashMap::new();
+    let mut lib_parts = HashMap::new();
+    for (instance_idx, instance) in instances.iter().enumerate() {
+        let file = &files[&instance.file];
+        for symbol in &file.symbols {
+            let Some(lib_symbol) = file.lib_symbols.get(symbol.lib_symbol_key()) else {
+                warnings.push(ParseError::new(
+                    &instance.file,
+                    symbol.span,
+                    ParseErrorKind::MissingLibSymbol {
+                        lib_id: symbol.lib_symbol_key().to_string(),
+                    },
+                ));
+                continue;
+            };
+            let (reference, unit) = symbol.reference_and_unit(&instance.path, file);
+            if lib_symbol.is_power || reference.starts_with('#') {
+                continue;
+            }
+            if reference.is_empty() {
+                warnings.push(ParseError::new(
+                    &instance.file,
+                    symbol.span,
+                    ParseErrorKind::EmptyDesignator {
+                        value: symbol.properties.get("Value").cloned().unwrap_or_default(),
+                    },
+                ));
+                continue;
+            }
+            let designator = Designator(reference);
+            let lib_source = split_lib_id(&symbol.lib_id);
+            if !lib_parts.contains_key(&lib_source) {
+                lib_parts.insert(lib_source.clone(), lib_symbol.to_lib_part());
+            }
+            let location = instance.location(&symbol.uuid, symbol.at);
+            let section = ComponentSection {
+                name: lib_symbol.unit_name(unit),
+                page_number: instance.page_number,
+                location: location.clone(),
+            };
+            if let Some(component) = components.get_mut(&designator) {
+                component.sections.push(section);
+                continue;
+            }
+            let mut fields = HashMap::new();
+            for (name, value) in &symbol.properties {
+                if name != "Reference" && name != "Value" && !value.is_empty() {
+                    fields.insert(name.clone(), value.clone());
+                }
+            }
+            components.insert(
+                designator,
+                Component {
+                    value: symbol.properties.get("Value").cloned().unwrap_or_default(),
+                    description: symbol
+                        .properties
+                        .get("Description")
+                        .cloned()
+                        .unwrap_or_default(),
+                    lib_source,
+                    fields,
+                    sections: vec![section],
+                    location,
+                },
+            );
+        }
+        connectivity.add_sheet_instance(instance_idx, instance, file);
+    }
+    connectivity.connect_hierarchy(&instances);
+
+    Ok(Netlist::new(
+        lib_parts,
+        connectivity.into_nets(),
+        components,
+        warnings,
+    ))
+}
+
+/// One placement of a schematic file in the hierarchy, the same file can be placed multiple times
+struct SheetInstance {
+    file: PathBuf,
+    /// Path of sheet UUIDs starting from the root, e.g. "/root-uuid/sheet-uuid"
+    path: String,
+    /// Net name prefix, "/" for the root sheet and "/Sheetname/" for others
+    name_prefix: String,
+    /// Sheet UUIDs path without the root, as in netlists, "/" for the root sheet and "/sheet-uuid/" for others
+    tstamps: String,
+    sheet_file: String,
+    page_number: Option<u32>,
+    depth: usize,
+    parent: Option<(usize, String)>,
+}
+
+fn collect_sheet_instances(
+    path: &Path,
+    parent: Option<(usize, &SheetRef)>,
+    files: &mut HashMap<PathBuf, SchematicFile>,
+    instances: &mut Vec<SheetInstance>,
+) -> Result<()> {
+    if !files.contains_key(path) {
+        let file = SchematicFile::load(path)?;
+        files.insert(path.to_path_buf(), file);
+    }
+    let file = &files[path];
+    let instance = match parent {
+        Some((parent_idx, sheet)) => {
+            let parent = &instances[parent_idx];
+            let page_number = sheet
+                .pages
+                .iter()
+                .find(|(p, _)| p == &parent.path)
+                .and_then(|(_, page)| page.parse().ok());
+            SheetInstance {
+                file: path.to_path_buf(),
+                path: format!("{}/{}", parent.path, sheet.uuid),
+                name_prefix: format!("{}{}/", parent.name_prefix, sheet.name),
+                tstamps: format!("{}{}/", parent.tstamps, sheet.uuid),
+                sheet_file: sheet.file.clone(),
+                page_number,
+                depth: parent.depth + 1,
+                parent: Some((parent_idx, sheet.uuid.clone())),
+            }
+        }
+        None => SheetInstance {
+            file: path.to_path_buf(),
+            path: format!("/{}", file.uuid),
+            name_prefix: "/".into(),
+            tstamps: "/".into(),
+            sheet_file: path
+                .file_name()
+                .map(|f| f.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            page_number: Some(1),
+            depth: 0,
+            parent: None,
+        },
+    };
+    let mut ancestor = instance.parent.as_ref().map(|(idx, _)| *idx);
+    while let Some(idx) = ancestor {
+        if instances[idx].file == instance.file {
+            let (parent_idx, sheet) = parent.unwrap();
+            return Err(ParseError::new(
+                &instances[parent_idx].file,
+                sheet.span,
+                ParseErrorKind::RecursiveSheet {
+                    file: sheet.file.clone(),
+                },
+            )
+            .into());
+        }
+        ancestor = instances[idx].parent.as_ref().map(|(idx, _)| *idx);
+    }
+    let instance_idx = instances.len();
+    instances.push(instance);
+
+    let sheets = files[path].sheets.clone();
+    let dir = path.parent().unwrap_or(Path::new(""));
+    for sheet in &sheets {
+        collect_sheet_instances(
+            &dir.join(&sheet.file),
+            Some((instance_idx, sheet)),
+            files,
+            instances,
+        )?;
+    }
+    Ok(())
+}
+
+impl SheetInstance {
+    fn location(&self, uuid: &str, (x, y): (f64, f64)) -> SourceLocation {
+        SourceLocation {
+            sheet_path: Some(self.name_prefix.clone()),
+            sheet_tstamps: Some(self.tstamps.clone()),
+            sheet_file: Some(self.sheet_file.clone()),
+            uuid: Some(uuid.to_string()),
+            position: Some((x as f32, y as f32)),
+        }
+    }
+}
+
+/// Coordinates in KiCad internal units (100nm), so that exact comparisons are possible
+type Point = (i64, i64);
+
+fn to_iu(mm: f64) -> i64 {
+    (mm * 10_000.0).round() as i64
+}
+
+fn from_iu((x, y): Point) -> (f64, f64) {
+    (x as f64 / 10_000.0, y as f64 / 10_000.0)
+}
+
+#[derive(Default)]
+struct SchematicFile {
+    uuid: String,
+    lib_symbols: HashMap<String, LibSymbol>,
+    symbols: Vec<PlacedSymbol>,
+    wires: Vec<(Point, Point)>,
+    junctions: Vec<Point>,
+    labels: Vec<Label>,
+    sheets: Vec<SheetRef>,
+    /// KiCad 6 stores references of all the symbols in the root file: full path -> (reference, unit)
+    symbol_instances: HashMap<String, (String, u32)>,
+}
+
+struct LibSymbol {
+    is_power: bool,
+    properties: HashMap<String, String>,
+    unit_count: u32,
+    pins: Vec<LibSymbolPin>,
+}
+
+struct LibSymbolPin {
+    /// 0 if pin is common to all units
+    unit: u32,
+    number: String,
+    name: String,
+    ty: PinType,
+    hidden: bool,
+    at: (f64, f64),
+}
+
+struct PlacedSymbol {
+    lib_id: String,
+    lib_name: Option<String>,
+    at: (f64, f64),
+    angle: f64,
+    mirror: Option<Mirror>,
+    unit: u32,
+    uuid: String,
+    properties: HashMap<String, String>,
+    /// Sheet path -> (reference, unit)
+    instances: HashMap<String, (String, u32)>,
+    span: Option<Span>,
+}
+
+#[derive(Copy, Clone)]
+enum Mirror {
+    X,
+    Y,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum LabelKind {
+    Local,
+    Global,
+    Hierarchical,
+}
+
+struct Label {
+    kind: LabelKind,
+    text: String,
+    at: Point,
+}
+
+#[derive(Clone)]
+struct SheetRef {
+    uuid: String,
+    name: String,
+    file: String,
+    pins: Vec<(String, Point)>,
+    /// Parent sheet path -> page number
+    pages: Vec<(String, String)>,
+    span: Option<Span>,
+}
+
+impl SchematicFile {
+    fn load(path: &Path) -> Result<Self> {
+        let contents = read_to_string(path).map_err(|e| ParseError::io(path, e))?;
+        let root = lexpr::from_str(&contents).map_err(|e| {
+            ParseError::new(
+                path,
+                e.location().map(|l| Span::at((l.line(), l.column()))),
+                ParseErrorKind::Invalid(e.to_string()),
+            )
+        })?;
+        if head(&root) != Some("kicad_sch") {
+            return Err(ParseError::new(
+                path,
+                None,
+                ParseErrorKind::UnexpectedContent {
+                    expected: "kicad_sch".into(),
+                    found: head(&root).unwrap_or("no s-expression head").into(),
+                },
+            )
+            .into());
+        }
+        // lexpr does not keep positions, placed symbols and sheets are located by their order
+        let symbol_spans = sexpr_list_spans(&contents, 1, "symbol");
+        let sheet_spans = sexpr_list_spans(&contents, 1, "sheet");
+        let mut file = SchematicFile::default();
+        for item in args(&root) {
+            match head(item) {
+                Some("uuid") => file.uuid = arg_str(item, 0).unwrap_or_default(),
+                Some("lib_symbols") => {
+                    for symbol in children(item, "symbol") {
+                        let Some(name) = arg_str(symbol, 0) else {
+                            continue;
+                        };
+                        file.lib_symbols.insert(name, LibSymbol::parse(symbol));
+                    }
+                }
+                Some("symbol") => {
+                    let span = symbol_spans.get(file.symbols.len()).copied();
+                    file.symbols.push(PlacedSymbol::parse(item, span));
+                }
+                Some("wire") => {
+                    let points = child(item, "pts")
+                        .map(|pts| children(pts, "xy").filter_map(xy).collect::<Vec<_>>())
+                        .unwrap_or_default();
+                    for w in points.windows(2) {
+                        file.wires.push((w[0], w[1]));
+                    }
+                }
+                Some("junction") => {
+                    if let Some((x, y, _)) = at(item) {
+                        file.junctions.push((to_iu(x), to_iu(y)));
+                    }
+                }
+                Some(kind @ ("label" | "global_label" | "hierarchical_label")) => {
+                    let kind = match kind {
+                        "label" => LabelKind::Local,
+                        "global_label" => LabelKind::Global,
+                        _ => LabelKind::Hierarchical,
+                    };
+                    if let (Some(text), Some((x, y, _))) = (arg_str(item, 0), at(item)) {
+                        file.labels.push(Label {
+                            kind,
+                            text,
+                            at: (to_iu(x), to_iu(y)),
+                        });
+                    }
+                }
+                Some("sheet") => {
+                    let span = sheet_spans.get(file.sheets.len()).copied();
+                    file.sheets.push(SheetRef::parse(item, span));
+                }
+                Some("symbol_instances") => {
+                    for path in children(item, "path") {
+                        let Some(p) = arg_str(path, 0) else {
+                            continue;
+                        };
+                        let reference = child(path, "reference")
+                            .and_then(|r| arg_str(r, 0))
+                            .unwrap_or_default();
+                        let unit = child(path, "unit")
+                            .and_then(|u| arg_f64(u, 0))
+                            .unwrap_or(1.0) as u32;
+                        file.symbol_instances.insert(p, (reference, unit));
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(file)
+    }
+}
+
+impl LibSymbol {
+    fn parse(symbol: &Value) -> Self {
+        let mut pins = Vec::new();
+        let mut unit_count = 1;
+        for unit_symbol in children(symbol, "symbol") {
+            // sub-symbols are named LibPartName_unit_style, unit 0 is common to all units
+            let name = arg_str(unit_symbol, 0).unwrap_or_default();
+            let mut parts = name.rsplitn(3, '_');
+            let style: u32 = parts.next().and_then(|s| s.parse().ok()).unwrap_or(1);
+            let unit: u32 = parts.next().and_then(|s| s.parse().ok()).unwrap_or(0);
+            if style > 1 {
+                // alternate body style (De Morgan), pins are the same
+                continue;
+            }
+            unit_count = unit_count.max(unit);
+            for pin in children(unit_symbol, "pin") {
+                let Some((x, y, _)) = at(pin) else {
+                    continue;
+                };
+                let hidden = args(pin).any(|a| a.as_symbol() == Some("hide"))
+                    || child(pin, "hide").and_then(|h| arg_str(h, 0)).as_deref() == Some("yes");
+                pins.push(LibSymbolPin {
+                    unit,
+                    number: child(pin, "number")
+                        .and_then(|n| arg_str(n, 0))
+                        .unwrap_or_default(),
+                    name: child(pin, "name")
+                        .and_then(|n| arg_str(n, 0))
+                        .unwrap_or_default(),
+                    ty: kicad_pin_type(arg_str(pin, 0).unwrap_or_default().as_str()),
+                    hidden,
+                    at: (x, y),
+                });
+            }
+        }
+        LibSymbol {
+            is_power: child(symbol, "power").is_some(),
+            properties: properties(symbol),
+            unit_count,
+            pins,
+        }
+    }
+
+    fn unit_pins(&self, unit: u32) -> impl Iterator<Item = &LibSymbolPin> {
+        self.pins
+            .iter()
+            .filter(move |p| p.unit == 0 || p.unit == unit)
+    }
+
+    fn unit_name(&self, unit: u32) -> String {
+        if self.unit_count > 1 && (1..=26).contains(&unit) {
+            char::from(b'A' + unit as u8 - 1).to_string()
+        } else {
+            unit.to_string()
+        }
+    }
+
+    fn to_lib_part(&self) -> LibPart {
+        let mut pins = HashMap::new();
+        for pin in &self.pins {
+            pins.insert(
+                PinId(pin.number.clone()),
+                Pin {
+                    name: PinName(pin.name.to_uppercase()),
+                    default_mode: PinMode {
+                        ty: pin.ty,
+                        pull_up: None,
+                        pull_down: None,
+                        io_standard: None,
+                    },
+                    alternate_modes: Default::default(),
+                    bank_name: None,
+                    section_name: if self.unit_count > 1 && pin.unit > 0 {
+                        Some(self.unit_name(pin.unit))
+                    } else {
+                        None
+                    },
+                },
+            );
+        }
+        let mut fields = HashMap::new();
+        for (name, value) in &self.properties {
+            if !name.starts_with("ki_") && !value.is_empty() {
+                fields.insert(name.clone(), value.clone());
+            }
+        }
+        LibPart {
+            description: self
+                .properties
+                .get("Description")
+                .cloned()
+                .unwrap_or_default(),
+            footprints: self
+                .properties
+                .get("ki_fp_filters")
+                .map(|f| f.split_whitespace().map(|f| f.to_string()).collect())
+                .unwrap_or_default(),
+            fields,
+            pins,
+            banks: HashMap::new(),
+        }
+    }
+}
+
+impl PlacedSymbol {
+    fn parse(symbol: &Value, span: Option<Span>) -> Self {
+        let (x, y, angle) = at(symbol).unwrap_or_default();
+        let mirror = match child(symbol, "mirror")
+            .and_then(|m| arg_str(m, 0))
+            .as_deref()
+        {
+            Some("x") => Some(Mirror::X),
+            Some("y") => Some(Mirror::Y),
+            _ => None,
+        };
+        let mut instances = HashMap::new();
+        if let Some(i) = child(symbol, "instances") {
+            for project in children(i, "project") {
+                for path in children(project, "path") {
+                    let Some(p) = arg_str(path, 0) else {
+                        continue;
+                    };
+                    let reference = child(path, "reference")
+                        .and_then(|r| arg_str(r, 0))
+                        .unwrap_or_default();
+                    let unit = child(path, "unit")
+                        .and_then(|u| arg_f64(u, 0))
+                        .unwrap_or(1.0) as u32;
+                    instances.insert(p, (reference, unit));
+                }
+            }
+        }
+        PlacedSymbol {
+            lib_id: child(symbol, "lib_id")
+                .and_then(|l| arg_str(l, 0))
+                .unwrap_or_default(),
+            lib_name: child(symbol, "lib_name").and_then(|l| arg_str(l, 0)),
+            at: (x, y),
+            angle,
+            mirror,
+            unit: child(symbol, "unit")
+                .and_then(|u| arg_f64(u, 0))
+                .unwrap_or(1.0) as u32,
+            uuid: child(symbol, "uuid")
+                .and_then(|u| arg_str(u, 0))
+                .unwrap_or_default(),
+            properties: properties(symbol),
+            instances,
+            span,
+        }
+    }
+
+    fn lib_symbol_key(&self) -> &str {
+        self.lib_name.as_deref().unwrap_or(self.lib_id.as_str())
+    }
+
+    /// Reference and unit for a particular placement of the sheet this symbol is in
+    fn reference_and_unit(&self, sheet_path: &str, file: &SchematicFile) -> (String, u32) {
+        if let Some(r) = self.instances.get(sheet_path) {
+            return r.clone();
+        }
+        let full_path = format!("{sheet_path}/{}", self.uuid);
+        if let Some(r) = file.symbol_instances.get(&full_path) {
+            return r.clone();
+        }
+        // sheet opened on its own, not through the project root
+        if self.instances.len() == 1 {
+            if let Some(r) = self.instances.values().next() {
+                return r.clone();
+            }
+        }
+        (
+            self.properties
+                .get("Reference")
+                .cloned()
+                .unwrap_or_default(),
+            self.unit,
+        )
+    }
+
+    /// Converts symbol library coordinates (Y axis up) into schematic coordinates (Y axis down),
+    /// rotation is applied first and mirroring second, same as KiCad does.
+    fn transform(&self, (x, y): (f64, f64)) -> Point {
+        let y = -y;
+        let (x, y) = match self.angle.rem_euclid(360.0).round() as i64 {
+            90 => (y, -x),
+            180 => (-x, -y),
+            270 => (-y, x),
+            _ => (x, y),
+        };
+        let (x, y) = match self.mirror {
+            Some(Mirror::X) => (x, -y),
+            Some(Mirror::Y) => (-x, y),
+            None => (x, y),
+        };
+        (to_iu(self.at.0 + x), to_iu(self.at.1 + y))
+    }
+}
+
+impl SheetRef {
+    fn parse(sheet: &Value, span: Option<Span>) -> Self {
+        let properties = properties(sheet);
+        let mut pins = Vec::new();
+        for pin in children(sheet, "pin") {
+            if let (Some(name), Some((x, y, _))) = (arg_str(pin, 0), at(pin)) {
+                pins.push((name, (to_iu(x), to_iu(y))));
+            }
+        }
+        let mut pages = Vec::new();
+        if let Some(i) = child(sheet, "instances") {
+            for project in children(i, "project") {
+                for path in children(project, "path") {
+                    if let (Some(p), Some(page)) = (
+                        arg_str(path, 0),
+                        child(path, "page").and_then(|p| arg_str(p, 0)),
+                    ) {
+                        pages.push((p, page));
+                    }
+                }
+            }
+        }
+        SheetRef {
+            uuid: child(sheet, "uuid")
+                .and_then(|u| arg_str(u, 0))
+                .unwrap_or_default(),
+            // KiCad 6 used "Sheet name" and "Sheet file"
+            name: properties
+                .get("Sheetname")
+                .or(properties.get("Sheet name"))
+                .cloned()
+                .unwrap_or_default(),
+            file: properties
+                .get("Sheetfile")
+                .or(properties.get("Sheet file"))
+                .cloned()
+                .unwrap_or_default(),
+            pins,
+            pages,
+            span,
+        }
+    }
+}
+
+fn split_lib_id(lib_id: &str) -> (LibName, LibPartName) {
+    match lib_id.split_once(':') {
+        Some((lib, part)) => (LibName(lib.into()), LibPartName(part.into())),
+        None => (LibName(String::new()), LibPartName(lib_id.into())),
+    }
+}
+
+/// Things that can give a name to a net, in the order of increasing priority (same as KiCad)
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum DriverKind {
+    SheetPin,
+    HierarchicalLabel,
+    LocalLabel,
+    PowerPin,
+    GlobalLabel,
+}
+
+struct Driver {
+    kind: DriverKind,
+    name: String,
+    depth: usize,
+}
+
+/// Union-find over all the connectable items of all the sheet instances
+#[derive(Default)]
+struct Connectivity {
+    parents: Vec<usize>,
+    nodes: Vec<Option<Node>>,
+    drivers: Vec<Option<Driver>>,
+    /// Local labels by (sheet instance, text), global labels and power symbols by text
+    local_names: HashMap<(usize, String), usize>,
+    global_names: HashMap<String, usize>,
+    /// (sheet instance, text) -> items
+    hierarchical_labels: HashMap<(usize, String), Vec<usize>>,
+    /// (parent sheet instance, sheet uuid, pin name) -> item
+    sheet_pins: HashMap<(usize, String, String), usize>,
+}
+
+impl Connectivity {
+    fn add_item(&mut self, node: Option<Node>, driver: Option<Driver>) -> usize {
+        let idx = self.parents.len();
+        self.parents.push(idx);
+        self.nodes.push(node);
+        self.drivers.push(driver);
+        idx
+    }
+
+    fn find(&mut self, mut idx: usize) -> usize {
+        while self.parents[idx] != idx {
+            self.parents[idx] = self.parents[self.parents[idx]];
+            idx = self.parents[idx];
+        }
+        idx
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let a = self.find(a);
+        let b = self.find(b);
+        if a != b {
+            self.parents[b] = a;
+        }
+    }
+
+    fn add_sheet_instance(
+        &mut self,
+        instance_idx: usize,
+        instance: &SheetInstance,
+        file: &SchematicFile,
+    ) {
+        let mut anchors: Vec<(Point, usize)> = Vec::new();
+        for symbol in &file.symbols {
+            let Some(lib_symbol) = file.lib_symbols.get(symbol.lib_symbol_key()) else {
+                continue;
+            };
+            let (reference, unit) = symbol.reference_and_unit(&instance.path, file);
+            let power_name = symbol.properties.get("Value").cloned().unwrap_or_default();
+            for pin in lib_symbol.unit_pins(unit) {
+                let at = symbol.transform(pin.at);
+                let idx = if lib_symbol.is_power {
+                    let idx = self.add_item(
+                        None,
+                        Some(Driver {
+                            kind: DriverKind::PowerPin,
+                            name: power_name.clone(),
+                            depth: instance.depth,
+                        }),
+                    );
+                    self.connect_global(power_name.clone(), idx);
+                    idx
+                } else if reference.starts_with('#') || reference.is_empty() {
+                    // PWR_FLAG and such
+                    self.add_item(None, None)
+                } else {
+                    let idx = self.add_item(
+                        Some(Node {
+                            designator: Designator(reference.clone()),
+                            pin_id: PinId(pin.number.clone()),
+                            location: instance.location(&symbol.uuid, from_iu(at)),
+                        }),
+                        None,
+                    );
+                    if pin.hidden && pin.ty == PinType::PowerIn {
+                        // invisible power pins of legacy symbols are connected by name globally
+                        self.drivers[idx] = Some(Driver {
+                            kind: DriverKind::PowerPin,
+                            name: pin.name.clone(),
+                            depth: instance.depth,
+                        });
+                        self.connect_global(pin.name.clone(), idx);
+                    }
+                    idx
+                };
+                anchors.push((at, idx));
+            }
+        }
+        for label in &file.labels {
+            let kind = match label.kind {
+                LabelKind::Local => DriverKind::LocalLabel,
+                LabelKind::Global => DriverKind::GlobalLabel,
+                LabelKind::Hierarchical => DriverKind::HierarchicalLabel,
+            };
+            let name = if label.kind == LabelKind::Global {
+                label.text.clone()
+            } else {
+                format!("{}{}", instance.name_prefix, label.text)
+            };
+            let idx = self.add_item(
+                None,
+                Some(Driver {
+                    kind,
+                    name,
+                    depth: instance.depth,
+                }),
+            );
+            match label.kind {
+                LabelKind::Local => {
+                    let key = (instance_idx, label.text.clone());
+                    if let Some(first) = self.local_names.get(&key).copied() {
+                        self.union(first, idx);
+                    } else {
+                        self.local_names.insert(key, idx);
+                    }
+                }
+                LabelKind::Global => self.connect_global(label.text.clone(), idx),
+                LabelKind::Hierarchical => {
+                    self.hierarchical_labels
+                        .entry((instance_idx, label.text.clone()))
+                        .or_default()
+                        .push(idx);
+                }
+            }
+            anchors.push((label.at, idx));
+        }
+        for sheet in &file.sheets {
+            for (name, at) in &sheet.pins {
+                let idx = self.add_item(
+                    None,
+                    Some(Driver {
+                        kind: DriverKind::SheetPin,
+                        name: format!("{}{}", instance.name_prefix, name),
+                        depth: instance.depth,
+                    }),
+                );
+                self.sheet_pins
+                    .insert((instance_idx, sheet.uuid.clone(), name.clone()), idx);
+                anchors.push((*at, idx));
+            }
+        }
+        for junction in &file.junctions {
+            let idx = self.add_item(None, None);
+            anchors.push((*junction, idx));
+        }
+        let mut wires = Vec::with_capacity(file.wires.len());
+        for (a, b) in &file.wires {
+            let idx = self.add_item(None, None);
+            wires.push(idx);
+            anchors.push((*a, idx));
+            anchors.push((*b, idx));
+        }
+
+        // items at the same point are connected
+        let mut by_point: HashMap<Point, usize> = HashMap::new();
+        for (point, idx) in &anchors {
+            if let Some(first) = by_point.get(point) {
+                self.union(*first, *idx);
+            } else {
+                by_point.insert(*point, *idx);
+            }
+        }
+        // points laying on a wire (T-connections, labels placed in the middle of a wire)
+        for (wire_idx, (a, b)) in wires.iter().zip(&file.wires) {
+            for (point, idx) in &by_point {
+                if is_on_segment(*point, *a, *b) {
+                    self.union(*wire_idx, *idx);
+                }
+            }
+        }
+    }
+
+    fn connect_global(&mut self, name: String, idx: usize) {
+        if let Some(first) = self.global_names.get(&name).copied() {
+            self.union(first, idx);
+        } else {
+            self.global_names.insert(name, idx);
+        }
+    }
+
+    /// Connects sheet pins with the hierarchical labels inside the corresponding sheet instances
+    fn connect_hierarchy(&mut self, instances: &[SheetInstance]) {
+        for (instance_idx, instance) in instances.iter().enumerate() {
+            let Some((parent_idx, sheet_uuid)) = &instance.parent else {
+                continue;
+            };
+            let labels = self
+                .hierarchical_labels
+                .iter()
+                .filter(|((i, _), _)| *i == instance_idx)
+                .map(|((_, text), items)| (text.clone(), items.clone()))
+                .collect::<Vec<_>>();
+            for (text, items) in labels {
+                let Some(pin_idx) = self
+                    .sheet_pins
+                    .get(&(*parent_idx, sheet_uuid.clone(), text))
+                    .copied()
+                else {
+                    continue;
+                };
+                for idx in items {
+                    self.union(pin_idx, idx);
+                }
+            }
+        }
+    }
+
+    fn into_nets(mut self) -> HashMap<NetName, Net> {
+        let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+        for idx in 0..self.parents.len() {
+            let root = self.find(idx);
+            groups.entry(root).or_default().push(idx);
+        }
+        let mut nets: HashMap<NetName, Net> = HashMap::new();
+        for items in groups.values() {
+            let nodes: HashSet<Node> = items
+                .iter()
+                .filter_map(|idx| self.nodes[*idx].clone())
+                .collect();
+            if nodes.is_empty() {
+                continue;
+            }
+            let driver = items
+                .iter()
+                .filter_map(|idx| self.drivers[*idx].as_ref())
+                .max_by(|a, b| {
+                    a.kind
+                        .cmp(&b.kind)
+                        .then(b.depth.cmp(&a.depth))
+                        .then(b.name.cmp(&a.name))
+                });
+            let name = match driver {
+                Some(driver) => driver.name.clone(),
+                None => {
+                    let mut sorted = nodes.iter().collect::<Vec<_>>();
+                    sorted.sort_by(|a, b| {
+                        (&a.designator.0, &a.pin_id.0).cmp(&(&b.designator.0, &b.pin_id.0))
+                    });
+                    let first = sorted[0];
+                    if nodes.len() == 1 {
+                        format!("unconnected-({}-Pad{})", first.designator.0, first.pin_id.0)
+                    } else {
+                        format!("Net-({}-Pad{})", first.designator.0, first.pin_id.0)
+                    }
+                }
+            };
+            nets.entry(NetName(name))
+                .or_insert_with(|| Net {
+                    nodes: HashSet::new(),
+                    properties: HashMap::new(),
+                })
+                .nodes
+                .extend(nodes);
+        }
+        nets
+    }
+}
+
+fn is_on_segment(p: Point, a: Point, b: Point) -> bool {
+    let cross =
+        (b.0 - a.0) as i128 * (p.1 - a.1) as i128 - (b.1 - a.1) as i128 * (p.0 - a.0) as i128;
+    if cross != 0 {
+        return false;
+    }
+    p.0 >= a.0.min(b.0) && p.0 <= a.0.max(b.0) && p.1 >= a.1.min(b.1) && p.1 <= a.1.max(b.1)
+}
+
+/// Symbol at the head of a list, e.g. "wire" for (wire (pts ...))
+fn head(v: &Value) -> Option<&str> {
+    v.list_iter()?.next()?.as_symbol()
+}
+
+/// All the elements of a list except the head
+fn args(v: &Value) -> impl Iterator<Item = &Value> {
+    v.list_iter().into_iter().flatten().skip(1)
+}
+
+fn children<'a>(v: &'a Value, name: &'a str) -> impl Iterator<Item = &'a Value> {
+    args(v).filter(move |c| head(c) == Some(name))
+}
+
+fn child<'a>(v: &'a Value, name: &str) -> Option<&'a Value> {
+    args(v).find(|c| head(c) == Some(name))
+}
+
+/// Argument as a string, symbols and numbers are converted as well
+fn arg_str(v: &Value, idx: usize) -> Option<String> {
+    let arg = args(v).nth(idx)?;
+    if let Some(s) = arg.as_str() {
+        Some(s.to_string())
+    } else if let Some(s) = arg.as_symbol() {
+        Some(s.to_string())
+    } else if arg.is_number() {
+        Some(arg.to_string())
+    } else {
+        None
+    }
+}
+
+fn arg_f64(v: &Value, idx: usize) -> Option<f64> {
+    args(v).nth(idx)?.as_f64()
+}
+
+/// (at x y angle), angle is optional
+fn at(v: &Value) -> Option<(f64, f64, f64)> {
+    let at = child(v, "at")?;
+    Some((
+        arg_f64(at, 0)?,
+        arg_f64(at, 1)?,
+        arg_f64(at, 2).unwrap_or(0.0),
+    ))
+}
+
+fn xy(v: &Value) -> Option<Point> {
+    Some((to_iu(arg_f64(v, 0)?), to_iu(arg_f64(v, 1)?)))
+}
+
+fn properties(v: &Value) -> HashMap<String, String> {
+    children(v, "property")
+        .filter_map(|p| Some((arg_str(p, 0)?, arg_str(p, 1)?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::load_kicad_schematic;
+    use crate::netlist::PinType;
+    use crate::parse_error::{ParseError, ParseErrorKind};
+    use crate::{Designator, NetName, PinId};
+    use std::path::Path;
+
+    #[test]
+    fn can_read_kicad_schematic() {
+        let netlist = load_kicad_schematic(Path::new(
+            "../test_schematics/sources/i2c_non_standard_pull_ups.kicad_sch",
+        ))
+        .unwrap();
+        let scl = netlist.nets().get(&NetName("/SCL".into())).unwrap();
+        assert!(
+            scl.nodes
+                .iter()
+                .any(|n| n.designator == Designator("U301".into()))
+        );
+        let component = netlist.components.get(&Designator("R301".into())).unwrap();
+        assert_eq!(component.value, "1K");
+        let lib_part = netlist.lib_parts.get(&component.lib_source).unwrap();
+        assert_eq!(
+            lib_part
+                .pins
+                .get(&PinId("1".into()))
+                .unwrap()
+                .default_mode
+                .ty,
+            PinType::Passive
+        );
+        assert!(netlist.nets().contains_key(&NetName("GND".into())));
+    }
+
+    #[test]
+    fn can_read_hierarchical_kicad_schematic() {
+        let netlist = load_kicad_schematic(Path::new(
+            "../test_schematics/sources/test_schematics.kicad_sch",
+        ))
+        .unwrap();
+        assert!(
+            netlist
+                .nets()
+                .keys()
+                .any(|n| n.0.starts_with("/C20: I2C no pull-ups/"))
+        );
+        let component = netlist.components.get(&Designator("U204".into())).unwrap();
+        assert_eq!(component.sections[0].page_number, Some(2));
+        assert_eq!(
+            component.location.sheet_path.as_deref(),
+            Some("/C20: I2C no pull-ups/")
+        );
+        assert_eq!(
+            component.location.sheet_tstamps.as_deref(),
+            Some("/29087d8a-217a-4d35-b399-b64f9dad2246/")
+        );
+        assert_eq!(
+            component.location.sheet_file.as_deref(),
+            Some("i2c_no_pull_ups.kicad_sch")
+        );
+        assert_eq!(component.location.position, Some((243.84, 100.33)));
+    }
+
+    #[test]
+    fn recursive_sheet_is_located() {
+        let path = Path::new("test_input/recursive_sheet/root.kicad_sch");
+        let error = load_kicad_schematic(path).unwrap_err();
+        let error = error.downcast_ref::<ParseError>().unwrap();
+        assert!(error.is_fatal());
+        assert_eq!(
+            error.path,
+            Path::new("test_input/recursive_sheet/child.kicad_sch")
+        );
+        assert_eq!(
+            error.kind,
+            ParseErrorKind::RecursiveSheet {
+                file: "root.kicad_sch".into()
+            }
+        );
+        assert_eq!(error.span.map(|s| s.start), Some((5, 3)));
+    }
+}
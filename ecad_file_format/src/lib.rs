@@ -1,20 +1,32 @@
 mod altium_netlist;
+pub mod bom;
 mod csv_util;
+pub mod detect;
 mod edif_netlist;
 pub mod kicad_netlist;
+pub mod kicad_schematic;
 pub mod netlist;
 pub mod orcad_netlist;
+pub mod parse_error;
 pub mod passive_value;
 pub mod pcb_assembly;
 pub mod pnp;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
 mod text_util;
 mod wirelist;
 
-pub use altium_netlist::load_altium_netlist;
-pub use kicad_netlist::load_kicad_netlist;
-pub use orcad_netlist::load_orcad_netlist;
+pub use altium_netlist::{load_altium_netlist, write_altium_netlist};
+pub use bom::load_bom;
+pub use detect::{DetectedNetlist, NetlistFormat, load_netlist};
+pub use edif_netlist::write_edif_netlist;
+pub use kicad_netlist::{load_kicad_netlist, write_kicad_netlist};
+pub use kicad_schematic::load_kicad_schematic;
+pub use orcad_netlist::{load_orcad_netlist, load_orcad_netlist_files};
+pub use parse_error::{ParseError, ParseErrorKind};
 pub use pnp::load_component_positions;
 use std::fmt::{Debug, Display, Formatter};
+pub use wirelist::write_wirelist_netlist;
 
 #[derive(Eq, PartialEq, Hash, Clone)]
 pub struct Designator(pub String);
@@ -1,15 +1,38 @@
-use crate::passive_value::{Ohm, parse_resistance_value};
+mod connectivity;
+
+pub use connectivity::ConnectivityIndex;
+
+use crate::parse_error::ParseError;
+use crate::passive_value::{
+    Farad, Henry, Ohm, parse_compound_capacitance, parse_compound_inductance,
+    parse_resistance_value,
+};
 use crate::{Designator, NetName, PinId, PinName};
 use anyhow::{Error, Result};
-use itertools::Itertools;
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Debug, Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::sync::OnceLock;
 
 #[derive(Debug, Default)]
 pub struct Netlist {
     pub lib_parts: HashMap<(LibName, LibPartName), LibPart>,
-    pub nets: HashMap<NetName, Net>,
+    /// Private so that the connectivity index can't go stale, see `nets_mut`
+    nets: HashMap<NetName, Net>,
     pub components: HashMap<Designator, Component>,
+    /// Recoverable problems found while loading, the items they refer to are skipped
+    pub warnings: Vec<ParseError>,
+    /// Built by `new` or on the first query, dropped by `nets_mut`
+    index: OnceLock<ConnectivityIndex>,
+}
+
+/// Limits for `Netlist::find_net_chains_limited`
+#[derive(Debug, Default, Clone)]
+pub struct ChainLimits {
+    /// Stop searching after this many chains are found
+    pub max_paths: Option<usize>,
+    /// Nets chains must not go through, for example ground. Start and end nets are never excluded.
+    pub exclude_nets: HashSet<NetName>,
 }
 
 #[derive(Clone, PartialEq, Eq, Hash)]
@@ -34,11 +57,29 @@ pub struct Component {
     pub lib_source: (LibName, LibPartName),
     pub fields: HashMap<String, String>,
     pub sections: Vec<ComponentSection>,
+    /// Where the component (or its first section for multi-section parts) is placed
+    pub location: SourceLocation,
 }
 
 pub struct ComponentSection {
     pub name: String,
     pub page_number: Option<u32>,
+    pub location: SourceLocation,
+}
+
+/// Where an object is placed in the source design, filled in as much as the source format allows
+#[derive(Default, Clone, PartialEq)]
+pub struct SourceLocation {
+    /// Human-readable sheet path, e.g. "/MCU_H7/"
+    pub sheet_path: Option<String>,
+    /// The same sheet path made of sheet timestamps or UUIDs, e.g. "/c16e79c7-955f-4686-8fe9-acff02e57751/"
+    pub sheet_tstamps: Option<String>,
+    /// File the sheet is stored in, e.g. "p2_mcu_h7.kicad_sch"
+    pub sheet_file: Option<String>,
+    /// Timestamp or UUID of the object itself
+    pub uuid: Option<String>,
+    /// X and Y coordinates on the sheet in mm
+    pub position: Option<(f32, f32)>,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -47,10 +88,12 @@ pub struct Net {
     pub properties: HashMap<String, String>,
 }
 
-#[derive(PartialEq, Eq, Clone, Hash)]
+/// Part's pin connected to a net, location is not part of node identity and is ignored in comparisons
+#[derive(Clone)]
 pub struct Node {
     pub designator: Designator,
     pub pin_id: PinId,
+    pub location: SourceLocation,
 }
 
 #[derive(Debug)]
@@ -73,16 +116,20 @@ pub struct PinMode {
     pub io_standard: Option<IOStandard>, // pub quiescent current vs bank voltage table
 }
 
-#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum PinType {
     DigitalInput,
     DigitalOutput,
+    #[serde(rename = "digital_io")]
     DigitalIO,
     AnalogInput,
     AnalogOutput,
+    #[serde(rename = "analog_io")]
     AnalogIO,
     PowerIn,
     PowerOut,
+    #[serde(rename = "power_io")]
     PowerIO,
     PowerUnspecified,
     OpenCollector,
@@ -97,6 +144,28 @@ pub enum PinType {
     Passive,
 }
 
+impl PinType {
+    /// In declaration order, so that `ty as usize` is the index
+    pub const ALL: [PinType; 16] = [
+        PinType::DigitalInput,
+        PinType::DigitalOutput,
+        PinType::DigitalIO,
+        PinType::AnalogInput,
+        PinType::AnalogOutput,
+        PinType::AnalogIO,
+        PinType::PowerIn,
+        PinType::PowerOut,
+        PinType::PowerIO,
+        PinType::PowerUnspecified,
+        PinType::OpenCollector,
+        PinType::OpenEmitter,
+        PinType::TriState,
+        PinType::Unconnected,
+        PinType::Unspecified,
+        PinType::Passive,
+    ];
+}
+
 #[derive(Debug)]
 pub enum IOStandard {
     LVTTL,
@@ -121,6 +190,39 @@ pub struct Bank {
 }
 
 impl Netlist {
+    /// Creates netlist with connectivity index already built
+    pub fn new(
+        lib_parts: HashMap<(LibName, LibPartName), LibPart>,
+        nets: HashMap<NetName, Net>,
+        components: HashMap<Designator, Component>,
+        warnings: Vec<ParseError>,
+    ) -> Self {
+        let netlist = Netlist {
+            lib_parts,
+            nets,
+            components,
+            warnings,
+            index: OnceLock::new(),
+        };
+        netlist.index();
+        netlist
+    }
+
+    pub fn index(&self) -> &ConnectivityIndex {
+        self.index
+            .get_or_init(|| ConnectivityIndex::new(&self.nets))
+    }
+
+    pub fn nets(&self) -> &HashMap<NetName, Net> {
+        &self.nets
+    }
+
+    /// Drops the connectivity index, it is rebuilt on the next query after the changes
+    pub fn nets_mut(&mut self) -> &mut HashMap<NetName, Net> {
+        self.index = OnceLock::new();
+        &mut self.nets
+    }
+
     /// Finds all the chains of parts connected in between two nets, each going through particular designators
     ///
     /// Example finding I2C pull up resistors:
@@ -128,7 +230,7 @@ impl Netlist {
     /// # use ecad_file_format::netlist::Netlist;
     /// # use ecad_file_format::{Designator, NetName};
     /// # let netlist = Netlist::default();
-    /// netlist.find_net_chains(&NetName("SCL".into()), &[Designator::is_resistor, Designator::is_resistor], &NetName("SDA".into()))
+    /// netlist.find_net_chains(&NetName("SCL".into()), &[Designator::is_resistor, Designator::is_resistor], &NetName("SDA".into()));
     /// ```
     pub fn find_net_chains<F: Fn(&Designator) -> bool>(
         &self,
@@ -136,54 +238,83 @@ impl Netlist {
         goes_through: &[F],
         end: &NetName,
     ) -> Vec<Vec<(PinId, Designator)>> {
-        let mut goes_through = goes_through.iter().collect::<VecDeque<_>>();
-        let Some(goes_through_first) = goes_through.pop_front() else {
-            return Vec::new();
-        };
-        let Some(net) = self.nets.get(start) else {
-            return Vec::new();
+        self.find_net_chains_limited(start, goes_through, end, &ChainLimits::default())
+    }
+
+    /// Same as `find_net_chains`, but stops after `limits.max_paths` chains are found and never goes
+    /// through `limits.exclude_nets`.
+    ///
+    /// Chains are built hop by hop with a depth-first search: each next part must pass its predicate
+    /// and be connected to the previous one through a net other than the one previous part was entered
+    /// from. A part is never visited twice in one chain. Each chain item is the entry pin and the part.
+    pub fn find_net_chains_limited<F: Fn(&Designator) -> bool>(
+        &self,
+        start: &NetName,
+        goes_through: &[F],
+        end: &NetName,
+        limits: &ChainLimits,
+    ) -> Vec<Vec<(PinId, Designator)>> {
+        let mut chains = Vec::new();
+        let (Some(is_first), Some(net)) = (goes_through.first(), self.nets.get(start)) else {
+            return chains;
         };
-        // find to which part.pin_id's 'start' net is connected
-        let mut first_layer = HashSet::new();
+        let mut chain = Vec::with_capacity(goes_through.len());
         for node in &net.nodes {
-            if goes_through_first(&node.designator) {
-                first_layer.insert((node.pin_id.clone(), node.designator.clone()));
+            if !is_first(&node.designator) {
+                continue;
             }
-        }
-        // println!("first layer {:?}", first_layer);
-        // for each go-through part, find all part.pin_id's for all of its pins except entry one
-        let mut links = vec![first_layer];
-        while let Some(goes_through_next) = goes_through.pop_front() {
-            let last_starts = links.last().unwrap();
-            let mut next_layer = HashSet::new();
-            for (except_pin, last_start) in last_starts {
-                next_layer.extend(self.find_reachable_pins(
-                    last_start,
-                    except_pin,
-                    goes_through_next,
-                ));
+            chain.push((node.pin_id.clone(), node.designator.clone()));
+            self.extend_net_chain(goes_through, end, limits, &mut chain, &mut chains);
+            chain.pop();
+            if limits.is_reached(&chains) {
+                break;
             }
-            // println!("next_layer {:?}", next_layer);
-            links.push(next_layer);
         }
-        // for the last layer, leave only the parts that are connected to 'end' net
-        links
-            .last_mut()
-            .unwrap()
-            .retain(|(_, designator)| self.is_connected(designator, end));
-        // println!("final {links:?}");
-        // all possible chains
-        let mut chains: Vec<_> = links.iter().multi_cartesian_product().collect();
-        // println!("chains {:?}", chains);
-        // remove broken chains
-        chains.retain(|c| {
-            c.windows(2)
-                .all(|w| self.are_parts_connected(&w[0].1, &w[1].1))
-        });
         chains
-            .into_iter()
-            .map(|c| c.into_iter().cloned().collect())
-            .collect()
+    }
+
+    fn extend_net_chain<F: Fn(&Designator) -> bool>(
+        &self,
+        goes_through: &[F],
+        end: &NetName,
+        limits: &ChainLimits,
+        chain: &mut Vec<(PinId, Designator)>,
+        chains: &mut Vec<Vec<(PinId, Designator)>>,
+    ) {
+        let Some((entry_pin, part)) = chain.last().cloned() else {
+            return;
+        };
+        let Some(is_next) = goes_through.get(chain.len()) else {
+            if self.is_connected(&part, end) {
+                chains.push(chain.clone());
+            }
+            return;
+        };
+        let index = self.index();
+        let entry_net = index.node_net(&part, &entry_pin);
+        let mut visited_nets = HashSet::new();
+        for (_, net_name) in index.part_nodes(&part) {
+            if Some(net_name) == entry_net
+                || limits.exclude_nets.contains(net_name)
+                || !visited_nets.insert(net_name)
+            {
+                continue;
+            }
+            let Some(net) = self.nets.get(net_name) else {
+                continue;
+            };
+            for node in &net.nodes {
+                if !is_next(&node.designator) || chain.iter().any(|(_, d)| d == &node.designator) {
+                    continue;
+                }
+                chain.push((node.pin_id.clone(), node.designator.clone()));
+                self.extend_net_chain(goes_through, end, limits, chain, chains);
+                chain.pop();
+                if limits.is_reached(chains) {
+                    return;
+                }
+            }
+        }
     }
 
     /// Returns a set of pins that are reachable from any pins of 'start' part, except via its 'except_pin'
@@ -193,65 +324,49 @@ impl Netlist {
         except_pin: &PinId,
         end_filter: F,
     ) -> HashSet<(PinId, Designator)> {
+        let index = self.index();
+        let except_net = index.node_net(start, except_pin);
         let mut found = HashSet::new();
-        'outer: for (_net_name, net) in &self.nets {
-            let mut potential = vec![];
-            let mut start_found = false;
+        for (_pin_id, net_name) in index.part_nodes(start) {
+            if Some(net_name) == except_net {
+                continue;
+            }
+            let Some(net) = self.nets.get(net_name) else {
+                continue;
+            };
             for node in &net.nodes {
-                if &node.designator == start && &node.pin_id == except_pin {
-                    continue 'outer;
-                }
-                if &node.designator == start {
-                    start_found = true;
-                }
                 if start != &node.designator && end_filter(&node.designator) {
-                    potential.push((node.pin_id.clone(), node.designator.clone()));
+                    found.insert((node.pin_id.clone(), node.designator.clone()));
                 }
             }
-            if start_found {
-                found.extend(potential);
-            }
         }
         found
     }
 
     /// Returns true if part is connected to net via any of its pins
     pub fn is_connected(&self, part: &Designator, target_net: &NetName) -> bool {
-        for (net_name, net) in &self.nets {
-            if net_name != target_net {
-                continue;
-            }
-            for node in &net.nodes {
-                if node.designator == *part {
-                    return true;
-                }
-            }
-        }
-        false
+        self.index()
+            .net_parts(target_net)
+            .is_some_and(|parts| parts.contains(part))
     }
 
     /// Returns true if two parts are connected via any of their pins
     pub fn are_parts_connected(&self, part_a: &Designator, part_b: &Designator) -> bool {
-        for (_, net) in &self.nets {
-            let contains_a = net.nodes.iter().any(|n| &n.designator == part_a);
-            let contains_b = net.nodes.iter().any(|n| &n.designator == part_b);
-            if contains_a && contains_b {
-                return true;
-            }
-        }
-        false
+        self.index()
+            .part_nodes(part_a)
+            .iter()
+            .any(|(_, net_name)| self.is_connected(part_b, net_name))
     }
 
     /// Returns net name for the part's pin
     pub fn pin_net(&self, part: &Designator, pin: &PinId) -> Option<NetName> {
-        for (net_name, net) in &self.nets {
-            for node in &net.nodes {
-                if node.designator == *part && node.pin_id == *pin {
-                    return Some(net_name.clone());
-                }
-            }
-        }
-        None
+        self.index().node_net(part, pin).cloned()
+    }
+
+    /// Returns library pin of the part, if both the part and its library part are known
+    pub fn pin(&self, part: &Designator, pin: &PinId) -> Option<&Pin> {
+        let component = self.components.get(part)?;
+        self.lib_parts.get(&component.lib_source)?.pins.get(pin)
     }
 
     /// Returns list of parts that have connection to any of the specified nets
@@ -271,15 +386,11 @@ impl Netlist {
 
     /// Returns list of nets part is connected to
     pub fn part_nets(&self, part: &Designator) -> HashSet<NetName> {
-        let mut nets = HashSet::new();
-        for (net_name, net) in &self.nets {
-            for node in &net.nodes {
-                if node.designator == *part {
-                    nets.insert(net_name.clone());
-                }
-            }
-        }
-        nets
+        self.index()
+            .part_nodes(part)
+            .iter()
+            .map(|(_, net_name)| net_name.clone())
+            .collect()
     }
 
     /// Returns list of nets between two parts
@@ -296,30 +407,46 @@ impl Netlist {
         exclude: &[&PinName],
     ) -> Vec<NetName> {
         let mut nets = vec![];
-        for (net_name, net) in &self.nets {
-            for node in &net.nodes {
-                if node.designator != *part {
-                    continue;
-                }
-
-                let Some(component) = self.components.get(&node.designator) else {
-                    continue;
-                };
-                let Some(lib_part) = self.lib_parts.get(&component.lib_source) else {
-                    continue;
-                };
-                let Some(pin) = lib_part.pins.get(&node.pin_id) else {
-                    continue;
-                };
-
-                if !exclude.contains(&&pin.name) {
-                    nets.push(net_name.clone());
-                }
+        let Some(component) = self.components.get(part) else {
+            return nets;
+        };
+        let Some(lib_part) = self.lib_parts.get(&component.lib_source) else {
+            return nets;
+        };
+        for (pin_id, net_name) in self.index().part_nodes(part) {
+            let Some(pin) = lib_part.pins.get(pin_id) else {
+                continue;
+            };
+            if !exclude.contains(&&pin.name) {
+                nets.push(net_name.clone());
             }
         }
         nets
     }
 
+    /// Returns where the part is placed in the source design, if it was known to the source format
+    pub fn component_location(&self, designator: &Designator) -> Option<&SourceLocation> {
+        let location = &self.components.get(designator)?.location;
+        if location.is_empty() {
+            None
+        } else {
+            Some(location)
+        }
+    }
+
+    /// Returns where the pin is placed in the source design, or where its part is if the source
+    /// format has no pin positions
+    pub fn node_location(&self, designator: &Designator, pin: &PinId) -> Option<&SourceLocation> {
+        let net = self.index().node_net(designator, pin)?;
+        let node = self.nets.get(net)?.nodes.iter().find(|node| {
+            &node.designator == designator && &node.pin_id == pin && !node.location.is_empty()
+        });
+        match node {
+            Some(node) => Some(&node.location),
+            None => self.component_location(designator),
+        }
+    }
+
     pub fn resistance(&self, designator: &Designator) -> Result<Ohm> {
         if !designator.0.starts_with('R') {
             return Err(Error::msg("{designator} is not a resistor"));
@@ -336,23 +463,50 @@ impl Netlist {
         }
     }
 
+    /// Capacitance of a capacitor, value field can contain ratings as well ("100nF 50V X7R")
+    pub fn capacitance(&self, designator: &Designator) -> Result<Farad> {
+        if !designator.is_capacitor() {
+            return Err(Error::msg(format!("{designator} is not a capacitor")));
+        }
+        let Some(component) = self.components.get(designator) else {
+            return Err(Error::msg(format!("{designator} not found")));
+        };
+        if component.value.is_empty() {
+            return Err(Error::msg(format!("{designator} has no value")));
+        }
+        let (val, _) = parse_compound_capacitance(component.value.as_str())?;
+        Ok(val.value)
+    }
+
+    /// Inductance of an inductor, value field can contain ratings as well ("2.2uH 20%")
+    pub fn inductance(&self, designator: &Designator) -> Result<Henry> {
+        if !designator.is_inductor() {
+            return Err(Error::msg(format!("{designator} is not an inductor")));
+        }
+        let Some(component) = self.components.get(designator) else {
+            return Err(Error::msg(format!("{designator} not found")));
+        };
+        if component.value.is_empty() {
+            return Err(Error::msg(format!("{designator} has no value")));
+        }
+        let (val, _) = parse_compound_inductance(component.value.as_str())?;
+        Ok(val.value)
+    }
+
     /// Returns all the nets that have specified pin types in them.
     pub fn find_nets_with_pin_types(&self, pin_types: &[PinType]) -> HashSet<NetName> {
+        let index = self.index();
         let mut nets = HashSet::new();
-        for (k, lib_part) in &self.lib_parts {
-            let components = self
-                .components
-                .iter()
-                .filter(|(_d, c)| &c.lib_source == k)
-                .collect::<Vec<_>>();
-            for (pin_id, pin) in &lib_part.pins {
-                if !pin_types.contains(&pin.default_mode.ty) {
+        for (designator, component) in &self.components {
+            let Some(lib_part) = self.lib_parts.get(&component.lib_source) else {
+                continue;
+            };
+            for (pin_id, net_name) in index.part_nodes(designator) {
+                let Some(pin) = lib_part.pins.get(pin_id) else {
                     continue;
-                }
-                for (designator, _c) in &components {
-                    if let Some(net) = self.pin_net(designator, pin_id) {
-                        nets.insert(net);
-                    }
+                };
+                if pin_types.contains(&pin.default_mode.ty) {
+                    nets.insert(net_name.clone());
                 }
             }
         }
@@ -360,6 +514,12 @@ impl Netlist {
     }
 }
 
+impl ChainLimits {
+    fn is_reached<T>(&self, chains: &[T]) -> bool {
+        self.max_paths.is_some_and(|max| chains.len() >= max)
+    }
+}
+
 impl Display for Netlist {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "Parts: {:?}", self.lib_parts)?;
@@ -399,6 +559,21 @@ impl Debug for LibPartName {
     }
 }
 
+impl PartialEq for Node {
+    fn eq(&self, other: &Self) -> bool {
+        self.designator == other.designator && self.pin_id == other.pin_id
+    }
+}
+
+impl Eq for Node {}
+
+impl Hash for Node {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.designator.hash(state);
+        self.pin_id.hash(state);
+    }
+}
+
 impl Display for Node {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "Node({}.{})", self.designator, self.pin_id)
@@ -429,8 +604,107 @@ impl Debug for ComponentSection {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "ComponentSection('{}', page: {:?})",
-            self.name, self.page_number
+            "ComponentSection('{}', page: {:?}, {})",
+            self.name, self.page_number, self.location
         )
     }
 }
+
+impl SourceLocation {
+    pub fn is_empty(&self) -> bool {
+        self == &SourceLocation::default()
+    }
+}
+
+impl Display for SourceLocation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match (&self.sheet_path, &self.sheet_tstamps) {
+            (Some(path), _) => write!(f, "sheet {path}")?,
+            (None, Some(tstamps)) => write!(f, "sheet {tstamps}")?,
+            (None, None) => write!(f, "sheet ?")?,
+        }
+        if let Some(file) = &self.sheet_file {
+            write!(f, " ({file})")?;
+        }
+        if let Some((x, y)) = self.position {
+            write!(f, " at ({x}, {y})")?;
+        }
+        Ok(())
+    }
+}
+impl Debug for SourceLocation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SourceLocation({self})")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChainLimits, Netlist};
+    use crate::test_util::NetlistBuilder;
+    use crate::{Designator, NetName, PinId};
+
+    /// SCL and SDA pulled up to 3V3 by R1 and R2, R3 pulls SCL down and R4 pulls SDA down
+    fn i2c_netlist() -> Netlist {
+        NetlistBuilder::new()
+            .net("SCL", &[("R1", "1"), ("R3", "1"), ("U1", "1")])
+            .net("SDA", &[("R2", "1"), ("R4", "1"), ("U1", "2")])
+            .net("+3V3", &[("R1", "2"), ("R2", "2")])
+            .net("GND", &[("R3", "2"), ("R4", "2")])
+            .build()
+    }
+
+    fn designators(chains: &[Vec<(PinId, Designator)>]) -> Vec<Vec<&str>> {
+        let mut chains = chains
+            .iter()
+            .map(|c| c.iter().map(|(_, d)| d.0.as_str()).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        chains.sort();
+        chains
+    }
+
+    #[test]
+    fn finds_chains_between_nets() {
+        let netlist = i2c_netlist();
+        let scl = NetName("SCL".into());
+        let sda = NetName("SDA".into());
+        let chains = netlist.find_net_chains(
+            &scl,
+            &[Designator::is_resistor, Designator::is_resistor],
+            &sda,
+        );
+        assert_eq!(designators(&chains), [["R1", "R2"], ["R3", "R4"]]);
+        assert_eq!(chains[0][0].0, PinId("1".into()));
+        assert_eq!(chains[0][1].0, PinId("2".into()));
+
+        // hops never go back through the entry net, so R1 -> SCL -> R3 is not a chain
+        let chains = netlist.find_net_chains(
+            &scl,
+            &[Designator::is_resistor, Designator::is_resistor],
+            &scl,
+        );
+        assert!(chains.is_empty());
+        let chains = netlist.find_net_chains(&scl, &[Designator::is_ic], &sda);
+        assert_eq!(designators(&chains), [["U1"]]);
+    }
+
+    #[test]
+    fn chain_limits_are_respected() {
+        let netlist = i2c_netlist();
+        let scl = NetName("SCL".into());
+        let sda = NetName("SDA".into());
+        let goes_through = [Designator::is_resistor, Designator::is_resistor];
+        let limits = ChainLimits {
+            max_paths: None,
+            exclude_nets: [NetName("GND".into())].into(),
+        };
+        let chains = netlist.find_net_chains_limited(&scl, &goes_through, &sda, &limits);
+        assert_eq!(designators(&chains), [["R1", "R2"]]);
+        let limits = ChainLimits {
+            max_paths: Some(1),
+            ..Default::default()
+        };
+        let chains = netlist.find_net_chains_limited(&scl, &goes_through, &sda, &limits);
+        assert_eq!(chains.len(), 1);
+    }
+}
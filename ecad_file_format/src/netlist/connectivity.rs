@@ -0,0 +1,104 @@
+use crate::netlist::Net;
+use crate::{Designator, NetName, PinId};
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Debug, Formatter};
+
+/// Lookup tables for connectivity queries, so that they do not have to scan all the nets
+#[derive(Default)]
+pub struct ConnectivityIndex {
+    node_net: HashMap<(Designator, PinId), NetName>,
+    part_nodes: HashMap<Designator, Vec<(PinId, NetName)>>,
+    net_parts: HashMap<NetName, HashSet<Designator>>,
+}
+
+impl ConnectivityIndex {
+    pub fn new(nets: &HashMap<NetName, Net>) -> Self {
+        let mut index = ConnectivityIndex::default();
+        for (net_name, net) in nets {
+            let parts = index.net_parts.entry(net_name.clone()).or_default();
+            for node in &net.nodes {
+                parts.insert(node.designator.clone());
+                let key = (node.designator.clone(), node.pin_id.clone());
+                if index.node_net.contains_key(&key) {
+                    // Malformed netlist with a pin in several nets, only one of them is kept
+                    continue;
+                }
+                index.node_net.insert(key, net_name.clone());
+                index
+                    .part_nodes
+                    .entry(node.designator.clone())
+                    .or_default()
+                    .push((node.pin_id.clone(), net_name.clone()));
+            }
+        }
+        index
+    }
+
+    /// Net the part's pin is connected to
+    pub fn node_net(&self, part: &Designator, pin: &PinId) -> Option<&NetName> {
+        self.node_net.get(&(part.clone(), pin.clone()))
+    }
+
+    /// All the connected pins of a part with the nets they are connected to
+    pub fn part_nodes(&self, part: &Designator) -> &[(PinId, NetName)] {
+        self.part_nodes
+            .get(part)
+            .map(|nodes| nodes.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Parts that have at least one pin connected to the net
+    pub fn net_parts(&self, net: &NetName) -> Option<&HashSet<Designator>> {
+        self.net_parts.get(net)
+    }
+}
+
+impl Debug for ConnectivityIndex {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "ConnectivityIndex({} nodes, {} parts, {} nets)",
+            self.node_net.len(),
+            self.part_nodes.len(),
+            self.net_parts.len()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::{NetlistBuilder, net};
+    use crate::{Designator, NetName, PinId};
+
+    #[test]
+    fn queries_use_index() {
+        let mut netlist = NetlistBuilder::new()
+            .net("A", &[("R1", "1"), ("U1", "5")])
+            .net("B", &[("R1", "2"), ("R2", "1")])
+            .net("GND", &[("R2", "2"), ("U1", "3")])
+            .build();
+        let r1 = Designator("R1".into());
+        let r2 = Designator("R2".into());
+        let u1 = Designator("U1".into());
+        assert_eq!(
+            netlist.pin_net(&r1, &PinId("2".into())),
+            Some(NetName("B".into()))
+        );
+        assert_eq!(netlist.pin_net(&r1, &PinId("3".into())), None);
+        assert_eq!(netlist.part_nets(&u1).len(), 2);
+        assert!(netlist.is_connected(&r2, &NetName("GND".into())));
+        assert!(!netlist.is_connected(&r1, &NetName("GND".into())));
+        assert!(netlist.are_parts_connected(&r1, &r2));
+        let reachable = netlist.find_reachable_pins(&r1, &PinId("1".into()), |_| true);
+        assert_eq!(reachable.len(), 1);
+        assert!(reachable.contains(&(PinId("1".into()), r2.clone())));
+
+        netlist
+            .nets_mut()
+            .insert(NetName("C".into()), net(&[("R1", "3"), ("U1", "7")]));
+        assert_eq!(
+            netlist.pin_net(&r1, &PinId("3".into())),
+            Some(NetName("C".into()))
+        );
+    }
+}
@@ -1,6 +1,7 @@
-use crate::netlist::{Net, Netlist, Node};
+use crate::netlist::{Net, Netlist, Node, SourceLocation};
+use crate::parse_error::{ParseError, ParseErrorKind};
 use crate::{Designator, NetName, PinId};
-use anyhow::{Error, Result};
+use anyhow::Result;
 use pest::Parser;
 use pest_derive::Parser;
 use std::collections::{HashMap, HashSet};
@@ -11,27 +12,37 @@ use std::path::{Path, PathBuf};
 #[grammar = "grammar/orcad_capture_netlist.pest"]
 struct NetListParser;
 
+/// Loads OrCAD netlist, part list and library parts are expected next to pstxnet.dat with
+/// `pstxnet.dat` replaced by `pstxprt.dat` and `pstchip.dat` in the file name
 pub fn load_orcad_netlist(pstxnet_path: &Path) -> Result<Netlist> {
-    let contents = read_to_string(pstxnet_path)?;
     let pstxprt_path = PathBuf::from(
         pstxnet_path
-            .to_str()
-            .unwrap()
+            .to_string_lossy()
             .replace("pstxnet.dat", "pstxprt.dat"),
     );
-    let mut components = part_list_parser::load_lib_parts(&pstxprt_path)?;
     let pstchip_path = PathBuf::from(
         pstxnet_path
-            .to_str()
-            .unwrap()
+            .to_string_lossy()
             .replace("pstxnet.dat", "pstchip.dat"),
     );
-    let lib_parts = lib_parts_parser::load_lib_parts(&pstchip_path, &mut components)?;
+    load_orcad_netlist_files(pstxnet_path, &pstxprt_path, &pstchip_path)
+}
+
+/// Loads OrCAD netlist from explicitly given pstxnet.dat, pstxprt.dat and pstchip.dat files
+pub fn load_orcad_netlist_files(
+    pstxnet_path: &Path,
+    pstxprt_path: &Path,
+    pstchip_path: &Path,
+) -> Result<Netlist> {
+    let contents = read_to_string(pstxnet_path).map_err(|e| ParseError::io(pstxnet_path, e))?;
+    let mut components = part_list_parser::load_lib_parts(pstxprt_path)?;
+    let lib_parts = lib_parts_parser::load_lib_parts(pstchip_path, &mut components)?;
 
     let pairs = match NetListParser::parse(Rule::file, &contents) {
         Ok(pairs) => pairs,
-        Err(e) => return Err(Error::msg(format!("{e}"))),
+        Err(e) => return Err(ParseError::from_pest(pstxnet_path, e).into()),
     };
+    let mut warnings = vec![];
     let mut nets = HashMap::new();
     // println!("{pairs:#?}");
     let rule_file = pairs.into_iter().next().unwrap();
@@ -39,6 +50,7 @@ pub fn load_orcad_netlist(pstxnet_path: &Path) -> Result<Netlist> {
         match pair.as_rule() {
             Rule::exporter_comment => {}
             Rule::net => {
+                let net_pair = pair.clone();
                 let mut net_name = None;
                 let mut nodes = HashSet::new();
                 for pair in pair.into_inner().into_iter() {
@@ -68,6 +80,7 @@ pub fn load_orcad_netlist(pstxnet_path: &Path) -> Result<Netlist> {
                                 nodes.insert(Node {
                                     designator: Designator(part_ref),
                                     pin_id: PinId(part_pin),
+                                    location: SourceLocation::default(),
                                 });
                             }
                         }
@@ -75,9 +88,25 @@ pub fn load_orcad_netlist(pstxnet_path: &Path) -> Result<Netlist> {
                     }
                 }
                 if let Some(net_name) = net_name {
-                    // TODO: Emit warning if replaces existing net?
+                    if net_name.is_empty() {
+                        warnings.push(ParseError::at_pair(
+                            pstxnet_path,
+                            &net_pair,
+                            ParseErrorKind::EmptyNetName,
+                        ));
+                        continue;
+                    }
+                    let net_name = NetName(net_name);
+                    if nets.contains_key(&net_name) {
+                        warnings.push(ParseError::at_pair(
+                            pstxnet_path,
+                            &net_pair,
+                            ParseErrorKind::DuplicateNet(net_name),
+                        ));
+                        continue;
+                    }
                     nets.insert(
-                        NetName(net_name),
+                        net_name,
                         Net {
                             nodes,
                             properties: HashMap::new(),
@@ -89,36 +118,32 @@ pub fn load_orcad_netlist(pstxnet_path: &Path) -> Result<Netlist> {
             _ => {}
         }
     }
-    Ok(Netlist {
-        lib_parts,
-        nets,
-        components,
-    })
+    Ok(Netlist::new(lib_parts, nets, components, warnings))
 }
 
 mod lib_parts_parser {
     use crate::netlist::{Component, LibName, LibPart, LibPartName, Pin, PinMode, PinType};
+    use crate::parse_error::{ParseError, ParseErrorKind};
     use crate::{Designator, PinId, PinName};
-    use anyhow::Error;
     use pest::Parser;
     use pest_derive::Parser;
     use std::collections::HashMap;
     use std::fs::read_to_string;
-    use std::path::PathBuf;
+    use std::path::Path;
 
     #[derive(Parser)]
     #[grammar = "grammar/orcad_capture_library_parts.pest"]
     struct LibPartsParser;
 
     pub(super) fn load_lib_parts(
-        pstchip_path: &PathBuf,
+        pstchip_path: &Path,
         components: &mut HashMap<Designator, Component>,
     ) -> anyhow::Result<HashMap<(LibName, LibPartName), LibPart>> {
-        let contents = read_to_string(pstchip_path)?;
+        let contents = read_to_string(pstchip_path).map_err(|e| ParseError::io(pstchip_path, e))?;
 
         let pairs = match LibPartsParser::parse(Rule::file, &contents) {
             Ok(pairs) => pairs,
-            Err(e) => return Err(Error::msg(format!("{e}"))),
+            Err(e) => return Err(ParseError::from_pest(pstchip_path, e).into()),
         };
         let mut lib_parts = HashMap::new();
         let rule_file = pairs.into_iter().next().unwrap();
@@ -143,8 +168,8 @@ mod lib_parts_parser {
                         .find(|c| c.lib_source.1 == lib_part_name)
                         .map(|c| c.sections.as_slice());
                     let mut pins_collect = HashMap::new();
-                    for pin in pins.into_inner() {
-                        let mut pin = pin.into_inner();
+                    for pin_pair in pins.into_inner() {
+                        let mut pin = pin_pair.clone().into_inner();
                         let pin_name = pin.next().unwrap().into_inner().next().unwrap().as_str();
                         let mut params = HashMap::new();
                         for param in pin {
@@ -158,7 +183,20 @@ mod lib_parts_parser {
                         let Some(pin_id) = params.get("PIN_NUMBER") else {
                             continue;
                         };
-                        let pin_id = &pin_id[1..pin_id.len() - 1];
+                        // e.g. "(1)", or "(0,C6,0)" with a position per section
+                        let Some(pin_id) =
+                            pin_id.strip_prefix('(').and_then(|p| p.strip_suffix(')'))
+                        else {
+                            return Err(ParseError::at_pair(
+                                pstchip_path,
+                                &pin_pair,
+                                ParseErrorKind::UnexpectedContent {
+                                    expected: "PIN_NUMBER in parentheses".into(),
+                                    found: format!("PIN_NUMBER='{pin_id}'"),
+                                },
+                            )
+                            .into());
+                        };
                         let mut section_name = None;
                         let pin_id = if let Some(sections) = component_sections {
                             if sections.len() > 1 {
@@ -166,7 +204,7 @@ mod lib_parts_parser {
                                 let idx_pin_id =
                                     nulls_and_name.enumerate().find(|(_, n)| n != &"0");
                                 if let Some((idx, pin_id)) = idx_pin_id {
-                                    section_name = Some(sections[idx].name.clone());
+                                    section_name = sections.get(idx).map(|s| s.name.clone());
                                     pin_id
                                 } else {
                                     pin_id
@@ -255,31 +293,31 @@ mod lib_parts_parser {
 
 mod part_list_parser {
     use crate::Designator;
-    use crate::netlist::{Component, ComponentSection, LibName, LibPartName};
-    use anyhow::Error;
+    use crate::netlist::{Component, ComponentSection, LibName, LibPartName, SourceLocation};
+    use crate::parse_error::ParseError;
     use pest::Parser;
     use pest_derive::Parser;
     use regex::Regex;
     use std::collections::HashMap;
     use std::fs::read_to_string;
-    use std::path::PathBuf;
+    use std::path::Path;
 
     #[derive(Parser)]
     #[grammar = "grammar/orcad_capture_part_list.pest"]
     struct PartListParser;
 
     pub(super) fn load_lib_parts(
-        pstchip_path: &PathBuf,
+        pstchip_path: &Path,
     ) -> anyhow::Result<HashMap<Designator, Component>> {
-        let contents = read_to_string(pstchip_path)?;
+        let contents = read_to_string(pstchip_path).map_err(|e| ParseError::io(pstchip_path, e))?;
 
         let pairs = match PartListParser::parse(Rule::file, &contents) {
             Ok(pairs) => pairs,
-            Err(e) => return Err(Error::msg(format!("{e}"))),
+            Err(e) => return Err(ParseError::from_pest(pstchip_path, e).into()),
         };
         let mut components = HashMap::new();
         let rule_file = pairs.into_iter().next().unwrap();
-        let page_re = Regex::new("@[\\w.()]+:page(\\d+).*")?;
+        let page_re = Regex::new("(@[\\w.()]+:page(\\d+)).*")?;
         for pair in rule_file.into_inner().into_iter() {
             match pair.as_rule() {
                 Rule::exporter_comment => {}
@@ -297,8 +335,9 @@ mod part_list_parser {
                             let _instance_name = section.next().unwrap();
                             let _c_path = section.next().unwrap();
                             let p_path = section.next().unwrap().as_str();
-                            let page_number = if let Some(caps) = page_re.captures(p_path) {
-                                if let Some(c) = caps.get(1) {
+                            let caps = page_re.captures(p_path);
+                            let page_number = if let Some(caps) = &caps {
+                                if let Some(c) = caps.get(2) {
                                     c.as_str().parse::<u32>().ok()
                                 } else {
                                     None
@@ -306,6 +345,8 @@ mod part_list_parser {
                             } else {
                                 None
                             };
+                            let sheet_path =
+                                caps.and_then(|c| c.get(1)).map(|c| c.as_str().to_string());
                             let _prim_file = section.next().unwrap();
                             let section_name = section
                                 .next()
@@ -317,8 +358,16 @@ mod part_list_parser {
                             sections.push(ComponentSection {
                                 name: section_name.to_string(),
                                 page_number,
+                                location: SourceLocation {
+                                    sheet_path,
+                                    ..Default::default()
+                                },
                             })
                         }
+                        let location = sections
+                            .first()
+                            .map(|s| s.location.clone())
+                            .unwrap_or_default();
                         components.insert(
                             Designator(designator.to_string()),
                             Component {
@@ -330,6 +379,7 @@ mod part_list_parser {
                                 ),
                                 fields: Default::default(),
                                 sections,
+                                location,
                             },
                         );
                     }
@@ -353,22 +403,25 @@ mod tests {
         let netlist =
             load_orcad_netlist(&PathBuf::from("test_input/netlist_orcad_pstxnet.dat")).unwrap();
         // println!("{:#?}", netlist);
-        assert_eq!(netlist.nets.len(), 3);
+        assert_eq!(netlist.nets().len(), 3);
         assert_eq!(
-            netlist.nets.get(&NetName("TOUCH_INT_N".into())),
+            netlist.nets().get(&NetName("TOUCH_INT_N".into())),
             Some(&Net {
                 nodes: [
                     Node {
                         designator: Designator("R610".to_string()),
-                        pin_id: PinId("2".to_string())
+                        pin_id: PinId("2".to_string()),
+                        location: Default::default(),
                     },
                     Node {
                         designator: Designator("Q34".to_string()),
-                        pin_id: PinId("3".to_string())
+                        pin_id: PinId("3".to_string()),
+                        location: Default::default(),
                     },
                     Node {
                         designator: Designator("R636".to_string()),
-                        pin_id: PinId("1".to_string())
+                        pin_id: PinId("1".to_string()),
+                        location: Default::default(),
                     }
                 ]
                 .into(),
@@ -386,5 +439,59 @@ mod tests {
             assert_eq!(pin.default_mode.ty, PinType::DigitalIO);
         }
         assert!(found);
+        let location = netlist
+            .component_location(&Designator("C1".into()))
+            .unwrap();
+        assert_eq!(
+            location.sheet_path.as_deref(),
+            Some("@pcba_xx.schematic1(sch_1):page3")
+        );
+    }
+
+    #[test]
+    fn first_definition_of_duplicate_net_is_kept() {
+        let netlist = load_orcad_netlist_files(
+            Path::new("test_input/duplicate_net/netlist_orcad_pstxnet.dat"),
+            Path::new("test_input/netlist_orcad_pstxprt.dat"),
+            Path::new("test_input/netlist_orcad_pstchip.dat"),
+        )
+        .unwrap();
+        assert_eq!(netlist.nets().len(), 1);
+        let net = netlist.nets().get(&NetName("CLKO2".into())).unwrap();
+        let first_node = Node {
+            designator: Designator("TP36".into()),
+            pin_id: PinId("1".into()),
+            location: Default::default(),
+        };
+        assert_eq!(net.nodes, [first_node].into());
+        let kinds = netlist
+            .warnings
+            .iter()
+            .map(|w| w.kind.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            [
+                ParseErrorKind::EmptyNetName,
+                ParseErrorKind::DuplicateNet(NetName("CLKO2".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn malformed_pin_number_is_an_error() {
+        let path = Path::new("test_input/netlist_orcad_malformed_pin_pstchip.dat");
+        let error = load_orcad_netlist_files(
+            Path::new("test_input/netlist_orcad_pstxnet.dat"),
+            Path::new("test_input/netlist_orcad_pstxprt.dat"),
+            path,
+        )
+        .unwrap_err();
+        let error = error.downcast_ref::<ParseError>().unwrap();
+        assert_eq!(error.path, path);
+        assert!(matches!(
+            error.kind,
+            ParseErrorKind::UnexpectedContent { .. }
+        ));
     }
 }
@@ -0,0 +1,195 @@
+use crate::NetName;
+use pest::RuleType;
+use pest::error::{ErrorVariant, LineColLocation};
+use pest::iterators::Pair;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+/// Problem found while loading a design file.
+///
+/// Fatal errors stop loading and are returned from loaders inside `anyhow::Error`, use
+/// `error.downcast_ref::<ParseError>()` to get to them. Recoverable ones are collected into
+/// `Netlist::warnings` and the offending item is skipped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub path: PathBuf,
+    /// Where in the file the problem is, if the format parser keeps track of it
+    pub span: Option<Span>,
+    pub kind: ParseErrorKind,
+}
+
+/// One-based (line, column) of the first and the last character
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Span {
+    pub start: (usize, usize),
+    pub end: (usize, usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseErrorKind {
+    /// File cannot be opened or read
+    Io(String),
+    /// Input does not match the file format grammar
+    Syntax {
+        expected: Vec<String>,
+        /// None if end of line or file was reached
+        found: Option<String>,
+    },
+    /// Input cannot be parsed, with the message from the underlying parser
+    Invalid(String),
+    /// Input is syntactically valid, but is not what this file format should contain
+    UnexpectedContent { expected: String, found: String },
+    /// Hierarchical sheet that places one of the sheets it is placed in
+    RecursiveSheet { file: String },
+    /// Component without designator, skipped
+    EmptyDesignator { value: String },
+    /// Placed symbol refers to a library symbol that is not in the file, skipped
+    MissingLibSymbol { lib_id: String },
+    /// Net without name, skipped
+    EmptyNetName,
+    /// Net node with empty designator or pin, skipped
+    IncompleteNode { net: NetName },
+    /// Net is defined more than once, the first definition is kept and later ones are skipped
+    DuplicateNet(NetName),
+    /// Row of a table file that cannot be read, skipped
+    UnreadableRecord(String),
+}
+
+impl ParseErrorKind {
+    pub fn is_fatal(&self) -> bool {
+        matches!(
+            self,
+            ParseErrorKind::Io(_)
+                | ParseErrorKind::Syntax { .. }
+                | ParseErrorKind::Invalid(_)
+                | ParseErrorKind::UnexpectedContent { .. }
+                | ParseErrorKind::RecursiveSheet { .. }
+        )
+    }
+}
+
+impl ParseError {
+    pub fn new(path: &Path, span: Option<Span>, kind: ParseErrorKind) -> Self {
+        ParseError {
+            path: path.to_path_buf(),
+            span,
+            kind,
+        }
+    }
+
+    pub fn is_fatal(&self) -> bool {
+        self.kind.is_fatal()
+    }
+
+    pub(crate) fn io(path: &Path, error: impl Display) -> Self {
+        ParseError::new(path, None, ParseErrorKind::Io(error.to_string()))
+    }
+
+    /// Converts pest error, expected rules are reported by their grammar names
+    pub(crate) fn from_pest<R: RuleType>(path: &Path, error: pest::error::Error<R>) -> Self {
+        let span = match error.line_col {
+            LineColLocation::Pos(pos) => Span::at(pos),
+            LineColLocation::Span(start, end) => Span { start, end },
+        };
+        let expected = match &error.variant {
+            ErrorVariant::ParsingError { positives, .. } => {
+                positives.iter().map(|rule| format!("{rule:?}")).collect()
+            }
+            ErrorVariant::CustomError { message } => vec![message.clone()],
+        };
+        let found = error
+            .line()
+            .chars()
+            .skip(span.start.1.saturating_sub(1))
+            .take_while(|c| !c.is_whitespace())
+            .take(32)
+            .collect::<String>();
+        ParseError::new(
+            path,
+            Some(span),
+            ParseErrorKind::Syntax {
+                expected,
+                found: (!found.is_empty()).then_some(found),
+            },
+        )
+    }
+
+    pub(crate) fn at_pair<R: RuleType>(path: &Path, pair: &Pair<R>, kind: ParseErrorKind) -> Self {
+        let span = pair.as_span();
+        ParseError::new(
+            path,
+            Some(Span {
+                start: span.start_pos().line_col(),
+                end: span.end_pos().line_col(),
+            }),
+            kind,
+        )
+    }
+}
+
+impl Span {
+    pub fn at(line_col: (usize, usize)) -> Self {
+        Span {
+            start: line_col,
+            end: line_col,
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.path.display())?;
+        if let Some(span) = &self.span {
+            write!(f, ":{}:{}", span.start.0, span.start.1)?;
+        }
+        write!(f, ": {}", self.kind)
+    }
+}
+
+impl Display for ParseErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseErrorKind::Io(e) => write!(f, "cannot read file: {e}"),
+            ParseErrorKind::Syntax { expected, found } => {
+                match expected.as_slice() {
+                    [] => write!(f, "unexpected input")?,
+                    [one] => write!(f, "expected {one}")?,
+                    [many @ .., last] => write!(f, "expected {} or {last}", many.join(", "))?,
+                }
+                match found {
+                    Some(found) => write!(f, ", found \"{found}\""),
+                    None => write!(f, ", found end of line"),
+                }
+            }
+            ParseErrorKind::Invalid(message) => write!(f, "{message}"),
+            ParseErrorKind::UnexpectedContent { expected, found } => {
+                write!(f, "expected {expected}, found {found}")
+            }
+            ParseErrorKind::RecursiveSheet { file } => {
+                write!(f, "sheet {file} includes itself")
+            }
+            ParseErrorKind::EmptyDesignator { value } => {
+                write!(
+                    f,
+                    "component without designator (value \"{value}\") skipped"
+                )
+            }
+            ParseErrorKind::MissingLibSymbol { lib_id } => {
+                write!(f, "library symbol {lib_id} not found, symbol skipped")
+            }
+            ParseErrorKind::EmptyNetName => write!(f, "net without name skipped"),
+            ParseErrorKind::IncompleteNode { net } => {
+                write!(f, "node without designator or pin in {net} skipped")
+            }
+            ParseErrorKind::DuplicateNet(net) => write!(
+                f,
+                "{net} is defined more than once, later definitions skipped"
+            ),
+            ParseErrorKind::UnreadableRecord(message) => {
+                write!(f, "record cannot be read ({message}), skipped")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
@@ -1,3 +1,5 @@
+pub mod e_series;
+
 use anyhow::{Error, Result};
 use human_repr::HumanCount;
 use pest::Parser;
@@ -14,11 +16,48 @@ impl Debug for Ohm {
     }
 }
 
+#[derive(Clone, PartialEq, PartialOrd)]
+pub struct Farad(pub f32);
+
+impl Debug for Farad {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.human_count("F"))
+    }
+}
+
+#[derive(Clone, PartialEq, PartialOrd)]
+pub struct Henry(pub f32);
+
+impl Debug for Henry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.human_count("H"))
+    }
+}
+
+/// Passive value together with ratings that are often typed into the same field,
+/// for example "100nF 50V X7R 0402" or "10k 1%"
+#[derive(Debug, Clone, PartialEq)]
+pub struct PassiveValue<T> {
+    pub value: T,
+    /// Voltage rating in volts
+    pub voltage: Option<f32>,
+    /// Tolerance in percent
+    pub tolerance: Option<f32>,
+    /// Ceramic capacitor dielectric (X7R, C0G, ...)
+    pub dielectric: Option<String>,
+    /// Imperial size code (0402, 0603, ...)
+    pub package: Option<String>,
+}
+
 #[derive(Debug)]
 pub enum PassiveValueParseWarning {
     RedundantSpace,
     SmallR,
     BigRInsteadOfOhmSymbol,
+    /// "f" or "h" instead of "F" or "H"
+    LowercaseUnit,
+    /// Part of a compound value that is not a voltage, tolerance, dielectric or package
+    UnknownAttribute(String),
 }
 
 #[derive(Parser)]
@@ -31,6 +70,98 @@ pub fn parse_resistance_value(value: &str) -> Result<(Ohm, Option<PassiveValuePa
         Err(e) => return Err(Error::msg(format!("{e}"))),
     };
     let resistance = pairs.into_iter().next().unwrap();
+    resistance_from_pair(resistance)
+}
+
+pub fn parse_capacitance_value(value: &str) -> Result<(Farad, Option<PassiveValueParseWarning>)> {
+    let pairs = match PassiveValueParser::parse(Rule::capacitance, value) {
+        Ok(pairs) => pairs,
+        Err(e) => return Err(Error::msg(format!("{e}"))),
+    };
+    let (val, warning) = lc_from_pair(pairs.into_iter().next().unwrap())?;
+    Ok((Farad(val), warning))
+}
+
+pub fn parse_inductance_value(value: &str) -> Result<(Henry, Option<PassiveValueParseWarning>)> {
+    let pairs = match PassiveValueParser::parse(Rule::inductance, value) {
+        Ok(pairs) => pairs,
+        Err(e) => return Err(Error::msg(format!("{e}"))),
+    };
+    let (val, warning) = lc_from_pair(pairs.into_iter().next().unwrap())?;
+    Ok((Henry(val), warning))
+}
+
+pub fn parse_compound_resistance(
+    value: &str,
+) -> Result<(PassiveValue<Ohm>, Vec<PassiveValueParseWarning>)> {
+    parse_compound(Rule::compound_resistance, value, resistance_from_pair)
+}
+
+pub fn parse_compound_capacitance(
+    value: &str,
+) -> Result<(PassiveValue<Farad>, Vec<PassiveValueParseWarning>)> {
+    parse_compound(Rule::compound_capacitance, value, |pair| {
+        lc_from_pair(pair).map(|(val, warning)| (Farad(val), warning))
+    })
+}
+
+pub fn parse_compound_inductance(
+    value: &str,
+) -> Result<(PassiveValue<Henry>, Vec<PassiveValueParseWarning>)> {
+    parse_compound(Rule::compound_inductance, value, |pair| {
+        lc_from_pair(pair).map(|(val, warning)| (Henry(val), warning))
+    })
+}
+
+fn parse_compound<T>(
+    rule: Rule,
+    value: &str,
+    parse_value: impl Fn(Pair<Rule>) -> Result<(T, Option<PassiveValueParseWarning>)>,
+) -> Result<(PassiveValue<T>, Vec<PassiveValueParseWarning>)> {
+    let pairs = match PassiveValueParser::parse(rule, value) {
+        Ok(pairs) => pairs,
+        Err(e) => return Err(Error::msg(format!("{e}"))),
+    };
+    let mut pairs = pairs.into_iter();
+    let (value, warning) = parse_value(pairs.next().unwrap())?;
+    let mut warnings = warning.into_iter().collect::<Vec<_>>();
+    let mut passive_value = PassiveValue {
+        value,
+        voltage: None,
+        tolerance: None,
+        dielectric: None,
+        package: None,
+    };
+    for pair in pairs {
+        match pair.as_rule() {
+            Rule::voltage => {
+                // "6.3V" and "6V3" both have integer and fractional parts as separate numbers
+                let numbers = pair.into_inner().map(|p| p.as_str()).collect::<Vec<_>>();
+                passive_value.voltage = Some(numbers.join(".").parse()?);
+            }
+            Rule::tolerance => {
+                let tolerance = pair.as_str().trim_start_matches('±').trim_end_matches('%');
+                passive_value.tolerance = Some(tolerance.parse()?);
+            }
+            Rule::dielectric => {
+                let dielectric = match pair.as_str().to_uppercase().as_str() {
+                    "COG" => "C0G".to_string(),
+                    "NPO" => "NP0".to_string(),
+                    d => d.to_string(),
+                };
+                passive_value.dielectric = Some(dielectric);
+            }
+            Rule::package => passive_value.package = Some(pair.as_str().to_string()),
+            Rule::unknown_attribute => warnings.push(PassiveValueParseWarning::UnknownAttribute(
+                pair.as_str().to_string(),
+            )),
+            _ => {}
+        }
+    }
+    Ok((passive_value, warnings))
+}
+
+fn resistance_from_pair(resistance: Pair<Rule>) -> Result<(Ohm, Option<PassiveValueParseWarning>)> {
     let kind = resistance.as_rule();
     let mut pairs = resistance.into_inner();
     match kind {
@@ -61,6 +192,83 @@ pub fn parse_resistance_value(value: &str) -> Result<(Ohm, Option<PassiveValuePa
     }
 }
 
+/// Capacitance and inductance values share the same structure and prefixes
+fn lc_from_pair(pair: Pair<Rule>) -> Result<(f32, Option<PassiveValueParseWarning>)> {
+    let kind = pair.as_rule();
+    let mut pairs = pair.into_inner();
+    match kind {
+        Rule::c_not_delimited | Rule::l_not_delimited => {
+            let integer = pairs.next().unwrap().as_str();
+            let (mul, warning) = parse_prefix_unit(pairs.next())?;
+            let val: f32 = integer.parse()?;
+            Ok((val * mul, warning))
+        }
+        Rule::c_letter_delimited | Rule::l_letter_delimited => {
+            let integer = pairs.next().unwrap().as_str();
+            let mul = parse_lc_prefix(pairs.next().unwrap().into_inner().next().unwrap())?;
+            let fractional = pairs.next().unwrap().as_str();
+            let warning = pairs.next().and_then(|unit| unit_warning(&unit));
+            let val = format!("{}.{}", integer, fractional);
+            let val: f32 = val.parse()?;
+            Ok((val * mul, warning))
+        }
+        Rule::c_dot_delimited | Rule::l_dot_delimited => {
+            let integer = pairs.next().unwrap().as_str();
+            let fractional = pairs.next().unwrap().as_str();
+            let (mul, warning) = parse_prefix_unit(pairs.next())?;
+            let val = format!("{}.{}", integer, fractional);
+            let val: f32 = val.parse()?;
+            Ok((val * mul, warning))
+        }
+        _ => Err(Error::msg("Invalid rule")),
+    }
+}
+
+fn parse_prefix_unit(
+    prefix_unit: Option<Pair<Rule>>,
+) -> Result<(f32, Option<PassiveValueParseWarning>)> {
+    let Some(prefix_unit) = prefix_unit else {
+        return Ok((1.0, None));
+    };
+    let mut warning = None;
+    let mut mul = 1.0;
+    for p in prefix_unit.into_inner() {
+        match p.as_rule() {
+            Rule::space if p.as_str().len() > 1 => {
+                warning = Some(PassiveValueParseWarning::RedundantSpace);
+            }
+            Rule::lc_prefix => {
+                mul = parse_lc_prefix(p.into_inner().next().unwrap())?;
+            }
+            Rule::farad | Rule::henry => {
+                if let Some(w) = unit_warning(&p) {
+                    warning = Some(w);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok((mul, warning))
+}
+
+fn unit_warning(unit: &Pair<Rule>) -> Option<PassiveValueParseWarning> {
+    if unit.as_str() == "f" || unit.as_str() == "h" {
+        Some(PassiveValueParseWarning::LowercaseUnit)
+    } else {
+        None
+    }
+}
+
+fn parse_lc_prefix(pair: Pair<Rule>) -> Result<f32> {
+    match pair.as_rule() {
+        Rule::pico => Ok(0.000_000_000_001),
+        Rule::nano => Ok(0.000_000_001),
+        Rule::micro => Ok(0.000_001),
+        Rule::milli => Ok(0.001),
+        _ => Err(Error::msg("Invalid rule")),
+    }
+}
+
 fn parse_prefix_ohm(
     prefix_ohm: Option<Pair<Rule>>,
 ) -> Result<(f32, Option<PassiveValueParseWarning>)> {
@@ -119,6 +327,10 @@ fn parse_r_prefix(pair: Pair<Rule>) -> Result<(f32, Option<PassiveValueParseWarn
 mod tests {
     use super::*;
 
+    fn approx_eq(a: f32, b: f32) -> bool {
+        (a - b).abs() <= b.abs() * 1e-6
+    }
+
     #[test]
     fn resistance_values() {
         let (v, w) = parse_resistance_value("100").unwrap();
@@ -193,4 +405,90 @@ mod tests {
             Some(PassiveValueParseWarning::BigRInsteadOfOhmSymbol)
         ));
     }
+
+    #[test]
+    fn capacitance_values() {
+        let (v, w) = parse_capacitance_value("100n").unwrap();
+        assert!(approx_eq(v.0, 100e-9));
+        assert!(w.is_none());
+
+        let (v, w) = parse_capacitance_value("4u7").unwrap();
+        assert!(approx_eq(v.0, 4.7e-6));
+        assert!(w.is_none());
+
+        let (v, w) = parse_capacitance_value("10µF").unwrap();
+        assert!(approx_eq(v.0, 10e-6));
+        assert!(w.is_none());
+
+        let (v, w) = parse_capacitance_value("2.2pF").unwrap();
+        assert!(approx_eq(v.0, 2.2e-12));
+        assert!(w.is_none());
+
+        let (v, w) = parse_capacitance_value("1 nF").unwrap();
+        assert!(approx_eq(v.0, 1e-9));
+        assert!(w.is_none());
+
+        let (v, w) = parse_capacitance_value("1F").unwrap();
+        assert!(approx_eq(v.0, 1.0));
+        assert!(w.is_none());
+
+        let (v, w) = parse_capacitance_value("100nf").unwrap();
+        assert!(approx_eq(v.0, 100e-9));
+        assert!(matches!(w, Some(PassiveValueParseWarning::LowercaseUnit)));
+
+        let (_, w) = parse_capacitance_value("10  uF").unwrap();
+        assert!(matches!(w, Some(PassiveValueParseWarning::RedundantSpace)));
+
+        assert!(parse_capacitance_value("10k").is_err());
+    }
+
+    #[test]
+    fn inductance_values() {
+        let (v, w) = parse_inductance_value("2.2uH").unwrap();
+        assert!(approx_eq(v.0, 2.2e-6));
+        assert!(w.is_none());
+
+        let (v, w) = parse_inductance_value("4n7").unwrap();
+        assert!(approx_eq(v.0, 4.7e-9));
+        assert!(w.is_none());
+
+        let (v, w) = parse_inductance_value("10mH").unwrap();
+        assert!(approx_eq(v.0, 10e-3));
+        assert!(w.is_none());
+    }
+
+    #[test]
+    fn compound_values() {
+        let (v, w) = parse_compound_capacitance("100nF 50V X7R 0402").unwrap();
+        assert!(approx_eq(v.value.0, 100e-9));
+        assert_eq!(v.voltage, Some(50.0));
+        assert_eq!(v.dielectric.as_deref(), Some("X7R"));
+        assert_eq!(v.package.as_deref(), Some("0402"));
+        assert!(w.is_empty());
+
+        let (v, w) = parse_compound_capacitance("4u7/6V3/10%/cog").unwrap();
+        assert!(approx_eq(v.value.0, 4.7e-6));
+        assert_eq!(v.voltage, Some(6.3));
+        assert_eq!(v.tolerance, Some(10.0));
+        assert_eq!(v.dielectric.as_deref(), Some("C0G"));
+        assert!(w.is_empty());
+
+        let (v, w) = parse_compound_resistance("10k 1%").unwrap();
+        assert_eq!(v.value.0, 10_000.0);
+        assert_eq!(v.tolerance, Some(1.0));
+        assert!(w.is_empty());
+
+        let (v, w) = parse_compound_resistance("0R 0603 AEC-Q200").unwrap();
+        assert_eq!(v.value.0, 0.0);
+        assert_eq!(v.package.as_deref(), Some("0603"));
+        assert!(matches!(
+            &w[..],
+            [PassiveValueParseWarning::UnknownAttribute(a)] if a == "AEC-Q200"
+        ));
+
+        let (v, w) = parse_compound_inductance("2.2uH ±20%").unwrap();
+        assert!(approx_eq(v.value.0, 2.2e-6));
+        assert_eq!(v.tolerance, Some(20.0));
+        assert!(w.is_empty());
+    }
 }
@@ -0,0 +1,166 @@
+/// IEC 60063 preferred number series, ordered from coarse to fine
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ESeries {
+    E3,
+    E6,
+    E12,
+    E24,
+    E48,
+    E96,
+    E192,
+}
+
+/// E3 to E24 are every n-th value of E24, two significant digits
+const E24: [u16; 24] = [
+    10, 11, 12, 13, 15, 16, 18, 20, 22, 24, 27, 30, 33, 36, 39, 43, 47, 51, 56, 62, 68, 75, 82, 91,
+];
+
+/// E48 and E96 are every n-th value of E192, three significant digits
+const E192: [u16; 192] = [
+    100, 101, 102, 104, 105, 106, 107, 109, 110, 111, 113, 114, 115, 117, 118, 120, 121, 123, 124,
+    126, 127, 129, 130, 132, 133, 135, 137, 138, 140, 142, 143, 145, 147, 149, 150, 152, 154, 156,
+    158, 160, 162, 164, 165, 167, 169, 172, 174, 176, 178, 180, 182, 184, 187, 189, 191, 193, 196,
+    198, 200, 203, 205, 208, 210, 213, 215, 218, 221, 223, 226, 229, 232, 234, 237, 240, 243, 246,
+    249, 252, 255, 258, 261, 264, 267, 271, 274, 277, 280, 284, 287, 291, 294, 298, 301, 305, 309,
+    312, 316, 320, 324, 328, 332, 336, 340, 344, 348, 352, 357, 361, 365, 370, 374, 379, 383, 388,
+    392, 397, 402, 407, 412, 417, 422, 427, 432, 437, 442, 448, 453, 459, 464, 470, 475, 481, 487,
+    493, 499, 505, 511, 517, 523, 530, 536, 542, 549, 556, 562, 569, 576, 583, 590, 597, 604, 612,
+    619, 626, 634, 642, 649, 657, 665, 673, 681, 690, 698, 706, 715, 723, 732, 741, 750, 759, 768,
+    777, 787, 796, 806, 816, 825, 835, 845, 856, 866, 876, 887, 898, 909, 920, 931, 942, 953, 965,
+    976, 988,
+];
+
+impl ESeries {
+    pub const ALL: [ESeries; 7] = [
+        ESeries::E3,
+        ESeries::E6,
+        ESeries::E12,
+        ESeries::E24,
+        ESeries::E48,
+        ESeries::E96,
+        ESeries::E192,
+    ];
+
+    /// Tolerance in percent the series is designed for
+    pub fn tolerance(&self) -> f32 {
+        match self {
+            ESeries::E3 => 40.0,
+            ESeries::E6 => 20.0,
+            ESeries::E12 => 10.0,
+            ESeries::E24 => 5.0,
+            ESeries::E48 => 2.0,
+            ESeries::E96 => 1.0,
+            ESeries::E192 => 0.5,
+        }
+    }
+
+    /// Coarsest series that is designed for the given tolerance in percent or better
+    pub fn for_tolerance(tolerance: f32) -> ESeries {
+        ESeries::ALL
+            .into_iter()
+            .find(|series| series.tolerance() <= tolerance)
+            .unwrap_or(ESeries::E192)
+    }
+
+    /// Values of one decade scaled to 100..1000
+    pub fn mantissas(&self) -> Vec<u16> {
+        let (table, step, scale): (&[u16], usize, u16) = match self {
+            ESeries::E3 => (&E24, 8, 10),
+            ESeries::E6 => (&E24, 4, 10),
+            ESeries::E12 => (&E24, 2, 10),
+            ESeries::E24 => (&E24, 1, 10),
+            ESeries::E48 => (&E192, 4, 1),
+            ESeries::E96 => (&E192, 2, 1),
+            ESeries::E192 => (&E192, 1, 1),
+        };
+        table.iter().step_by(step).map(|m| m * scale).collect()
+    }
+
+    /// Whether value is one of the series values in any decade.
+    /// E48 and finer series also accept E24 values, as precision parts are commonly made in both.
+    pub fn contains(&self, value: f32) -> bool {
+        let is_standard = |series: ESeries| {
+            standard_values_around(series, value)
+                .iter()
+                .any(|v| (v - value as f64).abs() <= v * 1e-4)
+        };
+        is_standard(*self) || (*self >= ESeries::E48 && is_standard(ESeries::E24))
+    }
+
+    /// Closest series values below and above the given value, both are equal to it if value is
+    /// standard. None for zero, negative or non-finite values.
+    pub fn nearest(&self, value: f32) -> Option<(f32, f32)> {
+        let values = standard_values_around(*self, value);
+        let value = value as f64;
+        let lower = values
+            .iter()
+            .copied()
+            .filter(|v| *v <= value * (1.0 + 1e-4))
+            .reduce(f64::max)?;
+        let upper = values
+            .iter()
+            .copied()
+            .filter(|v| *v >= value * (1.0 - 1e-4))
+            .reduce(f64::min)?;
+        Some((lower as f32, upper as f32))
+    }
+}
+
+/// Series values of the decade value is in and the ones next to it
+fn standard_values_around(series: ESeries, value: f32) -> Vec<f64> {
+    if !value.is_finite() || value <= 0.0 {
+        return vec![];
+    }
+    let exponent = (value as f64).log10().floor() as i32 - 2;
+    let mantissas = series.mantissas();
+    let mut values = vec![];
+    for e in exponent - 1..=exponent + 1 {
+        for m in &mantissas {
+            values.push(*m as f64 * 10f64.powi(e));
+        }
+    }
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn series_sizes() {
+        let sizes = ESeries::ALL.map(|s| s.mantissas().len());
+        assert_eq!(sizes, [3, 6, 12, 24, 48, 96, 192]);
+        assert_eq!(ESeries::E6.mantissas(), [100, 150, 220, 330, 470, 680]);
+        assert!(ESeries::ALL.is_sorted());
+        assert!(ESeries::E48 > ESeries::E24);
+    }
+
+    #[test]
+    fn series_for_tolerance() {
+        assert_eq!(ESeries::for_tolerance(1.0), ESeries::E96);
+        assert_eq!(ESeries::for_tolerance(5.0), ESeries::E24);
+        assert_eq!(ESeries::for_tolerance(10.0), ESeries::E12);
+        assert_eq!(ESeries::for_tolerance(0.1), ESeries::E192);
+        assert_eq!(ESeries::for_tolerance(50.0), ESeries::E3);
+    }
+
+    #[test]
+    fn standard_values() {
+        assert!(ESeries::E96.contains(4870.0));
+        assert!(!ESeries::E24.contains(4870.0));
+        assert!(ESeries::E24.contains(5100.0));
+        assert!(ESeries::E96.contains(5100.0));
+        assert!(ESeries::E12.contains(4.7e-6));
+        assert!(ESeries::E6.contains(1e-6));
+        assert!(ESeries::E24.contains(0.1));
+        assert!(!ESeries::E12.contains(5100.0));
+    }
+
+    #[test]
+    fn nearest_values() {
+        assert_eq!(ESeries::E24.nearest(4800.0), Some((4700.0, 5100.0)));
+        assert_eq!(ESeries::E12.nearest(9000.0), Some((8200.0, 10000.0)));
+        assert_eq!(ESeries::E24.nearest(4700.0), Some((4700.0, 4700.0)));
+        assert_eq!(ESeries::E24.nearest(0.0), None);
+    }
+}
@@ -1,4 +1,5 @@
 use crate::Designator;
+use crate::bom::Bom;
 use crate::netlist::Netlist;
 use crate::pnp::ComponentPosition;
 use std::collections::HashMap;
@@ -8,5 +9,5 @@ pub struct PcbAssembly {
     pub name: Arc<String>,
     pub netlist: Netlist,
     pub pnp: HashMap<Designator, ComponentPosition>,
-    pub bom: (),
+    pub bom: Bom,
 }
@@ -1,7 +1,9 @@
 use crate::Designator;
 use crate::csv_util::{
-    MINIMUM_PNP_COLUMNS_REQUIRED, POSSIBLE_PNP_COLUMN_NAMES, determine_separator, find_header_row,
+    MINIMUM_PNP_COLUMNS_REQUIRED, POSSIBLE_PNP_COLUMN_NAMES, determine_separator, find_column_idx,
+    find_header_row,
 };
+use crate::parse_error::{ParseError, ParseErrorKind, Span};
 use anyhow::{Error, Result};
 use std::collections::HashMap;
 use std::path::Path;
@@ -44,16 +46,15 @@ pub fn load_component_positions(path: &Path) -> Result<HashMap<Designator, Compo
         &POSSIBLE_PNP_COLUMN_NAMES,
         path,
     )
-    .unwrap();
-    let separator = determine_separator(path).unwrap();
-    let Ok(reader) = csv::ReaderBuilder::new()
+    .ok_or(Error::msg("Column header not found"))?;
+    let separator =
+        determine_separator(path).ok_or_else(|| ParseError::io(path, "cannot open file"))?;
+    let reader = csv::ReaderBuilder::new()
         .delimiter(separator)
         .has_headers(false)
         .flexible(true)
         .from_path(path)
-    else {
-        return Err(Error::msg("Column header not found"));
-    };
+        .map_err(|e| ParseError::io(path, e))?;
 
     let designator_idx = find_column_idx(&header, &["Ref", "RefDes", "Designator"])
         .ok_or(Error::msg("Required column not found (designator)"))?;
@@ -72,29 +73,45 @@ pub fn load_component_positions(path: &Path) -> Result<HashMap<Designator, Compo
     let records = reader.into_records().skip(header_idx + 1);
     for record in records {
         let Ok(record) = record else { continue };
+        let line = record.position().map(|p| p.line() as usize).unwrap_or(0);
+        let unexpected = |expected: &str, found: &str| {
+            ParseError::new(
+                path,
+                Some(Span::at((line, 1))),
+                ParseErrorKind::UnexpectedContent {
+                    expected: expected.into(),
+                    found: found.into(),
+                },
+            )
+        };
         let values = record.iter().collect::<Vec<&str>>();
+        let required_columns = [designator_idx, x_idx, y_idx, a_idx, side_idx];
+        if required_columns.iter().any(|idx| *idx >= values.len()) {
+            let found = format!("{} columns", values.len());
+            return Err(unexpected("all required columns", &found).into());
+        }
         let side = match values[side_idx] {
             "Top" | "TOP" | "top" | "TopLayer" => Side::Top,
             "Bottom" | "BOTTOM" | "bottom" | "BottomLayer" => Side::Bottom,
-            _ => {
-                return Err(Error::msg(format!(
-                    "Unknown board side: {}",
-                    values[side_idx].to_string()
-                )));
+            side => {
+                return Err(unexpected("board side", side).into());
             }
         };
+        let number = |s: &str| s.parse::<f32>().map_err(|_| unexpected("number", s));
         let x_str = values[x_idx].strip_suffix("mm").unwrap_or(values[x_idx]);
         let y_str = values[y_idx].strip_suffix("mm").unwrap_or(values[y_idx]);
+        let optional =
+            |idx: Option<usize>| idx.and_then(|idx| values.get(idx)).map(|v| v.to_string());
         let position = ComponentPosition {
-            x: x_str.parse::<f32>()?,
+            x: number(x_str)?,
             x_str: x_str.to_string(),
-            y: y_str.parse::<f32>()?,
+            y: number(y_str)?,
             y_str: y_str.to_string(),
-            rotation: values[a_idx].parse::<f32>()?,
+            rotation: number(values[a_idx])?,
             rotation_str: values[a_idx].to_string(),
             side,
-            value: value_idx.map(|idx| values[idx].to_string()),
-            package: package_idx.map(|idx| values[idx].to_string()),
+            value: optional(value_idx),
+            package: optional(package_idx),
         };
         let designator = Designator(values[designator_idx].to_string());
         positions.insert(designator, position);
@@ -102,14 +119,6 @@ pub fn load_component_positions(path: &Path) -> Result<HashMap<Designator, Compo
     Ok(positions)
 }
 
-fn find_column_idx(columns: &[String], synonyms: &[&str]) -> Option<usize> {
-    columns
-        .iter()
-        .enumerate()
-        .find(|(_idx, c)| synonyms.contains(&c.as_str()))
-        .map(|(idx, _)| idx)
-}
-
 #[cfg(test)]
 mod tests {
     use crate::pnp::{Designator, Side, load_component_positions};
@@ -0,0 +1,109 @@
+//! Small hand-made netlists for unit tests, other crates enable it with the `test-util` feature
+
+use crate::netlist::{
+    Component, LibName, LibPart, LibPartName, Net, Netlist, Node, Pin, PinMode, PinType,
+};
+use crate::{Designator, NetName, PinId, PinName};
+use std::collections::HashMap;
+
+/// Builds a netlist part by part and net by net, library parts all live in the "test" library
+#[derive(Default)]
+pub struct NetlistBuilder {
+    lib_parts: HashMap<(LibName, LibPartName), LibPart>,
+    nets: HashMap<NetName, Net>,
+    components: HashMap<Designator, Component>,
+}
+
+impl NetlistBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a library part with pins "1", "2", ... named and typed in order
+    pub fn lib_part(self, name: &str, pins: &[(&str, PinType)]) -> Self {
+        let pins = pins
+            .iter()
+            .enumerate()
+            .map(|(i, (pin_name, ty))| (PinId((i + 1).to_string()), pin(pin_name, *ty)))
+            .collect();
+        self.lib_part_with(
+            name,
+            LibPart {
+                pins,
+                ..Default::default()
+            },
+        )
+    }
+
+    pub fn lib_part_with(mut self, name: &str, lib_part: LibPart) -> Self {
+        self.lib_parts.insert(lib_key(name), lib_part);
+        self
+    }
+
+    /// Adds a part of library part `lib`, which doesn't have to be added if pins don't matter
+    pub fn part(self, designator: &str, value: &str, lib: &str) -> Self {
+        self.component(designator, component(value, lib))
+    }
+
+    pub fn component(mut self, designator: &str, component: Component) -> Self {
+        self.components
+            .insert(Designator(designator.into()), component);
+        self
+    }
+
+    /// Adds a net connecting (designator, pin id) nodes
+    pub fn net(mut self, name: &str, nodes: &[(&str, &str)]) -> Self {
+        self.nets.insert(NetName(name.into()), net(nodes));
+        self
+    }
+
+    pub fn build(self) -> Netlist {
+        Netlist::new(self.lib_parts, self.nets, self.components, vec![])
+    }
+}
+
+pub fn lib_key(name: &str) -> (LibName, LibPartName) {
+    (LibName("test".into()), LibPartName(name.into()))
+}
+
+/// Pin without pulls or IO standard
+pub fn pin(name: &str, ty: PinType) -> Pin {
+    Pin {
+        name: PinName(name.into()),
+        default_mode: PinMode {
+            ty,
+            pull_up: None,
+            pull_down: None,
+            io_standard: None,
+        },
+        alternate_modes: Default::default(),
+        bank_name: None,
+        section_name: None,
+    }
+}
+
+/// Part of library part `lib` without fields, sections or location
+pub fn component(value: &str, lib: &str) -> Component {
+    Component {
+        value: value.into(),
+        description: String::new(),
+        lib_source: lib_key(lib),
+        fields: Default::default(),
+        sections: vec![],
+        location: Default::default(),
+    }
+}
+
+pub fn net(nodes: &[(&str, &str)]) -> Net {
+    Net {
+        nodes: nodes
+            .iter()
+            .map(|(designator, pin)| Node {
+                designator: Designator(designator.to_string()),
+                pin_id: PinId(pin.to_string()),
+                location: Default::default(),
+            })
+            .collect(),
+        properties: Default::default(),
+    }
+}
@@ -1,3 +1,4 @@
+use crate::parse_error::Span;
 use anyhow::Result;
 use std::fs::File;
 use std::io::Read;
@@ -16,3 +17,50 @@ pub fn read_with_unknown_encoding(path: &Path) -> Result<String> {
     // println!("{:?}", encoding);
     Ok(encoding.decode(&buf).0.to_string())
 }
+
+/// Spans of s-expression lists starting with `head` at `depth`, in file order, the root list is
+/// at depth 0. Parsers that do not keep track of positions use it to locate items they found.
+pub(crate) fn sexpr_list_spans(contents: &str, depth: usize, head: &str) -> Vec<Span> {
+    let mut spans = vec![];
+    let mut open: Vec<Option<(usize, usize)>> = vec![];
+    let mut in_string = false;
+    let mut escaped = false;
+    let (mut line, mut column) = (1, 0);
+    for (idx, c) in contents.char_indices() {
+        if c == '\n' {
+            (line, column) = (line + 1, 0);
+        } else {
+            column += 1;
+        }
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '(' => {
+                let rest = &contents[idx + 1..];
+                let is_head = open.len() == depth
+                    && rest.starts_with(head)
+                    && rest[head.len()..]
+                        .starts_with(|c: char| c.is_whitespace() || c == '(' || c == ')');
+                open.push(is_head.then_some((line, column)));
+            }
+            ')' => {
+                if let Some(Some(start)) = open.pop() {
+                    spans.push(Span {
+                        start,
+                        end: (line, column),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    spans
+}
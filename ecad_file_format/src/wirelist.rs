@@ -1,12 +1,16 @@
 use crate::netlist::{
     Component, LibName, LibPart, LibPartName, Net, Netlist, Node, Pin, PinMode, PinType,
+    SourceLocation,
 };
+use crate::parse_error::{ParseError, ParseErrorKind};
 use crate::text_util::read_with_unknown_encoding;
 use crate::{Designator, NetName, PinId, PinName};
-use anyhow::{Error, Result};
+use anyhow::Result;
 use pest::Parser;
 use pest_derive::Parser;
 use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::fs::write;
 use std::path::Path;
 
 #[derive(Parser)]
@@ -15,11 +19,11 @@ use std::path::Path;
 struct WireListParser;
 
 pub(crate) fn load_wirelist_netlist(path: &Path) -> Result<Netlist> {
-    let contents = read_with_unknown_encoding(path)?;
+    let contents = read_with_unknown_encoding(path).map_err(|e| ParseError::io(path, e))?;
 
     let mut file = match WireListParser::parse(Rule::file, &contents) {
         Ok(pairs) => pairs,
-        Err(e) => return Err(Error::msg(format!("{e}"))),
+        Err(e) => return Err(ParseError::from_pest(path, e).into()),
     };
     let mut file = file.next().unwrap().into_inner();
     let component_list = file.next().unwrap();
@@ -30,23 +34,29 @@ pub(crate) fn load_wirelist_netlist(path: &Path) -> Result<Netlist> {
         let mut component = component.into_inner();
         let part_number = component.next().unwrap().as_str();
         let designator = component.next().unwrap().as_str();
-        let footprint = component.next().unwrap().as_str();
+        let fields = component
+            .next()
+            .map(|footprint| ("Footprint".to_string(), footprint.as_str().to_string()))
+            .into_iter()
+            .collect();
         components.insert(
             Designator(designator.into()),
             Component {
                 value: part_number.to_string(),
                 description: "".to_string(),
-                lib_source: (LibName("".into()), LibPartName("".into())),
-                fields: [("Footprint".to_string(), footprint.to_string())].into(),
+                lib_source: (LibName("".into()), LibPartName(designator.into())),
+                fields,
                 sections: vec![],
+                location: SourceLocation::default(),
             },
         );
     }
 
+    let mut warnings = vec![];
     let mut nets = HashMap::new();
     let mut lib_parts: HashMap<_, LibPart> = HashMap::new();
-    for net in wire_list.into_inner() {
-        let mut net = net.into_inner();
+    for net_pair in wire_list.into_inner() {
+        let mut net = net_pair.clone().into_inner();
         let _net_index = net.next().unwrap();
         let net_name = net.next().unwrap().as_str();
         let mut nodes = HashSet::new();
@@ -60,6 +70,7 @@ pub(crate) fn load_wirelist_netlist(path: &Path) -> Result<Netlist> {
             nodes.insert(Node {
                 designator: Designator(designator.into()),
                 pin_id: PinId(pin_id.into()),
+                location: SourceLocation::default(),
             });
 
             let io_type = match io_type {
@@ -89,24 +100,152 @@ pub(crate) fn load_wirelist_netlist(path: &Path) -> Result<Netlist> {
                 },
             );
         }
+        let net_name = NetName(net_name.into());
+        if nets.contains_key(&net_name) {
+            warnings.push(ParseError::at_pair(
+                path,
+                &net_pair,
+                ParseErrorKind::DuplicateNet(net_name),
+            ));
+            continue;
+        }
         nets.insert(
-            NetName(net_name.into()),
+            net_name,
             Net {
                 nodes,
                 properties: Default::default(),
             },
         );
     }
-    Ok(Netlist {
-        lib_parts,
-        nets,
-        components,
-    })
+    Ok(Netlist::new(lib_parts, nets, components, warnings))
+}
+
+/// Writes netlist in Altium "Wire List" text format.
+///
+/// Columns are padded so that they are separated by at least 5 spaces, as the format has no other
+/// delimiters. Component values longer than 40 characters and pin names longer than 15 characters
+/// are truncated, non-ASCII characters in net and pin names are replaced with underscores.
+pub fn write_wirelist_netlist(netlist: &Netlist, path: &Path) -> Result<()> {
+    let mut components = netlist.components.iter().collect::<Vec<_>>();
+    components.sort_by(|a, b| a.0.0.cmp(&b.0.0));
+    let values = components
+        .iter()
+        .map(|(designator, component)| {
+            let value = if component.value.is_empty() {
+                &designator.0
+            } else {
+                &component.value
+            };
+            value.chars().take(40).collect::<String>()
+        })
+        .collect::<Vec<_>>();
+    let value_width = column_width(values.iter().map(|v| v.chars().count()), 32);
+    let designator_width = column_width(components.iter().map(|(d, _)| d.len()), 15);
+
+    let mut out = String::new();
+    writeln!(out, "Wire List")?;
+    writeln!(out)?;
+    writeln!(out, "<<< Component List >>>")?;
+    for ((designator, component), value) in components.iter().zip(&values) {
+        let line = match component.fields.get("Footprint") {
+            Some(footprint) if !footprint.is_empty() => format!(
+                "{value:<value_width$}{:<designator_width$}{footprint}",
+                designator.0
+            ),
+            _ => format!("{value:<value_width$}{}", designator.0),
+        };
+        writeln!(out, "{line}")?;
+    }
+    writeln!(out)?;
+    writeln!(out, "<<< Wire List >>>")?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "  NODE  REFERENCE  PIN #   PIN NAME       PIN TYPE    PART VALUE"
+    )?;
+    writeln!(out)?;
+
+    let mut nets = netlist.nets().iter().collect::<Vec<_>>();
+    nets.sort_by(|a, b| a.0.0.cmp(&b.0.0));
+    let all_nodes = || nets.iter().flat_map(|(_, net)| net.nodes.iter());
+    let designator_width = column_width(all_nodes().map(|n| n.designator.len()), 11);
+    let pin_id_width = column_width(all_nodes().map(|n| n.pin_id.len()), 8);
+    for (net_index, (net_name, net)) in nets.iter().enumerate() {
+        writeln!(out, "[{:05}] {}", net_index + 1, ascii_name(&net_name.0))?;
+        let mut nodes = net.nodes.iter().collect::<Vec<_>>();
+        nodes.sort_by(|a, b| (&a.designator.0, &a.pin_id.0).cmp(&(&b.designator.0, &b.pin_id.0)));
+        for node in nodes {
+            let component = netlist.components.get(&node.designator);
+            // components only present in the wire list have lib parts named after their designators
+            let lib_source = component
+                .map(|c| c.lib_source.clone())
+                .unwrap_or_else(|| (LibName("".into()), LibPartName(node.designator.0.clone())));
+            let pin = netlist
+                .lib_parts
+                .get(&lib_source)
+                .and_then(|lib_part| lib_part.pins.get(&node.pin_id));
+            let pin_name = match pin {
+                Some(pin) if !pin.name.0.is_empty() => &pin.name.0,
+                _ => &node.pin_id.0,
+            };
+            let pin_name = ascii_name(pin_name).chars().take(15).collect::<String>();
+            let io_type = wirelist_io_type(pin.map(|p| p.default_mode.ty));
+            let part_value = match component {
+                Some(c) if !c.value.is_empty() => &c.value,
+                Some(c) if !c.lib_source.1.0.is_empty() => &c.lib_source.1.0,
+                _ => &node.designator.0,
+            };
+            writeln!(
+                out,
+                "        {:<designator_width$}{:<pin_id_width$}{pin_name:<15}{io_type:<11} {part_value}",
+                node.designator.0, node.pin_id.0
+            )?;
+        }
+        writeln!(out)?;
+    }
+
+    write(path, out)?;
+    Ok(())
+}
+
+/// Column is wide enough for the longest entry followed by 5 spaces
+fn column_width(lengths: impl Iterator<Item = usize>, min_width: usize) -> usize {
+    lengths.map(|len| len + 5).max().unwrap_or(0).max(min_width)
+}
+
+fn ascii_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii() && !c.is_ascii_control() {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if name.is_empty() { "_".into() } else { name }
+}
+
+/// Wirelists only have a single POWER type, power pins read back as `PinType::PowerUnspecified`
+fn wirelist_io_type(ty: Option<PinType>) -> &'static str {
+    match ty {
+        Some(PinType::DigitalOutput | PinType::AnalogOutput) => "OUTPUT",
+        Some(PinType::DigitalInput | PinType::AnalogInput) => "INPUT",
+        Some(PinType::DigitalIO | PinType::AnalogIO | PinType::TriState) => "I/O",
+        Some(PinType::OpenCollector) => "OPEN COLLECTOR",
+        Some(PinType::OpenEmitter) => "OPEN EMITTER",
+        Some(
+            PinType::PowerIn | PinType::PowerOut | PinType::PowerIO | PinType::PowerUnspecified,
+        ) => "POWER",
+        Some(PinType::Passive | PinType::Unconnected | PinType::Unspecified) | None => "PASSIVE",
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_util::NetlistBuilder;
 
     #[test]
     fn can_read_altium_wirelist_netlist() {
@@ -114,4 +253,111 @@ mod tests {
             load_wirelist_netlist(Path::new("test_input/netlist_altium_wirelist.net")).unwrap();
         println!("{:#?}", netlist);
     }
+
+    #[test]
+    fn parts_are_keyed_by_designator_and_footprint_is_optional() {
+        let path = Path::new("test_input/wirelist_footprints/netlist_altium_wirelist.net");
+        let netlist = load_wirelist_netlist(path).unwrap();
+        let r1 = &netlist.components[&Designator("R1".into())];
+        let c1 = &netlist.components[&Designator("C1".into())];
+        assert_eq!(r1.fields.get("Footprint").unwrap(), "RESC1005X40N");
+        assert!(c1.fields.is_empty());
+        assert_eq!(c1.value, "100nF");
+
+        // wirelists don't name library parts, so each part gets its own, keyed by designator
+        let key = (LibName("".into()), LibPartName("C1".into()));
+        assert_eq!(c1.lib_source, key);
+        let pins = &netlist.lib_parts[&key].pins;
+        assert_eq!(pins.len(), 2);
+        assert_eq!(pins[&PinId("2".into())].default_mode.ty, PinType::Passive);
+    }
+
+    #[test]
+    fn first_definition_of_duplicate_net_is_kept() {
+        let path = Path::new("test_input/duplicate_net/netlist_altium_wirelist.net");
+        let netlist = load_wirelist_netlist(path).unwrap();
+        let net = netlist.nets().get(&NetName("A".into())).unwrap();
+        let first_node = Node {
+            designator: Designator("R1".into()),
+            pin_id: PinId("1".into()),
+            location: Default::default(),
+        };
+        assert_eq!(net.nodes, [first_node].into());
+        let kinds = netlist
+            .warnings
+            .iter()
+            .map(|w| w.kind.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(kinds, [ParseErrorKind::DuplicateNet(NetName("A".into()))]);
+    }
+
+    #[test]
+    fn wirelist_netlist_round_trip() {
+        let netlist =
+            load_wirelist_netlist(Path::new("test_input/netlist_altium_wirelist.net")).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("round_trip_wirelist.net");
+        write_wirelist_netlist(&netlist, &path).unwrap();
+        let written = load_wirelist_netlist(&path).unwrap();
+        assert_eq!(written.nets(), netlist.nets());
+        assert_eq!(written.components.len(), netlist.components.len());
+        for (designator, component) in &netlist.components {
+            let written_component = written.components.get(designator).unwrap();
+            assert_eq!(written_component.value, component.value);
+            assert_eq!(written_component.fields, component.fields);
+        }
+        for (lib_source, lib_part) in &netlist.lib_parts {
+            let written_lib_part = written.lib_parts.get(lib_source).unwrap();
+            for (pin_id, pin) in &lib_part.pins {
+                let written_pin = written_lib_part.pins.get(pin_id).unwrap();
+                assert_eq!(written_pin.name, pin.name);
+                assert_eq!(written_pin.default_mode.ty, pin.default_mode.ty);
+            }
+        }
+    }
+
+    #[test]
+    fn can_write_kicad_netlist_as_wirelist() {
+        let netlist = crate::load_kicad_netlist(Path::new("test_input/netlist_kicad.net")).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("kicad_as_wirelist.net");
+        write_wirelist_netlist(&netlist, &path).unwrap();
+        let written = load_wirelist_netlist(&path).unwrap();
+        assert_eq!(written.nets(), netlist.nets());
+        let component = written.components.get(&Designator("C1".into())).unwrap();
+        assert_eq!(component.value, "4.7uF");
+        assert_eq!(
+            component.fields.get("Footprint").unwrap(),
+            "Capacitor_SMD:C_0402_1005Metric"
+        );
+    }
+
+    #[test]
+    fn power_pin_direction_is_lost_in_wirelist() {
+        let netlist = NetlistBuilder::new()
+            .lib_part(
+                "LDO",
+                &[
+                    ("VIN", PinType::PowerIn),
+                    ("VOUT", PinType::PowerOut),
+                    ("VIO", PinType::PowerIO),
+                    ("EN", PinType::DigitalInput),
+                ],
+            )
+            .part("U1", "AMS1117-3.3", "LDO")
+            .net("+5V", &[("U1", "1"), ("U1", "4")])
+            .net("+3V3", &[("U1", "2")])
+            .net("VIO", &[("U1", "3")])
+            .build();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("power_pins.net");
+        write_wirelist_netlist(&netlist, &path).unwrap();
+        let written = load_wirelist_netlist(&path).unwrap();
+        let lib_part = &written.lib_parts[&(LibName("".into()), LibPartName("U1".into()))];
+        let ty = |pin: &str| lib_part.pins[&PinId(pin.into())].default_mode.ty;
+        assert_eq!(ty("1"), PinType::PowerUnspecified);
+        assert_eq!(ty("2"), PinType::PowerUnspecified);
+        assert_eq!(ty("3"), PinType::PowerUnspecified);
+        assert_eq!(ty("4"), PinType::DigitalInput);
+    }
 }
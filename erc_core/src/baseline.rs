@@ -0,0 +1,223 @@
+use crate::diagnostics::{Diagnostic, Diagnostics, Location};
+use anyhow::{Error, Result};
+use ecad_file_format::NetName;
+use ecad_file_format::netlist::Netlist;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::OnceLock;
+
+pub const BASELINE_VERSION: u32 = 1;
+
+/// Diagnostics of a previous run, so that only findings introduced since then need attention
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Baseline {
+    pub version: u32,
+    pub entries: Vec<BaselineEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BaselineEntry {
+    /// Code and locations, see [diagnostic_key]
+    pub key: String,
+    /// Message at the time of the baseline, only shown for resolved findings
+    pub message: String,
+}
+
+/// Current diagnostics classified against a baseline
+#[derive(Debug)]
+pub struct BaselineComparison<'a> {
+    pub new: Vec<&'a Diagnostic>,
+    pub unchanged: Vec<&'a Diagnostic>,
+    /// In the baseline, but not found anymore
+    pub resolved: Vec<BaselineEntry>,
+}
+
+impl Baseline {
+    pub fn new(diagnostics: &Diagnostics, netlist: &Netlist) -> Self {
+        let mut entries = diagnostics
+            .iter()
+            .map(|d| BaselineEntry {
+                key: diagnostic_key(d, netlist),
+                message: d.message.clone(),
+            })
+            .collect::<Vec<_>>();
+        entries.sort_by(|a, b| a.key.cmp(&b.key).then(a.message.cmp(&b.message)));
+        Baseline {
+            version: BASELINE_VERSION,
+            entries,
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| Error::msg(format!("{}: {e}", path.display())))?;
+        let baseline: Baseline = serde_json::from_str(&contents)
+            .map_err(|e| Error::msg(format!("{}: {e}", path.display())))?;
+        if baseline.version != BASELINE_VERSION {
+            return Err(Error::msg(format!(
+                "{}: baseline version {} is not supported, expected {BASELINE_VERSION}",
+                path.display(),
+                baseline.version
+            )));
+        }
+        Ok(baseline)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let contents =
+            serde_json::to_string_pretty(self).map_err(|e| Error::msg(format!("{e}")))?;
+        std::fs::write(path, contents).map_err(|e| Error::msg(format!("{}: {e}", path.display())))
+    }
+
+    /// Matches diagnostics by key, each baseline entry is used at most once, so that a second
+    /// occurrence of the same finding is new
+    pub fn compare<'a>(
+        &self,
+        diagnostics: &'a Diagnostics,
+        netlist: &Netlist,
+    ) -> BaselineComparison<'a> {
+        let mut remaining: HashMap<&str, Vec<&BaselineEntry>> = HashMap::new();
+        for entry in &self.entries {
+            remaining.entry(entry.key.as_str()).or_default().push(entry);
+        }
+        let mut comparison = BaselineComparison {
+            new: vec![],
+            unchanged: vec![],
+            resolved: vec![],
+        };
+        for diagnostic in diagnostics.iter() {
+            let key = diagnostic_key(diagnostic, netlist);
+            match remaining
+                .get_mut(key.as_str())
+                .and_then(|entries| entries.pop())
+            {
+                Some(_) => comparison.unchanged.push(diagnostic),
+                None => comparison.new.push(diagnostic),
+            }
+        }
+        comparison.resolved = remaining
+            .into_values()
+            .flatten()
+            .cloned()
+            .collect::<Vec<_>>();
+        comparison
+            .resolved
+            .sort_by(|a, b| a.key.cmp(&b.key).then(a.message.cmp(&b.message)));
+        comparison
+    }
+}
+
+/// Identifies a finding between revisions of a design: code and sorted locations.
+///
+/// Messages are not part of the key, as they contain values that may change. Nets with names
+/// generated by the ECAD tool are identified by the pins on them, so that renumbering does not
+/// turn all their findings into new ones.
+pub fn diagnostic_key(diagnostic: &Diagnostic, netlist: &Netlist) -> String {
+    let mut locations = diagnostic
+        .locations
+        .iter()
+        .map(|location| match location {
+            Location::Part(designator) => format!("part:{}", designator.0),
+            Location::Node(designator, pin) => format!("node:{}.{}", designator.0, pin.0),
+            Location::Bus(name) => format!("bus:{name}"),
+            Location::Net(net) => format!("net:{}", net_key(net, netlist)),
+            Location::Rail(net) => format!("rail:{}", net_key(net, netlist)),
+        })
+        .collect::<Vec<_>>();
+    locations.sort();
+    format!("{} {}", diagnostic.code, locations.join(" "))
+}
+
+fn net_key(net: &NetName, netlist: &Netlist) -> String {
+    let Some(nodes) = netlist.nets().get(net).map(|net| &net.nodes) else {
+        return net.0.clone();
+    };
+    if !is_generated_net_name(&net.0) || nodes.is_empty() {
+        return net.0.clone();
+    }
+    let mut pins = nodes
+        .iter()
+        .map(|node| format!("{}.{}", node.designator.0, node.pin_id.0))
+        .collect::<Vec<_>>();
+    pins.sort();
+    format!("[{}]", pins.join(","))
+}
+
+/// Names given by KiCad, Altium, OrCAD and Eagle to nets without a label
+fn is_generated_net_name(name: &str) -> bool {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    let re = RE.get_or_init(|| {
+        Regex::new(r"^(/.*/)?(Net-\(.*\)|unconnected-\(.*\)|N\$?\d+|Net[A-Z]+\d+_\w+)$").unwrap()
+    });
+    re.is_match(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Baseline, diagnostic_key};
+    use crate::Severity;
+    use crate::diagnostics::{Diagnostic, Diagnostics, Location};
+    use crate::general::UndrivenInput;
+    use ecad_file_format::netlist::Netlist;
+    use ecad_file_format::test_util::NetlistBuilder;
+    use ecad_file_format::{Designator, NetName, PinId};
+
+    fn netlist(generated_name: &str) -> Netlist {
+        NetlistBuilder::new()
+            .net(generated_name, &[("U1", "4"), ("R1", "2")])
+            .net("RESET", &[("U1", "1"), ("R2", "1")])
+            .build()
+    }
+
+    fn undriven(net: &str, input: (&str, &str)) -> Diagnostic {
+        UndrivenInput {
+            net: NetName(net.into()),
+            inputs: vec![(Designator(input.0.into()), PinId(input.1.into()))],
+        }
+        .to_diagnostic(Severity::Error)
+    }
+
+    fn diagnostics(generated_name: &str, extra: bool) -> Diagnostics {
+        let mut diagnostics = Diagnostics::default();
+        diagnostics.push(undriven(generated_name, ("U1", "4")));
+        diagnostics.push(
+            Diagnostic::new("STY003", Severity::Error, "value is empty")
+                .at(Location::Part(Designator("R2".into()))),
+        );
+        if extra {
+            diagnostics.push(
+                Diagnostic::new("STY003", Severity::Error, "value is empty")
+                    .at(Location::Part(Designator("R2".into()))),
+            );
+            diagnostics.push(
+                Diagnostic::new("STY003", Severity::Error, "value is empty")
+                    .at(Location::Part(Designator("R7".into()))),
+            );
+        }
+        diagnostics
+    }
+
+    #[test]
+    fn findings_are_compared_by_stable_keys() {
+        let before = netlist("Net-(R1-Pad2)");
+        let baseline = Baseline::new(&diagnostics("Net-(R1-Pad2)", false), &before);
+        assert_eq!(baseline.entries[0].key, "CON001 net:[R1.2,U1.4] node:U1.4");
+
+        // net was renumbered, R2 has a second finding with the same key and R7 is new
+        let after = netlist("Net-(U1-Pad4)");
+        let current = diagnostics("Net-(U1-Pad4)", true);
+        let comparison = baseline.compare(&current, &after);
+        assert_eq!(comparison.unchanged.len(), 2);
+        assert_eq!(comparison.new.len(), 2);
+        assert!(comparison.resolved.is_empty());
+
+        let none = Diagnostics::default();
+        let comparison = baseline.compare(&none, &after);
+        assert_eq!(comparison.resolved.len(), 2);
+
+        let named = undriven("RESET", ("U1", "1"));
+        assert_eq!(diagnostic_key(&named, &after), "CON001 net:RESET node:U1.1");
+    }
+}
@@ -1,10 +1,486 @@
-use ecad_file_format::passive_value::Ohm;
+use crate::Severity;
+use crate::pin_matrix::PinCompatibility;
+use anyhow::{Error, Result};
+use ecad_file_format::netlist::PinType;
+use ecad_file_format::passive_value::e_series::ESeries;
+use ecad_file_format::passive_value::{Ohm, parse_resistance_value};
+use ecad_file_format::{Designator, NetName};
+use regex::Regex;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer};
+use std::collections::BTreeMap;
 use std::ops::RangeInclusive;
+use std::path::Path;
 
-/// For digital circuits, resistors lower than this value will be considered a 'tie'.
-/// For power circuits, resistors lower than this value will be considered as current sense shunts.
-/// Except when resistance is 0 Ohm, in which case it's a tie.
-pub const MAX_TIE_RESISTANCE: Ohm = Ohm(100.0);
+/// Per-project settings, loaded from `better_erc.toml` next to the design files.
+///
+/// Every table and key is optional, missing ones keep their default values, which are:
+/// ```
+/// # use erc_core::ProjectConfig;
+/// let defaults = r#"
+/// [thresholds]
+/// max_tie_resistance = "100R"
+/// i2c_pull_up_min = "2k2"
+/// i2c_pull_up_max = "10k"
+///
+/// [nets]
+/// strict_voltage_names = true
+/// power = ['\+V', "VDD", "VCC", "VBAT", "VBUS"]
+/// ground = ["GND", "VSS", "VEE", "^ISO"]
+/// rail_voltages = { VBUS = 5.0, VUSB = 5.0 }
+/// signal_words = [
+///     "EN", "ENABLE", "PG", "PGOOD", "PWRGD", "GOOD", "FAULT", "FLT", "ALERT", "FB",
+///     "SENSE", "SNS", "MON", "DET", "DETECT", "ADC", "DIV", "MEAS", "CTRL", "SEL",
+/// ]
+///
+/// [designators]
+/// resistor = ["R"]
+/// capacitor = ["C"]
+/// inductor = ["L"]
+/// ferrite = ["FB"]
+/// diode = ["D", "LED"]
+/// transistor = ["Q"]
+/// ic = ["U"]
+/// connector = ["J"]
+/// battery = ["BT"]
+/// test_point = ["TP"]
+///
+/// [power]
+/// load_switch = ['(?i)\bswitch\b', "^TPS22[0-9]", "^AP22[0-9]"]
+/// feedback_pins = ['(?i)^V?FB\d?$', "(?i)^ADJ$"]
+/// reference_fields = ["Vref", "Vfb"]
+/// reference_voltages = { AMS1117 = 1.25, LM1117 = 1.25, LM317 = 1.25, LM2596 = 1.23, MP1584 = 0.8, MP2307 = 0.925, MT3608 = 0.6, TLV62569 = 0.6, TPS5430 = 1.221, TPS62160 = 0.8 }
+/// "#;
+/// let config = ProjectConfig::from_toml(defaults).unwrap();
+/// assert_eq!(format!("{config:?}"), format!("{:?}", ProjectConfig::default()));
+/// ```
+///
+/// `passive_e_series` is unset, so the series comes from each part's tolerance. The pin matrix and
+/// rules keep their built-in settings unless overridden:
+/// ```toml
+/// [[pin_matrix]]
+/// pins = ["tri_state", "power_in"]
+/// level = "error"
+///
+/// [rules."style.e_series"]
+/// enabled = false
+///
+/// [rules."i2c.bus"]
+/// severity = "error"
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectConfig {
+    pub thresholds: Thresholds,
+    pub nets: NetPatterns,
+    pub designators: DesignatorClasses,
+    pub power: PowerSettings,
+    /// Overrides of the default pin compatibility matrix, later entries win
+    pub pin_matrix: Vec<PinMatrixOverride>,
+    /// Overrides by rule id
+    pub rules: BTreeMap<String, RuleOverride>,
+}
 
-/// No warnings will be issued if I2C pull-ups are withing this range.
-pub const I2C_ACCEPTABLE_PULL_UP_RANGE: RangeInclusive<Ohm> = Ohm(2200.0)..=Ohm(10_000.0);
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Thresholds {
+    /// For digital circuits, resistors lower than this value will be considered a 'tie'.
+    /// For power circuits, resistors lower than this value will be considered as current sense shunts.
+    /// Except when resistance is 0 Ohm, in which case it's a tie.
+    #[serde(deserialize_with = "resistance")]
+    pub max_tie_resistance: Ohm,
+    /// No warnings will be issued if I2C pull-ups are withing i2c_pull_up_min..=i2c_pull_up_max.
+    #[serde(deserialize_with = "resistance")]
+    pub i2c_pull_up_min: Ohm,
+    #[serde(deserialize_with = "resistance")]
+    pub i2c_pull_up_max: Ohm,
+    /// Resistor and capacitor values are expected to be from this series.
+    /// When None, the series is derived from the part's tolerance and parts without tolerance are not checked.
+    #[serde(deserialize_with = "e_series")]
+    pub passive_e_series: Option<ESeries>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NetPatterns {
+    /// If true, then a voltage is only taken from the start of a name or after rail-like words,
+    /// as in +3V3 or VCC_3V3, but not UART_TX_3V3. See [crate::power::rail_name]
+    pub strict_voltage_names: bool,
+    /// Nets with a name matching any of these are power rails, even without power pins on them
+    #[serde(deserialize_with = "regexes")]
+    pub power: Vec<Regex>,
+    #[serde(deserialize_with = "regexes")]
+    pub ground: Vec<Regex>,
+    /// Voltages of rails with well-known names, e.g. VBUS = 5.0
+    pub rail_voltages: BTreeMap<String, f32>,
+    /// Nets with any of these words in their name are signals, even if the name contains a voltage
+    /// or matches a power pattern, e.g. EN_3V3 or VBUS_DET
+    pub signal_words: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PowerSettings {
+    /// ICs with a value or description matching any of these pass their input rail voltage to
+    /// the output rail, instead of being treated as regulators
+    #[serde(deserialize_with = "regexes")]
+    pub load_switch: Vec<Regex>,
+    /// Names of regulator pins that take the output voltage through a resistor divider.
+    /// `ADJ` pins are referenced to the output, as on the LM317, other pins to ground.
+    #[serde(deserialize_with = "regexes")]
+    pub feedback_pins: Vec<Regex>,
+    /// Component fields with the regulator's reference voltage, e.g. "0.6V"
+    pub reference_fields: Vec<String>,
+    /// Reference voltages by value prefix, the longest matching prefix wins.
+    /// Used when a regulator has none of `reference_fields`.
+    pub reference_voltages: BTreeMap<String, f32>,
+}
+
+/// Designator prefixes of each part class, the longest matching prefix wins, so that for example
+/// LED1 is a diode and L1 is an inductor
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DesignatorClasses {
+    pub resistor: Vec<String>,
+    pub capacitor: Vec<String>,
+    pub inductor: Vec<String>,
+    pub ferrite: Vec<String>,
+    pub diode: Vec<String>,
+    pub transistor: Vec<String>,
+    pub ic: Vec<String>,
+    pub connector: Vec<String>,
+    pub battery: Vec<String>,
+    pub test_point: Vec<String>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PartClass {
+    Resistor,
+    Capacitor,
+    Inductor,
+    Ferrite,
+    Diode,
+    Transistor,
+    Ic,
+    Connector,
+    Battery,
+    TestPoint,
+}
+
+/// Compatibility of two pin types, the order of the types does not matter
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PinMatrixOverride {
+    pub pins: [PinType; 2],
+    pub level: PinCompatibility,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RuleOverride {
+    pub enabled: Option<bool>,
+    /// Replaces severity of all the rule's findings
+    pub severity: Option<Severity>,
+}
+
+impl ProjectConfig {
+    pub const FILE_NAME: &'static str = "better_erc.toml";
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| Error::msg(format!("{}: {e}", path.display())))?;
+        Self::from_toml(&contents).map_err(|e| Error::msg(format!("{}: {e}", path.display())))
+    }
+
+    /// Loads `better_erc.toml` from the directory if there is one, otherwise returns defaults
+    pub fn load_from_dir(dir: &Path) -> Result<Self> {
+        let path = dir.join(Self::FILE_NAME);
+        if path.exists() {
+            Self::load(&path)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    pub fn from_toml(contents: &str) -> Result<Self> {
+        let config: ProjectConfig =
+            toml::from_str(contents).map_err(|e| Error::msg(format!("{e}")))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Checks what cannot be expressed by the types, rule ids are checked when applied to a registry
+    pub fn validate(&self) -> Result<()> {
+        let t = &self.thresholds;
+        if t.max_tie_resistance.0 < 0.0 {
+            return Err(Error::msg(
+                "thresholds.max_tie_resistance cannot be negative",
+            ));
+        }
+        if t.i2c_pull_up_min.0 > t.i2c_pull_up_max.0 {
+            return Err(Error::msg(format!(
+                "thresholds.i2c_pull_up_min ({:?}) is larger than i2c_pull_up_max ({:?})",
+                t.i2c_pull_up_min, t.i2c_pull_up_max
+            )));
+        }
+        let mut seen: BTreeMap<&str, PartClass> = BTreeMap::new();
+        for (class, prefixes) in self.designators.classes() {
+            for prefix in prefixes {
+                if prefix.is_empty() {
+                    return Err(Error::msg(format!(
+                        "designators: empty prefix for {class:?}"
+                    )));
+                }
+                if let Some(other) = seen.insert(prefix, class) {
+                    return Err(Error::msg(format!(
+                        "designators: prefix \"{prefix}\" is used for both {other:?} and {class:?}"
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Thresholds {
+    pub fn i2c_pull_up_range(&self) -> RangeInclusive<Ohm> {
+        self.i2c_pull_up_min.clone()..=self.i2c_pull_up_max.clone()
+    }
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Thresholds {
+            max_tie_resistance: Ohm(100.0),
+            i2c_pull_up_min: Ohm(2200.0),
+            i2c_pull_up_max: Ohm(10_000.0),
+            passive_e_series: None,
+        }
+    }
+}
+
+impl NetPatterns {
+    /// Returns true if net name looks like a power rail
+    pub fn is_power(&self, name: &NetName) -> bool {
+        self.power.iter().any(|re| re.is_match(&name.0)) && !self.has_signal_word(&name.0)
+    }
+
+    /// Returns true if any word of the name is one of `signal_words`. Words are separated by `_`,
+    /// spaces and `/`, as `is_power` gets hierarchical names with the sheet path, e.g. /USB/VBUS_DET
+    pub fn has_signal_word(&self, name: &str) -> bool {
+        name.split(['_', ' ', '/']).any(|token| {
+            self.signal_words
+                .iter()
+                .any(|word| word.eq_ignore_ascii_case(token))
+        })
+    }
+
+    /// Returns true if net name looks like a ground net
+    pub fn is_ground(&self, name: &NetName) -> bool {
+        self.ground.iter().any(|re| re.is_match(&name.0))
+    }
+}
+
+impl Default for NetPatterns {
+    fn default() -> Self {
+        NetPatterns {
+            strict_voltage_names: true,
+            power: [r"\+V", "VDD", "VCC", "VBAT", "VBUS"]
+                .into_iter()
+                .map(|re| Regex::new(re).unwrap())
+                .collect(),
+            ground: ["GND", "VSS", "VEE", "^ISO"]
+                .into_iter()
+                .map(|re| Regex::new(re).unwrap())
+                .collect(),
+            rail_voltages: BTreeMap::from([("VBUS".into(), 5.0), ("VUSB".into(), 5.0)]),
+            signal_words: [
+                "EN", "ENABLE", "PG", "PGOOD", "PWRGD", "GOOD", "FAULT", "FLT", "ALERT", "FB",
+                "SENSE", "SNS", "MON", "DET", "DETECT", "ADC", "DIV", "MEAS", "CTRL", "SEL",
+            ]
+            .into_iter()
+            .map(String::from)
+            .collect(),
+        }
+    }
+}
+
+impl DesignatorClasses {
+    fn classes(&self) -> [(PartClass, &[String]); 10] {
+        [
+            (PartClass::Resistor, &self.resistor),
+            (PartClass::Capacitor, &self.capacitor),
+            (PartClass::Inductor, &self.inductor),
+            (PartClass::Ferrite, &self.ferrite),
+            (PartClass::Diode, &self.diode),
+            (PartClass::Transistor, &self.transistor),
+            (PartClass::Ic, &self.ic),
+            (PartClass::Connector, &self.connector),
+            (PartClass::Battery, &self.battery),
+            (PartClass::TestPoint, &self.test_point),
+        ]
+    }
+
+    pub fn class_of(&self, designator: &Designator) -> Option<PartClass> {
+        self.classes()
+            .into_iter()
+            .flat_map(|(class, prefixes)| prefixes.iter().map(move |p| (class, p)))
+            .filter(|(_, prefix)| designator.0.starts_with(prefix.as_str()))
+            .max_by_key(|(_, prefix)| prefix.len())
+            .map(|(class, _)| class)
+    }
+
+    pub fn is(&self, designator: &Designator, class: PartClass) -> bool {
+        self.class_of(designator) == Some(class)
+    }
+}
+
+impl Default for PowerSettings {
+    fn default() -> Self {
+        PowerSettings {
+            load_switch: [r"(?i)\bswitch\b", "^TPS22[0-9]", "^AP22[0-9]"]
+                .into_iter()
+                .map(|re| Regex::new(re).unwrap())
+                .collect(),
+            feedback_pins: [r"(?i)^V?FB\d?$", "(?i)^ADJ$"]
+                .into_iter()
+                .map(|re| Regex::new(re).unwrap())
+                .collect(),
+            reference_fields: vec!["Vref".into(), "Vfb".into()],
+            reference_voltages: [
+                ("AMS1117", 1.25),
+                ("LM1117", 1.25),
+                ("LM317", 1.25),
+                ("LM2596", 1.23),
+                ("MP1584", 0.8),
+                ("MP2307", 0.925),
+                ("MT3608", 0.6),
+                ("TLV62569", 0.6),
+                ("TPS5430", 1.221),
+                ("TPS62160", 0.8),
+            ]
+            .into_iter()
+            .map(|(prefix, voltage)| (prefix.to_string(), voltage))
+            .collect(),
+        }
+    }
+}
+
+impl Default for DesignatorClasses {
+    fn default() -> Self {
+        let prefixes = |p: &[&str]| p.iter().map(|p| p.to_string()).collect();
+        DesignatorClasses {
+            resistor: prefixes(&["R"]),
+            capacitor: prefixes(&["C"]),
+            inductor: prefixes(&["L"]),
+            ferrite: prefixes(&["FB"]),
+            diode: prefixes(&["D", "LED"]),
+            transistor: prefixes(&["Q"]),
+            ic: prefixes(&["U"]),
+            connector: prefixes(&["J"]),
+            battery: prefixes(&["BT"]),
+            test_point: prefixes(&["TP"]),
+        }
+    }
+}
+
+/// Resistance as a number of Ohms or as a value string, e.g. "2k2" or "4.7 kΩ"
+fn resistance<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Ohm, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Value {
+        Number(f32),
+        Text(String),
+    }
+    match Value::deserialize(deserializer)? {
+        Value::Number(ohms) => Ok(Ohm(ohms)),
+        Value::Text(text) => parse_resistance_value(&text)
+            .map(|(ohms, _)| ohms)
+            .map_err(|_| D::Error::custom(format!("\"{text}\" is not a resistance"))),
+    }
+}
+
+fn e_series<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<ESeries>, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    ESeries::ALL
+        .iter()
+        .find(|series| format!("{series:?}").eq_ignore_ascii_case(&name))
+        .copied()
+        .map(Some)
+        .ok_or_else(|| D::Error::custom(format!("unknown E-series \"{name}\"")))
+}
+
+fn regexes<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Regex>, D::Error> {
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|re| Regex::new(re).map_err(D::Error::custom))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PartClass, ProjectConfig};
+    use crate::Severity;
+    use crate::pin_matrix::PinCompatibility;
+    use ecad_file_format::netlist::PinType;
+    use ecad_file_format::passive_value::e_series::ESeries;
+    use ecad_file_format::{Designator, NetName};
+    use std::path::Path;
+
+    #[test]
+    fn config_is_loaded_and_validated() {
+        let config = ProjectConfig::load(Path::new("test_input/better_erc.toml")).unwrap();
+        assert_eq!(config.thresholds.max_tie_resistance.0, 47.0);
+        assert_eq!(config.thresholds.i2c_pull_up_min.0, 1000.0);
+        assert_eq!(config.thresholds.i2c_pull_up_max.0, 10_000.0);
+        assert_eq!(config.thresholds.passive_e_series, Some(ESeries::E24));
+        assert!(config.nets.is_power(&NetName("VBAT".into())));
+        assert!(!config.nets.is_power(&NetName("VCC_3V3".into())));
+        assert!(config.nets.is_ground(&NetName("AGND".into())));
+        assert!(
+            config
+                .designators
+                .is(&Designator("X1".into()), PartClass::Connector)
+        );
+        assert!(
+            config
+                .designators
+                .is(&Designator("R1".into()), PartClass::Resistor)
+        );
+        assert!(!config.rules["style.e_series"].enabled.unwrap());
+        assert_eq!(config.rules["i2c.bus"].severity, Some(Severity::Error));
+        assert_eq!(config.pin_matrix[0].pins, [PinType::PowerOut; 2]);
+        assert_eq!(config.pin_matrix[0].level, PinCompatibility::Warning);
+
+        let defaults = ProjectConfig::from_toml("").unwrap();
+        assert_eq!(defaults.thresholds.max_tie_resistance.0, 100.0);
+        assert_eq!(defaults.thresholds.i2c_pull_up_min.0, 2200.0);
+        assert_eq!(defaults.thresholds.i2c_pull_up_max.0, 10_000.0);
+        assert!(defaults.nets.strict_voltage_names);
+        assert!(defaults.nets.is_ground(&NetName("ISO_GND".into())));
+        assert!(
+            defaults
+                .designators
+                .is(&Designator("L1".into()), PartClass::Inductor)
+        );
+        assert!(
+            defaults
+                .designators
+                .is(&Designator("LED1".into()), PartClass::Diode)
+        );
+
+        for wrong in [
+            "[thresholds]\nmax_tie = 10",
+            "[thresholds]\ni2c_pull_up_min = \"10k\"\ni2c_pull_up_max = \"1k\"",
+            "[thresholds]\npassive_e_series = \"E7\"",
+            "[thresholds]\nmax_tie_resistance = \"ten\"",
+            "[nets]\nground = [\"GND(\"]",
+            "[designators]\ncapacitor = [\"R\"]",
+            "[rules.\"i2c.bus\"]\nseverity = \"fatal\"",
+            "[[pin_matrix]]\npins = [\"power_out\"]\nlevel = \"ok\"",
+            "[[pin_matrix]]\npins = [\"output\", \"input\"]\nlevel = \"ok\"",
+        ] {
+            assert!(ProjectConfig::from_toml(wrong).is_err(), "{wrong}");
+        }
+    }
+}
@@ -1,8 +1,126 @@
-use crate::i2c::I2cDiagnostic;
-use crate::style::StyleDiagnostic;
+use crate::Severity;
+use crate::waiver::Waiver;
+use ecad_file_format::{Designator, NetName, PinId};
+use std::fmt::{Display, Formatter};
+
+/// Problem found by one of the rules.
+///
+/// Codes are stable between releases (e.g. `I2C003` is always a pull-up to a net with nothing
+/// else on it), so they can be used for filtering, waivers and comparing reports.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub code: &'static str,
+    pub severity: Severity,
+    pub message: String,
+    /// Objects the problem is about
+    pub locations: Vec<Location>,
+    /// Objects that help to understand the problem, e.g. the other pull-up on the same bus
+    pub related: Vec<Location>,
+    /// Waiver that covers this diagnostic, it is still reported, but should not fail the check
+    pub waived_by: Option<Waiver>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Location {
+    Part(Designator),
+    Net(NetName),
+    Node(Designator, PinId),
+    /// I2C bus by its derived name
+    Bus(String),
+    Rail(NetName),
+}
 
 #[derive(Default, Debug)]
 pub struct Diagnostics {
-    pub i2c: Vec<I2cDiagnostic>,
-    pub style: Vec<StyleDiagnostic>,
+    pub items: Vec<Diagnostic>,
+}
+
+impl Diagnostic {
+    pub fn new(code: &'static str, severity: Severity, message: impl Into<String>) -> Self {
+        Diagnostic {
+            code,
+            severity,
+            message: message.into(),
+            locations: vec![],
+            related: vec![],
+            waived_by: None,
+        }
+    }
+
+    pub fn at(mut self, location: Location) -> Self {
+        self.locations.push(location);
+        self
+    }
+
+    pub fn related_to(mut self, location: Location) -> Self {
+        self.related.push(location);
+        self
+    }
+
+    /// Parts the problem is about, including the ones only referred to by a node
+    pub fn designators(&self) -> impl Iterator<Item = &Designator> {
+        self.locations.iter().filter_map(|location| match location {
+            Location::Part(designator) | Location::Node(designator, _) => Some(designator),
+            _ => None,
+        })
+    }
+}
+
+impl Diagnostics {
+    pub fn push(&mut self, diagnostic: Diagnostic) {
+        self.items.push(diagnostic);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.items.iter()
+    }
+
+    /// Diagnostics that are not waived
+    pub fn active(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.items.iter().filter(|d| d.waived_by.is_none())
+    }
+
+    pub fn with_code<'a>(&'a self, code: &'a str) -> impl Iterator<Item = &'a Diagnostic> {
+        self.items.iter().filter(move |d| d.code == code)
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+}
+
+impl Extend<Diagnostic> for Diagnostics {
+    fn extend<T: IntoIterator<Item = Diagnostic>>(&mut self, iter: T) {
+        self.items.extend(iter);
+    }
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Location::Part(designator) => write!(f, "{}", designator.0),
+            Location::Net(net) => write!(f, "net {}", net.0),
+            Location::Node(designator, pin) => write!(f, "{}.{}", designator.0, pin.0),
+            Location::Bus(name) => write!(f, "bus {name}"),
+            Location::Rail(net) => write!(f, "rail {}", net.0),
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}: {}", self.severity, self.code, self.message)?;
+        for (i, location) in self.locations.iter().enumerate() {
+            let separator = if i == 0 { " at " } else { ", " };
+            write!(f, "{separator}{location}")?;
+        }
+        if let Some(waiver) = &self.waived_by {
+            write!(f, " (waived: {})", waiver.reason)?;
+        }
+        Ok(())
+    }
 }
@@ -1,61 +1,692 @@
+use crate::config::PartClass;
+use crate::diagnostics::{Diagnostic, Diagnostics, Location};
+use crate::rule::{Rule, RuleCategory};
+use crate::{Pcba, Severity};
 use ecad_file_format::netlist::{Netlist, Node, PinType};
+use ecad_file_format::{Designator, NetName, PinId};
+use std::collections::HashSet;
 
-pub fn input_without_driving_source(netlist: &Netlist) {
-    let mut nets_with_inputs = vec![];
-    for (k, lib_part) in &netlist.lib_parts {
-        let components = netlist
-            .components
+/// How many resistors in series an input can be driven through, e.g. a source termination
+/// followed by a protection resistor
+const MAX_SERIES_RESISTORS: usize = 2;
+
+/// Two pins that can drive a net against each other
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputContention {
+    pub net: NetName,
+    /// Push-pull output
+    pub output: (Designator, PinId, PinType),
+    /// Other driver, ordered after `output` if both are push-pull
+    pub other: (Designator, PinId, PinType),
+}
+
+/// Pin or net that is not connected the way its type says it should be
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConnectivityFinding {
+    /// Net with one pin on it, which is not an input or a no-connect pin
+    SinglePinNet {
+        net: NetName,
+        designator: Designator,
+        pin_id: PinId,
+    },
+    /// Input or power input pin on no net or alone on its net
+    UnconnectedPin {
+        designator: Designator,
+        pin_id: PinId,
+        ty: PinType,
+    },
+    /// Pin marked as no-connect, that is connected to other pins
+    WiredNoConnect {
+        net: NetName,
+        designator: Designator,
+        pin_id: PinId,
+    },
+}
+
+/// Input pins on a net that nothing can drive
+#[derive(Debug, Clone, PartialEq)]
+pub struct UndrivenInput {
+    pub net: NetName,
+    /// Sorted
+    pub inputs: Vec<(Designator, PinId)>,
+}
+
+/// Flags digital and analog inputs that have no output, bidirectional, tri-state, open drain with
+/// a pull-up, power or connector driver, directly or through series resistors
+pub struct UndrivenInputRule;
+
+impl Rule for UndrivenInputRule {
+    fn id(&self) -> &'static str {
+        "connectivity.undriven_input"
+    }
+
+    fn category(&self) -> RuleCategory {
+        RuleCategory::Connectivity
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn run(&self, pcba: &Pcba, diagnostics: &mut Diagnostics) {
+        let severity = self.default_severity();
+        diagnostics.extend(
+            find_undriven_inputs(pcba)
+                .into_iter()
+                .map(|undriven| undriven.to_diagnostic(severity)),
+        );
+    }
+}
+
+/// Push-pull outputs sharing a net with another pin that can drive it
+pub struct OutputContentionRule;
+
+impl Rule for OutputContentionRule {
+    fn id(&self) -> &'static str {
+        "connectivity.output_contention"
+    }
+
+    fn category(&self) -> RuleCategory {
+        RuleCategory::Connectivity
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn run(&self, pcba: &Pcba, diagnostics: &mut Diagnostics) {
+        let severity = self.default_severity();
+        diagnostics.extend(
+            find_output_contentions(pcba)
+                .into_iter()
+                .map(|contention| contention.to_diagnostic(severity)),
+        );
+    }
+}
+
+/// Single pin nets, unconnected inputs and wired no-connect pins
+pub struct ConnectivityCompletenessRule;
+
+impl Rule for ConnectivityCompletenessRule {
+    fn id(&self) -> &'static str {
+        "connectivity.completeness"
+    }
+
+    fn category(&self) -> RuleCategory {
+        RuleCategory::Connectivity
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn run(&self, pcba: &Pcba, diagnostics: &mut Diagnostics) {
+        let severity = self.default_severity();
+        diagnostics.extend(
+            find_incomplete_connections(&pcba.netlist)
+                .into_iter()
+                .map(|finding| finding.to_diagnostic(severity)),
+        );
+    }
+}
+
+/// Returns nets with input pins and no driver, sorted by net name.
+///
+/// Pins of ICs and other active parts with an unknown type are assumed to be able to drive, so
+/// that netlists without pin types do not produce a finding on every net.
+pub fn find_undriven_inputs(pcba: &Pcba) -> Vec<UndrivenInput> {
+    let mut undriven = vec![];
+    for (net_name, net) in pcba.netlist.nets() {
+        // inputs alone on a net are reported by the completeness rule
+        if net.nodes.len() < 2 {
+            continue;
+        }
+        let mut inputs = net
+            .nodes
             .iter()
-            .filter(|(_d, c)| &c.lib_source == k)
+            .filter(|node| is_input_without_pull(pcba, node))
+            .map(|node| (node.designator.clone(), node.pin_id.clone()))
             .collect::<Vec<_>>();
-        for (pin_id, pin) in &lib_part.pins {
-            if pin.default_mode.ty != PinType::DigitalInput {
+        if inputs.is_empty() {
+            continue;
+        }
+        let mut visited = HashSet::from([net_name.clone()]);
+        if is_driven(pcba, net_name, MAX_SERIES_RESISTORS, &mut visited) {
+            continue;
+        }
+        inputs.sort_by(|a, b| (&a.0.0, &a.1.0).cmp(&(&b.0.0, &b.1.0)));
+        undriven.push(UndrivenInput {
+            net: net_name.clone(),
+            inputs,
+        });
+    }
+    undriven.sort_by(|a, b| a.net.0.cmp(&b.net.0));
+    undriven
+}
+
+fn is_input_without_pull(pcba: &Pcba, node: &Node) -> bool {
+    let Some(pin) = pcba.netlist.pin(&node.designator, &node.pin_id) else {
+        return false;
+    };
+    let mode = &pin.default_mode;
+    matches!(mode.ty, PinType::DigitalInput | PinType::AnalogInput)
+        && mode.pull_up.is_none()
+        && mode.pull_down.is_none()
+}
+
+/// Returns true if something on the net, or on a net behind up to `series_left` resistors, drives it
+fn is_driven(
+    pcba: &Pcba,
+    net_name: &NetName,
+    series_left: usize,
+    visited: &mut HashSet<NetName>,
+) -> bool {
+    if pcba.power.is_power_net(net_name) {
+        return true;
+    }
+    let Some(net) = pcba.netlist.nets().get(net_name) else {
+        return false;
+    };
+    if net.nodes.iter().any(|node| is_driver(pcba, net_name, node)) {
+        return true;
+    }
+    if series_left == 0 {
+        return false;
+    }
+    let designators = &pcba.config.designators;
+    for node in &net.nodes {
+        if !designators.is(&node.designator, PartClass::Resistor) {
+            continue;
+        }
+        // resistor arrays are skipped, as it is not known which pins are the other side
+        let nets = pcba.netlist.part_nets(&node.designator);
+        if nets.len() != 2 {
+            continue;
+        }
+        for other_side in nets {
+            if visited.insert(other_side.clone())
+                && is_driven(pcba, &other_side, series_left - 1, visited)
+            {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+fn is_driver(pcba: &Pcba, net_name: &NetName, node: &Node) -> bool {
+    let designators = &pcba.config.designators;
+    let class = designators.class_of(&node.designator);
+    if class == Some(PartClass::Connector) {
+        return true;
+    }
+    let ty = pcba
+        .netlist
+        .pin(&node.designator, &node.pin_id)
+        .map(|pin| pin.default_mode.ty);
+    match ty {
+        Some(
+            PinType::DigitalOutput
+            | PinType::DigitalIO
+            | PinType::AnalogOutput
+            | PinType::AnalogIO
+            | PinType::PowerOut
+            | PinType::PowerIO
+            | PinType::TriState,
+        ) => true,
+        Some(PinType::OpenCollector) => has_pull(pcba, net_name, true),
+        Some(PinType::OpenEmitter) => has_pull(pcba, net_name, false),
+        // discrete transistors are usually drawn with passive pins
+        Some(PinType::Passive) => class == Some(PartClass::Transistor),
+        Some(PinType::Unspecified) | None => !matches!(
+            class,
+            Some(
+                PartClass::Resistor
+                    | PartClass::Capacitor
+                    | PartClass::Inductor
+                    | PartClass::Ferrite
+                    | PartClass::Diode
+                    | PartClass::TestPoint
+            )
+        ),
+        Some(
+            PinType::DigitalInput
+            | PinType::AnalogInput
+            | PinType::PowerIn
+            | PinType::PowerUnspecified
+            | PinType::Unconnected,
+        ) => false,
+    }
+}
+
+/// Returns true if there is a resistor from the net to a power rail (`up`) or to ground
+fn has_pull(pcba: &Pcba, net_name: &NetName, up: bool) -> bool {
+    let Some(net_parts) = pcba.netlist.index().net_parts(net_name) else {
+        return false;
+    };
+    net_parts
+        .iter()
+        .filter(|d| pcba.config.designators.is(d, PartClass::Resistor))
+        .flat_map(|d| pcba.netlist.part_nets(d))
+        .any(|other_side| {
+            if up {
+                pcba.power.power_rails.contains_key(&other_side)
+            } else {
+                pcba.power.ground_nets.contains(&other_side)
+            }
+        })
+}
+
+/// Returns each pair of conflicting drivers, sorted by net name and pins.
+///
+/// Open collector and open emitter outputs can be wired together, as can tri-state outputs on a
+/// bus, but not with a push-pull output. `PowerOut` pins of diodes are OR-ing diodes and may share
+/// a rail. Pins of the same part on one net (e.g. several VOUT pins) are not in conflict.
+pub fn find_output_contentions(pcba: &Pcba) -> Vec<OutputContention> {
+    let mut contentions = vec![];
+    for (net_name, net) in pcba.netlist.nets() {
+        let mut drivers = net
+            .nodes
+            .iter()
+            .filter_map(|node| {
+                let ty = pcba
+                    .netlist
+                    .pin(&node.designator, &node.pin_id)?
+                    .default_mode
+                    .ty;
+                let is_or_ing_diode = ty == PinType::PowerOut
+                    && pcba
+                        .config
+                        .designators
+                        .is(&node.designator, PartClass::Diode);
+                let can_drive = matches!(
+                    ty,
+                    PinType::DigitalOutput
+                        | PinType::AnalogOutput
+                        | PinType::PowerOut
+                        | PinType::OpenCollector
+                        | PinType::OpenEmitter
+                        | PinType::TriState
+                );
+                (can_drive && !is_or_ing_diode)
+                    .then(|| (node.designator.clone(), node.pin_id.clone(), ty))
+            })
+            .collect::<Vec<_>>();
+        drivers.sort_by(|a, b| (&a.0.0, &a.1.0).cmp(&(&b.0.0, &b.1.0)));
+        for (i, output) in drivers.iter().enumerate() {
+            if !is_push_pull(output.2) {
                 continue;
             }
-            for (net_name, net) in &netlist.nets {
-                for (designator, _component) in &components {
-                    if net.nodes.contains(&Node {
-                        designator: (*designator).clone(),
+            for (j, other) in drivers.iter().enumerate() {
+                // pairs of push-pull outputs are reported once
+                if i == j || output.0 == other.0 || (is_push_pull(other.2) && j < i) {
+                    continue;
+                }
+                contentions.push(OutputContention {
+                    net: net_name.clone(),
+                    output: output.clone(),
+                    other: other.clone(),
+                });
+            }
+        }
+    }
+    contentions.sort_by(|a, b| {
+        (
+            &a.net.0,
+            &a.output.0.0,
+            &a.output.1.0,
+            &a.other.0.0,
+            &a.other.1.0,
+        )
+            .cmp(&(
+                &b.net.0,
+                &b.output.0.0,
+                &b.output.1.0,
+                &b.other.0.0,
+                &b.other.1.0,
+            ))
+    });
+    contentions
+}
+
+fn is_push_pull(ty: PinType) -> bool {
+    matches!(
+        ty,
+        PinType::DigitalOutput | PinType::AnalogOutput | PinType::PowerOut
+    )
+}
+
+/// Checks library pins of each component against the nets it is on.
+///
+/// Nets KiCad creates for pins without a wire (`unconnected-(U1-PA3)`) are treated as no net, so
+/// that unused outputs are not reported.
+pub fn find_incomplete_connections(netlist: &Netlist) -> Vec<ConnectivityFinding> {
+    let index = netlist.index();
+    let mut findings = vec![];
+    for (net_name, net) in netlist.nets() {
+        let Some(node) = net.nodes.iter().next() else {
+            continue;
+        };
+        if net.nodes.len() != 1 || is_unconnected_net(net_name) {
+            continue;
+        }
+        let ty = netlist
+            .pin(&node.designator, &node.pin_id)
+            .map(|pin| pin.default_mode.ty);
+        if ty.is_some_and(|ty| ty == PinType::Unconnected || must_be_connected(ty)) {
+            continue;
+        }
+        findings.push(ConnectivityFinding::SinglePinNet {
+            net: net_name.clone(),
+            designator: node.designator.clone(),
+            pin_id: node.pin_id.clone(),
+        });
+    }
+    for (designator, component) in &netlist.components {
+        let Some(lib_part) = netlist.lib_parts.get(&component.lib_source) else {
+            continue;
+        };
+        for (pin_id, pin) in &lib_part.pins {
+            let ty = pin.default_mode.ty;
+            let net = index
+                .node_net(designator, pin_id)
+                .and_then(|net_name| netlist.nets().get_key_value(net_name));
+            let connected_to_others = net.is_some_and(|(net_name, net)| {
+                net.nodes.len() > 1 && !is_unconnected_net(net_name)
+            });
+            if ty == PinType::Unconnected {
+                if let (Some((net_name, _)), true) = (net, connected_to_others) {
+                    findings.push(ConnectivityFinding::WiredNoConnect {
+                        net: net_name.clone(),
+                        designator: designator.clone(),
                         pin_id: pin_id.clone(),
-                    }) {
-                        nets_with_inputs.push((net_name.clone(), *designator, pin_id));
-                    }
+                    });
                 }
+            } else if must_be_connected(ty) && !connected_to_others {
+                findings.push(ConnectivityFinding::UnconnectedPin {
+                    designator: designator.clone(),
+                    pin_id: pin_id.clone(),
+                    ty,
+                });
+            }
+        }
+    }
+    findings.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()));
+    findings
+}
+
+fn must_be_connected(ty: PinType) -> bool {
+    matches!(
+        ty,
+        PinType::DigitalInput | PinType::AnalogInput | PinType::PowerIn
+    )
+}
+
+fn is_unconnected_net(net_name: &NetName) -> bool {
+    net_name.0.starts_with("unconnected-(")
+}
+
+impl UndrivenInput {
+    pub fn to_diagnostic(self, severity: Severity) -> Diagnostic {
+        let message = if self.inputs.len() == 1 {
+            "input is not driven by anything".to_string()
+        } else {
+            format!("{} inputs are not driven by anything", self.inputs.len())
+        };
+        let mut diagnostic =
+            Diagnostic::new("CON001", severity, message).at(Location::Net(self.net));
+        for (designator, pin_id) in self.inputs {
+            diagnostic = diagnostic.at(Location::Node(designator, pin_id));
+        }
+        diagnostic
+    }
+}
+
+impl OutputContention {
+    pub fn to_diagnostic(self, severity: Severity) -> Diagnostic {
+        let (output, output_pin, output_ty) = self.output;
+        let (other, other_pin, other_ty) = self.other;
+        let message = format!(
+            "{output_ty:?} {}.{} drives against {other_ty:?} {}.{}",
+            output.0, output_pin.0, other.0, other_pin.0
+        );
+        Diagnostic::new("CON002", severity, message)
+            .at(Location::Net(self.net))
+            .at(Location::Node(output, output_pin))
+            .at(Location::Node(other, other_pin))
+    }
+}
+
+impl ConnectivityFinding {
+    fn code(&self) -> &'static str {
+        match self {
+            ConnectivityFinding::SinglePinNet { .. } => "CON004",
+            ConnectivityFinding::UnconnectedPin { .. } => "CON005",
+            ConnectivityFinding::WiredNoConnect { .. } => "CON006",
+        }
+    }
+
+    /// Code, net and node, the order findings are reported in
+    fn sort_key(&self) -> (&'static str, Option<&str>, &str, &str) {
+        let (net, designator, pin_id) = match self {
+            ConnectivityFinding::SinglePinNet {
+                net,
+                designator,
+                pin_id,
             }
+            | ConnectivityFinding::WiredNoConnect {
+                net,
+                designator,
+                pin_id,
+            } => (Some(net.0.as_str()), designator, pin_id),
+            ConnectivityFinding::UnconnectedPin {
+                designator, pin_id, ..
+            } => (None, designator, pin_id),
+        };
+        (self.code(), net, &designator.0, &pin_id.0)
+    }
+
+    /// Single pin nets are often left for later and are at most a warning
+    pub fn to_diagnostic(self, severity: Severity) -> Diagnostic {
+        let code = self.code();
+        match self {
+            ConnectivityFinding::SinglePinNet {
+                net,
+                designator,
+                pin_id,
+            } => Diagnostic::new(
+                code,
+                severity.min(Severity::Warning),
+                "net has a single pin",
+            )
+            .at(Location::Net(net))
+            .at(Location::Node(designator, pin_id)),
+            ConnectivityFinding::UnconnectedPin {
+                designator,
+                pin_id,
+                ty,
+            } => Diagnostic::new(code, severity, format!("{ty:?} pin is not connected"))
+                .at(Location::Node(designator, pin_id)),
+            ConnectivityFinding::WiredNoConnect {
+                net,
+                designator,
+                pin_id,
+            } => Diagnostic::new(
+                code,
+                severity,
+                "pin marked as not connected is wired to other pins",
+            )
+            .at(Location::Net(net))
+            .at(Location::Node(designator, pin_id)),
         }
     }
-    println!("{:#?}", nets_with_inputs);
 }
 
 #[cfg(test)]
 mod tests {
     use crate::Pcba;
-    use crate::general::input_without_driving_source;
-    use crate::power::derive_power_structure;
-    use ecad_file_format::Designator;
-    use std::path::Path;
+    use crate::general::{
+        ConnectivityCompletenessRule, find_incomplete_connections, find_output_contentions,
+        find_undriven_inputs,
+    };
+    use crate::rule::Rule;
+    use ecad_file_format::netlist::{LibPart, Netlist, PinType, Pull};
+    use ecad_file_format::test_util::{NetlistBuilder, pin};
+    use ecad_file_format::{Designator, PinId};
+    use std::collections::HashMap;
+
+    fn netlist() -> Netlist {
+        let mut pulled_up = pin("IO", PinType::DigitalInput);
+        pulled_up.default_mode.pull_up = Some(Pull::Unknown);
+        let builder = NetlistBuilder::new()
+            .lib_part("IN", &[("IO", PinType::DigitalInput)])
+            .lib_part_with(
+                "IN_PU",
+                LibPart {
+                    pins: HashMap::from([(PinId("1".into()), pulled_up)]),
+                    ..Default::default()
+                },
+            )
+            .lib_part("OUT", &[("IO", PinType::DigitalOutput)])
+            .lib_part("OD", &[("IO", PinType::OpenCollector)])
+            .lib_part("TRI", &[("IO", PinType::TriState)])
+            .lib_part("PWR", &[("IO", PinType::PowerOut)]);
+        [
+            ("U1", "OUT"),
+            ("U2", "IN"),
+            ("U3", "IN"),
+            ("U4", "IN"),
+            ("U5", "IN"),
+            ("U6", "OUT"),
+            ("U7", "IN"),
+            ("U8", "OD"),
+            ("U9", "IN"),
+            ("U10", "OD"),
+            ("U11", "IN"),
+            ("U12", "IN"),
+            ("U13", "IN_PU"),
+            ("U14", "OUT"),
+            ("U15", "OUT"),
+            ("U16", "OD"),
+            ("U17", "OD"),
+            ("U18", "OD"),
+            ("U19", "TRI"),
+            ("U20", "TRI"),
+            ("U21", "PWR"),
+            ("U22", "PWR"),
+            ("D1", "PWR"),
+            ("D2", "PWR"),
+            ("R1", "R"),
+            ("R2", "R"),
+            ("C1", "C"),
+            ("J1", "CONN"),
+        ]
+        .into_iter()
+        .fold(builder, |builder, (designator, lib)| {
+            builder.part(designator, "", lib)
+        })
+        .net("DRIVEN", &[("U1", "1"), ("U2", "1")])
+        .net("FLOATING", &[("U3", "1"), ("U4", "1")])
+        .net("SERIES_IN", &[("U5", "1"), ("R1", "1")])
+        .net("SERIES_OUT", &[("U6", "1"), ("R1", "2")])
+        .net("OD_NO_PULL_UP", &[("U7", "1"), ("U8", "1")])
+        .net("OD_PULL_UP", &[("U9", "1"), ("U10", "1"), ("R2", "1")])
+        .net("+3V3", &[("R2", "2")])
+        .net("EXTERNAL", &[("U11", "1"), ("J1", "1")])
+        .net("CAP_ONLY", &[("U12", "1"), ("C1", "1")])
+        .net("GND", &[("C1", "2")])
+        .net("INTERNAL_PULL_UP", &[("U13", "1")])
+        .net("CONTENTION", &[("U14", "1"), ("U15", "1"), ("U16", "1")])
+        .net("WIRED_AND", &[("U17", "1"), ("U18", "1")])
+        .net("TRI_BUS", &[("U19", "1"), ("U20", "1")])
+        .net("+5V", &[("U21", "1"), ("U22", "1")])
+        .net("VBUS", &[("D1", "1"), ("D2", "1")])
+        .build()
+    }
+
+    #[test]
+    fn able_to_find_undriven_inputs() {
+        let pcba = Pcba::new(netlist());
+        let undriven = find_undriven_inputs(&pcba);
+        let nets = undriven
+            .iter()
+            .map(|u| u.net.0.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(nets, ["CAP_ONLY", "FLOATING", "OD_NO_PULL_UP"]);
+        let floating = undriven.iter().find(|u| u.net.0 == "FLOATING").unwrap();
+        assert_eq!(
+            floating.inputs,
+            [
+                (Designator("U3".into()), PinId("1".into())),
+                (Designator("U4".into()), PinId("1".into()))
+            ]
+        );
+
+        let diagnostics = pcba.diagnostics.with_code("CON001").collect::<Vec<_>>();
+        assert_eq!(diagnostics.len(), 3);
+        assert!(diagnostics.iter().any(|d| d.to_string()
+            == "error CON001: 2 inputs are not driven by anything at net FLOATING, U3.1, U4.1"));
+    }
 
     #[test]
-    fn able_to_find_unconnected_inputs() {
-        // let path = Path::new("/Users/roman/Downloads/test_projects/vb135a_fdcan_iso_usb_hw.net");
-        // let netlist = ecad_file_format::load_kicad_netlist(&path).unwrap();
-        // let netlist = ecad_file_format::load_altium_netlist(Path::new("/Users/roman/Downloads/test_projects/typec_sbu_serial_revb/typec_sbu_serial.NET.EDF"), Path::new("/Users/roman/Downloads/test_projects/typec_sbu_serial_revb/typec_sbu_serial.NET")).unwrap();
-        let path = Path::new("/Users/roman/Downloads/test_projects/c_a6/pstxnet.dat");
-        let netlist = ecad_file_format::load_orcad_netlist(&path).unwrap();
-        let pcba = Pcba::new(netlist);
-        let k = &pcba
-            .netlist
-            .components
-            .get(&Designator("U12".into()))
-            .unwrap()
-            .lib_source;
-        let lib_part = pcba.netlist.lib_parts.get(k).unwrap();
-        println!("{:#?}", lib_part);
-
-        // println!("{:#?}", pcba.switching_nodes);
-        // println!("{:#?}", pcba.power);
-        // input_without_driving_source(&netlist);
-        // let power = find_power_structure(&netlist);
-        // println!("{power:#?}");
+    fn able_to_find_output_contentions() {
+        let pcba = Pcba::new(netlist());
+        let pairs = find_output_contentions(&pcba)
+            .into_iter()
+            .map(|c| format!("{} {}-{}", c.net.0, c.output.0.0, c.other.0.0))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            pairs,
+            [
+                "+5V U21-U22",
+                "CONTENTION U14-U15",
+                "CONTENTION U14-U16",
+                "CONTENTION U15-U16"
+            ]
+        );
+
+        let diagnostics = pcba.diagnostics.with_code("CON002").collect::<Vec<_>>();
+        assert_eq!(diagnostics.len(), 4);
+        assert!(diagnostics.iter().any(|d| d.to_string()
+            == "error CON002: DigitalOutput U14.1 drives against OpenCollector U16.1 at net CONTENTION, U14.1, U16.1"));
+    }
+
+    #[test]
+    fn able_to_find_incomplete_connections() {
+        let netlist = NetlistBuilder::new()
+            .lib_part(
+                "MCU",
+                &[
+                    ("P1", PinType::DigitalInput),
+                    ("P2", PinType::PowerIn),
+                    ("P3", PinType::Unconnected),
+                    ("P4", PinType::DigitalOutput),
+                    ("P5", PinType::Unconnected),
+                    ("P6", PinType::DigitalOutput),
+                ],
+            )
+            .part("U1", "", "MCU")
+            .net("unconnected-(U1-P2-Pad2)", &[("U1", "2")])
+            .net("TEST", &[("U1", "4")])
+            .net("WIRED", &[("U1", "5"), ("R1", "1")])
+            .net("unconnected-(U1-P6-Pad6)", &[("U1", "6")])
+            .net("LONELY", &[("R2", "1")])
+            .build();
+
+        let severity = ConnectivityCompletenessRule.default_severity();
+        let found = find_incomplete_connections(&netlist)
+            .into_iter()
+            .map(|finding| finding.to_diagnostic(severity).to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            [
+                "warning CON004: net has a single pin at net LONELY, R2.1",
+                "warning CON004: net has a single pin at net TEST, U1.4",
+                "error CON005: DigitalInput pin is not connected at U1.1",
+                "error CON005: PowerIn pin is not connected at U1.2",
+                "error CON006: pin marked as not connected is wired to other pins at net WIRED, U1.5",
+            ]
+        );
     }
 }
@@ -1,6 +1,9 @@
-use crate::config::{I2C_ACCEPTABLE_PULL_UP_RANGE, MAX_TIE_RESISTANCE};
+use crate::config::{PartClass, ProjectConfig};
+use crate::diagnostics::{Diagnostic, Diagnostics, Location};
+use crate::rule::{Rule, RuleCategory};
 use crate::util::collapse_underscores;
-use ecad_file_format::netlist::Netlist;
+use crate::{Pcba, Severity};
+use ecad_file_format::netlist::{ChainLimits, Netlist};
 use ecad_file_format::passive_value::Ohm;
 use ecad_file_format::{Designator, NetName, PinName};
 use std::collections::{HashMap, HashSet};
@@ -44,7 +47,7 @@ pub enum I2cNode {
     Unknown(Designator),
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum I2cDiagnosticKind {
     RedundantPullUps {
         redundant_pull_ups: HashSet<Designator>,
@@ -70,7 +73,7 @@ pub enum I2cDiagnosticKind {
     },
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct I2cDiagnostic {
     pub derived_name: String,
     pub kind: I2cDiagnosticKind,
@@ -83,9 +86,127 @@ pub struct I2cBuses {
     pub same_bus_segments: Vec<HashSet<String>>,
 }
 
-pub fn find_i2c_buses(netlist: &Netlist, diagnostics: &mut Vec<I2cDiagnostic>) -> I2cBuses {
+impl I2cDiagnosticKind {
+    pub fn code(&self) -> &'static str {
+        match self {
+            I2cDiagnosticKind::RedundantPullUps { .. } => "I2C001",
+            I2cDiagnosticKind::NoPullUps => "I2C002",
+            I2cDiagnosticKind::PullUpToNowhere => "I2C003",
+            I2cDiagnosticKind::WrongPullUpValue { .. } => "I2C004",
+            I2cDiagnosticKind::NonStandardPullUps { .. } => "I2C005",
+            I2cDiagnosticKind::NonEqualPullUps { .. } => "I2C006",
+            I2cDiagnosticKind::TieTooHighValue { .. } => "I2C007",
+            I2cDiagnosticKind::UnknownNode { .. } => "I2C008",
+        }
+    }
+
+    /// The rule's severity for a bus that does not work, at most a warning, suggestion or info
+    /// for the rest
+    pub fn severity(&self, rule_severity: Severity) -> Severity {
+        match self {
+            I2cDiagnosticKind::NoPullUps
+            | I2cDiagnosticKind::PullUpToNowhere
+            | I2cDiagnosticKind::WrongPullUpValue { .. } => rule_severity,
+            I2cDiagnosticKind::RedundantPullUps { .. }
+            | I2cDiagnosticKind::NonStandardPullUps { .. }
+            | I2cDiagnosticKind::TieTooHighValue { .. } => rule_severity.min(Severity::Warning),
+            I2cDiagnosticKind::NonEqualPullUps { .. } => rule_severity.min(Severity::Suggestion),
+            I2cDiagnosticKind::UnknownNode { .. } => rule_severity.min(Severity::Info),
+        }
+    }
+}
+
+impl I2cDiagnostic {
+    pub fn to_diagnostic(self, rule_severity: Severity) -> Diagnostic {
+        let kind = self.kind;
+        let message = match &kind {
+            I2cDiagnosticKind::RedundantPullUps { .. } => {
+                "more than one pull-up on the same bus segment".to_string()
+            }
+            I2cDiagnosticKind::NoPullUps => "no pull-ups on the bus segment".into(),
+            I2cDiagnosticKind::PullUpToNowhere => {
+                "pull-ups are connected to a net without a power source".into()
+            }
+            I2cDiagnosticKind::WrongPullUpValue { parse_message } => {
+                format!("pull-up value cannot be parsed: {parse_message}")
+            }
+            I2cDiagnosticKind::NonStandardPullUps { resistance } => {
+                format!("pull-up value {resistance:?} is out of the usual range")
+            }
+            I2cDiagnosticKind::NonEqualPullUps {
+                scl_resistance,
+                sda_resistance,
+            } => format!("SCL pull-up is {scl_resistance:?} and SDA pull-up is {sda_resistance:?}"),
+            I2cDiagnosticKind::TieTooHighValue {
+                resistance,
+                other_side,
+            } => format!("tie to {other_side} is {resistance:?}"),
+            I2cDiagnosticKind::UnknownNode { designator } => {
+                format!(
+                    "{} is connected to the bus, but is not a known I2C node",
+                    designator.0
+                )
+            }
+        };
+        let mut diagnostic = Diagnostic::new(kind.code(), kind.severity(rule_severity), message)
+            .at(Location::Bus(self.derived_name));
+        match kind {
+            I2cDiagnosticKind::RedundantPullUps { redundant_pull_ups } => {
+                let mut pull_ups = redundant_pull_ups.into_iter().collect::<Vec<_>>();
+                pull_ups.sort_by(|a, b| a.0.cmp(&b.0));
+                diagnostic
+                    .related
+                    .extend(pull_ups.into_iter().map(Location::Part));
+            }
+            I2cDiagnosticKind::TieTooHighValue { other_side, .. } => {
+                diagnostic = diagnostic.related_to(Location::Bus(other_side));
+            }
+            I2cDiagnosticKind::UnknownNode { designator } => {
+                diagnostic = diagnostic.at(Location::Part(designator));
+            }
+            _ => {}
+        }
+        diagnostic
+    }
+}
+
+/// Reports pull-up, tie and unknown node problems found while I2C buses were derived.
+///
+/// Buses are derived in `Pcba::new` whether the rule is enabled or not, as their nets are taken
+/// out of the power rails, the rule only reports `Pcba::i2c_findings`.
+pub struct I2cRule;
+
+impl Rule for I2cRule {
+    fn id(&self) -> &'static str {
+        "i2c.bus"
+    }
+
+    fn category(&self) -> RuleCategory {
+        RuleCategory::I2c
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn run(&self, pcba: &Pcba, diagnostics: &mut Diagnostics) {
+        let severity = self.default_severity();
+        diagnostics.extend(
+            pcba.i2c_findings
+                .iter()
+                .cloned()
+                .map(|finding| finding.to_diagnostic(severity)),
+        );
+    }
+}
+
+pub fn find_i2c_buses(
+    netlist: &Netlist,
+    config: &ProjectConfig,
+    diagnostics: &mut Vec<I2cDiagnostic>,
+) -> I2cBuses {
     let mut buses = vec![];
-    for scl_net in netlist.nets.keys() {
+    for scl_net in netlist.nets().keys() {
         let Some(scl_start) = scl_net.0.find("SCL") else {
             continue;
         };
@@ -96,13 +217,14 @@ pub fn find_i2c_buses(netlist: &Netlist, diagnostics: &mut Vec<I2cDiagnostic>) -
             ""
         };
         let sda_net = NetName(format!("{}SDA{}", prefix, suffix));
-        if !netlist.nets.keys().any(|k| k == &sda_net) {
+        if !netlist.nets().keys().any(|k| k == &sda_net) {
             continue;
         }
         let derived_name = collapse_underscores(format!("{}I2C{}", prefix, suffix).as_str());
         let mut connected_parts = netlist.any_net_parts(&[scl_net, &sda_net]);
         let pull_up = find_pull_ups(
             netlist,
+            config,
             diagnostics,
             scl_net,
             &sda_net,
@@ -114,11 +236,11 @@ pub fn find_i2c_buses(netlist: &Netlist, diagnostics: &mut Vec<I2cDiagnostic>) -
             scl_net: scl_net.clone(),
             sda_net,
             pull_up,
-            nodes: parts_to_nodes(netlist, connected_parts),
+            nodes: parts_to_nodes(netlist, config, connected_parts),
         });
     }
-    look_for_non_standard_pull_ups(netlist, &buses, diagnostics);
-    look_for_bus_interconnects(netlist, &mut buses, diagnostics);
+    look_for_non_standard_pull_ups(netlist, config, &buses, diagnostics);
+    look_for_bus_interconnects(netlist, config, &mut buses, diagnostics);
     let mut buses = I2cBuses {
         by_name: buses
             .into_iter()
@@ -130,23 +252,32 @@ pub fn find_i2c_buses(netlist: &Netlist, diagnostics: &mut Vec<I2cDiagnostic>) -
     buses.collect_nodes(netlist);
     buses.collect_segments();
     buses.warning_unknown_nodes(diagnostics);
-    buses.check_pull_ups(netlist, diagnostics);
+    buses.check_pull_ups(netlist, config, diagnostics);
     buses
 }
 
 fn find_pull_ups(
     netlist: &Netlist,
+    config: &ProjectConfig,
     diagnostics: &mut Vec<I2cDiagnostic>,
     scl_net: &NetName,
     sda_net: &NetName,
     derived_name: &String,
     mut connected_parts: &mut HashSet<Designator>,
 ) -> Option<I2cPullUp> {
-    let mut pull_up_chains = netlist.find_net_chains(
-        scl_net,
-        &[Designator::is_resistor, Designator::is_resistor],
-        &sda_net,
-    );
+    // going through ground would find pull-downs instead
+    let limits = ChainLimits {
+        max_paths: Some(1),
+        exclude_nets: netlist
+            .nets()
+            .keys()
+            .filter(|name| config.nets.is_ground(name))
+            .cloned()
+            .collect(),
+    };
+    let is_resistor = |d: &Designator| config.designators.is(d, PartClass::Resistor);
+    let mut pull_up_chains =
+        netlist.find_net_chains_limited(scl_net, &[is_resistor, is_resistor], &sda_net, &limits);
     let pull_up = if let Some(chain) = pull_up_chains.pop() {
         let scl_pull_up = chain[0].1.clone();
         let sda_pull_up = chain[1].1.clone();
@@ -160,15 +291,20 @@ fn find_pull_ups(
         connected_parts.remove(&pull_up.scl);
         connected_parts.remove(&pull_up.sda);
         // TODO: look in respect to any power net instead
-        look_for_redundant_pull_ups(
+        let redundant_pull_ups = look_for_redundant_pull_ups(
             netlist,
-            diagnostics,
+            config,
             scl_net,
             &sda_net,
-            &derived_name,
             &mut connected_parts,
             &pull_up,
         );
+        if !redundant_pull_ups.is_empty() {
+            diagnostics.push(I2cDiagnostic {
+                derived_name: derived_name.clone(),
+                kind: I2cDiagnosticKind::RedundantPullUps { redundant_pull_ups },
+            })
+        }
         Some(pull_up)
     } else {
         None
@@ -178,21 +314,16 @@ fn find_pull_ups(
 
 fn look_for_redundant_pull_ups(
     netlist: &Netlist,
-    diagnostics: &mut Vec<I2cDiagnostic>,
+    config: &ProjectConfig,
     scl_net: &NetName,
     sda_net: &NetName,
-    derived_name: &String,
     connected_parts: &mut HashSet<Designator>,
     pull_up: &I2cPullUp,
-) {
+) -> HashSet<Designator> {
     // find redundant pull-ups
-    let mut redundant_chains =
-        netlist.find_net_chains(scl_net, &[Designator::is_resistor], &pull_up.v_net);
-    redundant_chains.extend(netlist.find_net_chains(
-        &sda_net,
-        &[Designator::is_resistor],
-        &pull_up.v_net,
-    ));
+    let is_resistor = |d: &Designator| config.designators.is(d, PartClass::Resistor);
+    let mut redundant_chains = netlist.find_net_chains(scl_net, &[is_resistor], &pull_up.v_net);
+    redundant_chains.extend(netlist.find_net_chains(&sda_net, &[is_resistor], &pull_up.v_net));
     redundant_chains.retain(|c| {
         !c.iter()
             .any(|(_, d)| d == &pull_up.scl || d == &pull_up.sda)
@@ -205,16 +336,12 @@ fn look_for_redundant_pull_ups(
             redundant_pull_ups.insert(d);
         }
     }
-    if !redundant_pull_ups.is_empty() {
-        diagnostics.push(I2cDiagnostic {
-            derived_name: derived_name.clone(),
-            kind: I2cDiagnosticKind::RedundantPullUps { redundant_pull_ups },
-        })
-    }
+    redundant_pull_ups
 }
 
 fn look_for_non_standard_pull_ups(
     netlist: &Netlist,
+    config: &ProjectConfig,
     buses: &[I2cBus],
     diagnostics: &mut Vec<I2cDiagnostic>,
 ) {
@@ -226,9 +353,9 @@ fn look_for_non_standard_pull_ups(
         let sda_value = netlist.resistance(&pull_up.sda);
         match (scl_value, sda_value) {
             (Ok(scl_value), Ok(sda_value)) => {
-                check_pull_up_range(&scl_value, bus.derived_name.as_str(), diagnostics);
+                check_pull_up_range(config, &scl_value, bus.derived_name.as_str(), diagnostics);
                 if scl_value != sda_value {
-                    check_pull_up_range(&sda_value, bus.derived_name.as_str(), diagnostics);
+                    check_pull_up_range(config, &sda_value, bus.derived_name.as_str(), diagnostics);
                     diagnostics.push(I2cDiagnostic {
                         derived_name: bus.derived_name.clone(),
                         kind: I2cDiagnosticKind::NonEqualPullUps {
@@ -239,7 +366,7 @@ fn look_for_non_standard_pull_ups(
                 }
             }
             (Err(e), Ok(value)) | (Ok(value), Err(e)) => {
-                check_pull_up_range(&value, bus.derived_name.as_str(), diagnostics);
+                check_pull_up_range(config, &value, bus.derived_name.as_str(), diagnostics);
                 diagnostics.push(I2cDiagnostic {
                     derived_name: bus.derived_name.clone(),
                     kind: I2cDiagnosticKind::WrongPullUpValue {
@@ -265,9 +392,14 @@ fn look_for_non_standard_pull_ups(
     }
 }
 
-fn check_pull_up_range(value: &Ohm, bus_name: &str, diagnostics: &mut Vec<I2cDiagnostic>) {
-    if value <= I2C_ACCEPTABLE_PULL_UP_RANGE.start() || value >= I2C_ACCEPTABLE_PULL_UP_RANGE.end()
-    {
+fn check_pull_up_range(
+    config: &ProjectConfig,
+    value: &Ohm,
+    bus_name: &str,
+    diagnostics: &mut Vec<I2cDiagnostic>,
+) {
+    let range = config.thresholds.i2c_pull_up_range();
+    if value <= range.start() || value >= range.end() {
         diagnostics.push(I2cDiagnostic {
             derived_name: bus_name.to_string(),
             kind: I2cDiagnosticKind::NonStandardPullUps {
@@ -277,13 +409,18 @@ fn check_pull_up_range(value: &Ohm, bus_name: &str, diagnostics: &mut Vec<I2cDia
     }
 }
 
-fn parts_to_nodes(netlist: &Netlist, parts: HashSet<Designator>) -> Vec<I2cNode> {
+fn parts_to_nodes(
+    netlist: &Netlist,
+    config: &ProjectConfig,
+    parts: HashSet<Designator>,
+) -> Vec<I2cNode> {
     // TODO: implement I2C parts to nodes
     let mut nodes = vec![];
     for designator in parts {
-        if designator.0.starts_with('J') {
+        let class = config.designators.class_of(&designator);
+        if class == Some(PartClass::Connector) {
             nodes.push(I2cNode::Connector(designator));
-        } else if designator.0.starts_with('U') {
+        } else if class == Some(PartClass::Ic) {
             if let Some(component) = netlist.components.get(&designator) {
                 if let Some(lib_part) = netlist.lib_parts.get(&component.lib_source) {
                     let d = lib_part.description.to_lowercase();
@@ -299,7 +436,7 @@ fn parts_to_nodes(netlist: &Netlist, parts: HashSet<Designator>) -> Vec<I2cNode>
             } else {
                 nodes.push(I2cNode::Unknown(designator));
             }
-        } else if designator.0.starts_with("TP") {
+        } else if class == Some(PartClass::TestPoint) {
             nodes.push(I2cNode::TestPoint(designator));
         } else {
             nodes.push(I2cNode::Unknown(designator));
@@ -324,9 +461,12 @@ impl I2cNode {
 
 fn look_for_bus_interconnects(
     netlist: &Netlist,
+    config: &ProjectConfig,
     buses: &mut Vec<I2cBus>,
     diagnostics: &mut Vec<I2cDiagnostic>,
 ) {
+    let is_resistor = |d: &Designator| config.designators.is(d, PartClass::Resistor);
+    let is_transistor = |d: &Designator| config.designators.is(d, PartClass::Transistor);
     let mut modifications: HashMap<String, Vec<I2cNode>> = HashMap::new();
     let mut create_buses: Vec<I2cBus> = vec![];
     for bus in buses.iter() {
@@ -340,7 +480,7 @@ fn look_for_bus_interconnects(
             {
                 continue;
             }
-            if designator.is_resistor() || designator.is_transistor() {
+            if is_resistor(designator) || is_transistor(designator) {
                 let nets = netlist.part_nets_exclude_pin_names(
                     designator,
                     &[&PinName("G".into()), &PinName("GATE".into())],
@@ -365,10 +505,10 @@ fn look_for_bus_interconnects(
                     } else {
                         &other_bus.scl_net
                     };
-                    let goes_through = if designator.is_resistor() {
-                        Designator::is_resistor
+                    let goes_through: &dyn Fn(&Designator) -> bool = if is_resistor(designator) {
+                        &is_resistor
                     } else {
-                        Designator::is_transistor
+                        &is_transistor
                     };
                     let complementary_part = netlist.find_net_chains(
                         other_complementary_net,
@@ -378,7 +518,7 @@ fn look_for_bus_interconnects(
                     if let Some(complementary_part) = complementary_part.first() {
                         if let Some(p) = complementary_part.first() {
                             let complementary_part = &p.1;
-                            if designator.is_resistor() {
+                            if is_resistor(designator) {
                                 modifications
                                     .entry(bus.derived_name.clone())
                                     .or_default()
@@ -397,6 +537,7 @@ fn look_for_bus_interconnects(
                                     });
                                 check_tie_resistance(
                                     netlist,
+                                    config,
                                     diagnostics,
                                     bus.derived_name.as_str(),
                                     designator,
@@ -438,8 +579,7 @@ fn look_for_bus_interconnects(
                         if complementary_part == designator {
                             continue;
                         }
-                        if !complementary_part.is_resistor() && !complementary_part.is_transistor()
-                        {
+                        if !is_resistor(complementary_part) && !is_transistor(complementary_part) {
                             continue;
                         }
                         for other_part in &other_side_parts {
@@ -470,6 +610,7 @@ fn look_for_bus_interconnects(
                         let mut connected_parts = netlist.any_net_parts(&[&scl_net, &sda_net]);
                         let pull_up = find_pull_ups(
                             netlist,
+                            config,
                             diagnostics,
                             &scl_net,
                             &sda_net,
@@ -481,11 +622,12 @@ fn look_for_bus_interconnects(
                             scl_net,
                             sda_net,
                             pull_up,
-                            nodes: parts_to_nodes(netlist, connected_parts),
+                            nodes: parts_to_nodes(netlist, config, connected_parts),
                         });
-                        if designator.is_resistor() {
+                        if is_resistor(designator) {
                             check_tie_resistance(
                                 netlist,
+                                config,
                                 diagnostics,
                                 bus.derived_name.as_str(),
                                 designator,
@@ -538,6 +680,7 @@ fn look_for_bus_interconnects(
 
 fn check_tie_resistance(
     netlist: &Netlist,
+    config: &ProjectConfig,
     diagnostics: &mut Vec<I2cDiagnostic>,
     bus_name: &str,
     scl_tie: &Designator,
@@ -546,7 +689,8 @@ fn check_tie_resistance(
 ) {
     let scl_tie_resistance = netlist.resistance(scl_tie).unwrap_or(Ohm(0.0));
     let sda_tie_resistance = netlist.resistance(sda_tie).unwrap_or(Ohm(0.0));
-    if scl_tie_resistance > MAX_TIE_RESISTANCE || sda_tie_resistance > MAX_TIE_RESISTANCE {
+    let max_tie_resistance = &config.thresholds.max_tie_resistance;
+    if &scl_tie_resistance > max_tie_resistance || &sda_tie_resistance > max_tie_resistance {
         diagnostics.push(I2cDiagnostic {
             derived_name: bus_name.to_string(),
             kind: I2cDiagnosticKind::TieTooHighValue {
@@ -655,7 +799,12 @@ impl I2cBuses {
         }
     }
 
-    fn check_pull_ups(&self, netlist: &Netlist, diagnostics: &mut Vec<I2cDiagnostic>) {
+    fn check_pull_ups(
+        &self,
+        netlist: &Netlist,
+        config: &ProjectConfig,
+        diagnostics: &mut Vec<I2cDiagnostic>,
+    ) {
         for direct_segment in &self.direct_segments {
             let mut pull_up_count = 0;
             let mut pull_ups = HashSet::new();
@@ -666,7 +815,7 @@ impl I2cBuses {
                 if let Some(pull_up) = &bus.pull_up {
                     pull_ups.insert(pull_up.scl.clone());
                     pull_ups.insert(pull_up.sda.clone());
-                    if let Some(pull_up_net) = netlist.nets.get(&pull_up.v_net) {
+                    if let Some(pull_up_net) = netlist.nets().get(&pull_up.v_net) {
                         let node_count = pull_up_net.nodes.iter().count();
                         if node_count == 2 {
                             diagnostics.push(I2cDiagnostic {
@@ -681,11 +830,14 @@ impl I2cBuses {
                             let mut parts = netlist.any_net_parts(&[&pull_up.v_net]);
                             parts.remove(&pull_up.scl);
                             parts.remove(&pull_up.sda);
-                            if let Some(third_resistor) = parts.iter().find(|d| d.is_resistor()) {
+                            if let Some(third_resistor) = parts
+                                .iter()
+                                .find(|d| config.designators.is(d, PartClass::Resistor))
+                            {
                                 let mut nets = netlist.part_nets(third_resistor);
                                 nets.remove(&pull_up.v_net);
                                 if let Some(should_be_power_net) = nets.iter().next() {
-                                    if let Some(net) = netlist.nets.get(should_be_power_net) {
+                                    if let Some(net) = netlist.nets().get(should_be_power_net) {
                                         if net.nodes.iter().count() == 1 {
                                             diagnostics.push(I2cDiagnostic {
                                                 derived_name: direct_segment
@@ -729,14 +881,67 @@ impl I2cBuses {
 mod tests {
     use super::*;
     use ecad_file_format::kicad_netlist::load_kicad_netlist;
-    use generate_netlists::get_netlist_path;
+    use ecad_file_format::load_kicad_schematic;
+    use generate_netlists::{get_netlist_path, kicad_cli_available};
+    use std::collections::BTreeMap;
+    use std::path::PathBuf;
+
+    const TEST_SCHEMATICS: [&str; 5] = [
+        "i2c_segments",
+        "i2c_no_pull_ups",
+        "i2c_non_standard_pull_ups",
+        "i2c_non_equal_pull_ups",
+        "i2c_multiple_pull_ups",
+    ];
+
+    fn load_test_schematic(name: &str) -> Netlist {
+        let path = PathBuf::from(format!("../test_schematics/sources/{name}.kicad_sch"));
+        load_kicad_schematic(&path).unwrap()
+    }
+
+    /// Nets as sorted (designator, pin id) lists, locations differ between the two readers
+    fn connectivity(netlist: &Netlist) -> BTreeMap<String, Vec<(String, String)>> {
+        netlist
+            .nets()
+            .iter()
+            .map(|(name, net)| {
+                let mut nodes = net
+                    .nodes
+                    .iter()
+                    .map(|n| (n.designator.0.clone(), n.pin_id.0.clone()))
+                    .collect::<Vec<_>>();
+                nodes.sort();
+                (name.0.clone(), nodes)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn native_reader_matches_kicad_cli_netlists() {
+        if !kicad_cli_available() {
+            println!("kicad-cli not found, skipped");
+            return;
+        }
+        for name in TEST_SCHEMATICS {
+            let native = load_test_schematic(name);
+            let exported = load_kicad_netlist(&get_netlist_path(name)).unwrap();
+            assert_eq!(connectivity(&native), connectivity(&exported), "{name}");
+            let values = |netlist: &Netlist| {
+                netlist
+                    .components
+                    .iter()
+                    .map(|(designator, c)| (designator.0.clone(), c.value.clone()))
+                    .collect::<BTreeMap<_, _>>()
+            };
+            assert_eq!(values(&native), values(&exported), "{name}");
+        }
+    }
 
     #[test]
     fn able_to_recognize_i2c_bus_segments() {
-        let path = get_netlist_path("i2c_segments");
-        let netlist = load_kicad_netlist(&path).unwrap();
+        let netlist = load_test_schematic("i2c_segments");
         let mut diagnostics = Vec::new();
-        let buses = find_i2c_buses(&netlist, &mut diagnostics);
+        let buses = find_i2c_buses(&netlist, &ProjectConfig::default(), &mut diagnostics);
         // println!("buses: {buses:#?}");
         // println!("diagnostics: {diagnostics:#?}");
         // println!("ds: {:?}", buses.direct_segments);
@@ -833,10 +1038,9 @@ mod tests {
 
     #[test]
     fn able_to_find_missing_i2c_pull_ups() {
-        let path = get_netlist_path("i2c_no_pull_ups");
-        let netlist = load_kicad_netlist(&path).unwrap();
+        let netlist = load_test_schematic("i2c_no_pull_ups");
         let mut diagnostics = Vec::new();
-        let _buses = find_i2c_buses(&netlist, &mut diagnostics);
+        let _buses = find_i2c_buses(&netlist, &ProjectConfig::default(), &mut diagnostics);
         // println!("buses: {buses:#?}");
         // println!("diagnostics: {diagnostics:#?}");
         assert!(diagnostics.contains(&I2cDiagnostic {
@@ -855,10 +1059,9 @@ mod tests {
 
     #[test]
     fn able_to_find_non_standard_pull_ups() {
-        let path = get_netlist_path("i2c_non_standard_pull_ups");
-        let netlist = load_kicad_netlist(&path).unwrap();
+        let netlist = load_test_schematic("i2c_non_standard_pull_ups");
         let mut diagnostics = Vec::new();
-        let _buses = find_i2c_buses(&netlist, &mut diagnostics);
+        let _buses = find_i2c_buses(&netlist, &ProjectConfig::default(), &mut diagnostics);
         assert_eq!(
             diagnostics[0],
             I2cDiagnostic {
@@ -872,10 +1075,9 @@ mod tests {
 
     #[test]
     fn able_to_find_non_equal_pull_ups() {
-        let path = get_netlist_path("i2c_non_equal_pull_ups");
-        let netlist = load_kicad_netlist(&path).unwrap();
+        let netlist = load_test_schematic("i2c_non_equal_pull_ups");
         let mut diagnostics = Vec::new();
-        let _buses = find_i2c_buses(&netlist, &mut diagnostics);
+        let _buses = find_i2c_buses(&netlist, &ProjectConfig::default(), &mut diagnostics);
         assert_eq!(
             diagnostics[0],
             I2cDiagnostic {
@@ -890,10 +1092,9 @@ mod tests {
 
     #[test]
     fn able_to_find_multiple_i2c_pull_ups() {
-        let path = get_netlist_path("i2c_multiple_pull_ups");
-        let netlist = load_kicad_netlist(&path).unwrap();
+        let netlist = load_test_schematic("i2c_multiple_pull_ups");
         let mut diagnostics = Vec::new();
-        let buses = find_i2c_buses(&netlist, &mut diagnostics);
+        let buses = find_i2c_buses(&netlist, &ProjectConfig::default(), &mut diagnostics);
 
         let i2c1_1v8_bus = buses.by_name.get("/I2C1_1V8").unwrap();
         let pull_up = i2c1_1v8_bus.pull_up.as_ref().unwrap();
@@ -913,4 +1114,40 @@ mod tests {
         expected_redundant_pull_ups.remove(&pull_up.sda);
         assert_eq!(&expected_redundant_pull_ups, redundant_pull_ups);
     }
+
+    #[test]
+    fn findings_convert_to_diagnostics() {
+        let diagnostic = I2cDiagnostic {
+            derived_name: "I2C1".into(),
+            kind: I2cDiagnosticKind::RedundantPullUps {
+                redundant_pull_ups: HashSet::from([
+                    Designator("R7".into()),
+                    Designator("R3".into()),
+                ]),
+            },
+        }
+        .to_diagnostic(I2cRule.default_severity());
+        assert_eq!(diagnostic.code, "I2C001");
+        assert_eq!(diagnostic.severity, Severity::Warning);
+        assert_eq!(diagnostic.locations, [Location::Bus("I2C1".into())]);
+        assert_eq!(
+            diagnostic.related,
+            [
+                Location::Part(Designator("R3".into())),
+                Location::Part(Designator("R7".into()))
+            ]
+        );
+
+        let no_pull_ups = I2cDiagnosticKind::NoPullUps;
+        assert_eq!(
+            no_pull_ups.severity(I2cRule.default_severity()),
+            Severity::Error
+        );
+        assert_eq!(no_pull_ups.severity(Severity::Warning), Severity::Warning);
+        let unknown = I2cDiagnosticKind::UnknownNode {
+            designator: Designator("U1".into()),
+        };
+        assert_eq!(unknown.severity(Severity::Error), Severity::Info);
+        assert_eq!(unknown.severity(Severity::Suggestion), Severity::Suggestion);
+    }
 }
@@ -1,18 +1,33 @@
-mod config;
+pub mod baseline;
+pub mod config;
 mod diagnostics;
 pub mod general;
 pub mod i2c;
 pub mod pcba;
+pub mod pin_matrix;
 pub mod power;
+pub mod report;
+pub mod rule;
 pub mod style;
 pub(crate) mod util;
+pub mod waiver;
 
-pub use pcba::Pcba;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
 
-#[cfg(test)]
-mod tests {}
+pub use baseline::{Baseline, BaselineComparison};
+pub use config::ProjectConfig;
+pub use diagnostics::{Diagnostic, Diagnostics, Location};
+pub use pcba::Pcba;
+pub use report::{Report, ReportFormat};
+pub use rule::{Rule, RuleCategory, RuleRegistry};
+pub use waiver::{Waiver, Waivers};
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// Ordered by how severe it is, Error is the greatest
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Severity {
     Error,
     SevereWarning,
@@ -20,3 +35,83 @@ pub enum Severity {
     Info,
     Suggestion,
 }
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Severity::Error => "error",
+            Severity::SevereWarning => "severe warning",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+            Severity::Suggestion => "suggestion",
+        };
+        write!(f, "{s}")
+    }
+}
+
+impl Severity {
+    /// Returns true if self is as severe as the threshold or more severe, e.g. Error is at least Warning
+    pub fn is_at_least(self, threshold: Severity) -> bool {
+        self >= threshold
+    }
+
+    fn rank(self) -> u8 {
+        match self {
+            Severity::Suggestion => 0,
+            Severity::Info => 1,
+            Severity::Warning => 2,
+            Severity::SevereWarning => 3,
+            Severity::Error => 4,
+        }
+    }
+}
+
+impl PartialOrd for Severity {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Severity {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.rank().cmp(&other.rank())
+    }
+}
+
+impl FromStr for Severity {
+    type Err = anyhow::Error;
+
+    /// Parses the same names as the project config, e.g. "error" or "severe_warning"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "error" => Severity::Error,
+            "severe_warning" => Severity::SevereWarning,
+            "warning" => Severity::Warning,
+            "info" => Severity::Info,
+            "suggestion" => Severity::Suggestion,
+            _ => {
+                return Err(anyhow::Error::msg(format!(
+                    "unknown severity \"{s}\", expected error, severe_warning, warning, info or suggestion"
+                )));
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Severity;
+
+    #[test]
+    fn severities_are_ordered() {
+        assert!(Severity::Error.is_at_least(Severity::Warning));
+        assert!(Severity::Warning.is_at_least(Severity::Warning));
+        assert!(!Severity::Info.is_at_least(Severity::Warning));
+        assert!(Severity::SevereWarning > Severity::Warning);
+        assert_eq!(
+            "severe_warning".parse::<Severity>().unwrap(),
+            Severity::SevereWarning
+        );
+        assert!("fatal".parse::<Severity>().is_err());
+    }
+}
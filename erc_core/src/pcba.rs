@@ -1,32 +1,63 @@
+use crate::config::ProjectConfig;
 use crate::diagnostics::Diagnostics;
-use crate::i2c::{I2cBuses, find_i2c_buses};
+use crate::i2c::{I2cBuses, I2cDiagnostic, find_i2c_buses};
+use crate::power::feedback::{FeedbackDivider, find_feedback_dividers};
+use crate::power::propagation::{VoltageConflict, propagate_voltages};
+use crate::power::tree::{PowerTree, build_power_tree};
 use crate::power::{Power, derive_power_structure};
-use crate::style::check_style;
+use crate::rule::RuleRegistry;
+use crate::waiver::{Date, Waivers};
+use anyhow::Result;
 use ecad_file_format::netlist::Netlist;
 use ecad_file_format::{Designator, NetName};
 use std::collections::HashSet;
 
 pub struct Pcba {
     pub netlist: Netlist,
+    pub config: ProjectConfig,
     pub power: Power,
     pub switching_nodes: HashSet<NetName>,
+    /// Sources and converters feeding each power rail, and the ICs on it
+    pub power_tree: PowerTree,
+    /// Resistor dividers on the feedback pins of adjustable regulators
+    pub feedback_dividers: Vec<FeedbackDivider>,
     pub i2c_buses: I2cBuses,
+    /// Problems found while looking for I2C buses, reported by the i2c rule
+    pub i2c_findings: Vec<I2cDiagnostic>,
+    /// Rails that got different voltages from different sources, reported by the rail voltage rule
+    pub voltage_conflicts: Vec<VoltageConflict>,
     pub diagnostics: Diagnostics,
 }
 
 impl Pcba {
+    /// Analyses the board with default settings and runs all the built-in rules
     pub fn new(netlist: Netlist) -> Self {
-        let mut diagnostics = Diagnostics::default();
-        let power = derive_power_structure(&netlist, true); // TODO: move strict to config
-        let i2c_buses = find_i2c_buses(&netlist, &mut diagnostics.i2c);
-        check_style(&netlist, &mut diagnostics.style);
+        Self::with_rules(netlist, ProjectConfig::default(), &RuleRegistry::default())
+    }
+
+    /// Analyses the board and runs the built-in rules, as configured for the project
+    pub fn with_config(netlist: Netlist, config: ProjectConfig) -> Result<Self> {
+        let rules = RuleRegistry::from_config(&config)?;
+        Ok(Self::with_rules(netlist, config, &rules))
+    }
+
+    /// Analyses the board and runs enabled rules from the registry
+    pub fn with_rules(netlist: Netlist, config: ProjectConfig, rules: &RuleRegistry) -> Self {
+        let power = derive_power_structure(&netlist, &config);
+        let mut i2c_findings = Vec::new();
+        let i2c_buses = find_i2c_buses(&netlist, &config, &mut i2c_findings);
 
         let mut pcba = Self {
             netlist,
+            config,
             power,
             switching_nodes: HashSet::new(),
+            power_tree: PowerTree::default(),
+            feedback_dividers: vec![],
             i2c_buses,
-            diagnostics,
+            i2c_findings,
+            voltage_conflicts: vec![],
+            diagnostics: Diagnostics::default(),
         };
 
         let switching_nodes = crate::power::find_switching_nodes(&pcba);
@@ -43,9 +74,33 @@ impl Pcba {
             pcba.power.power_rails.remove(&bus.sda_net);
         }
 
+        pcba.power_tree = build_power_tree(
+            &pcba.netlist,
+            &pcba.config,
+            &pcba.power,
+            &pcba.switching_nodes,
+        );
+        pcba.feedback_dividers =
+            find_feedback_dividers(&pcba.netlist, &pcba.config, &pcba.power, &pcba.power_tree);
+        pcba.voltage_conflicts = propagate_voltages(
+            &pcba.netlist,
+            &pcba.config,
+            &mut pcba.power,
+            &pcba.power_tree,
+            &pcba.feedback_dividers,
+        );
+
+        let mut diagnostics = Diagnostics::default();
+        rules.run(&pcba, &mut diagnostics);
+        pcba.diagnostics = diagnostics;
         pcba
     }
 
+    /// Marks waived diagnostics, waivers that expired before today no longer apply
+    pub fn apply_waivers(&mut self, waivers: &Waivers) {
+        waivers.apply(&mut self.diagnostics, Date::today());
+    }
+
     /// Returns a set of parts with a particular designator that are connected to 'from' part.
     /// Optionally ignoring parts connected through power nets, which is probably what is needed most of the time.
     pub fn find_connected_parts<F: Fn(&Designator) -> bool>(
@@ -55,21 +110,21 @@ impl Pcba {
         ignore_power_nets: bool,
     ) -> HashSet<Designator> {
         let mut parts = HashSet::new();
-        for designator in self.netlist.components.keys() {
-            if to_filter(designator) {
-                let mut common_nets = self.netlist.parts_common_nets(from, designator);
-                if ignore_power_nets {
-                    for ground_net in &self.power.ground_nets {
-                        common_nets.remove(ground_net);
-                    }
-                    for power_net in self.power.power_rails.keys() {
-                        common_nets.remove(power_net);
-                    }
-                }
-                if !common_nets.is_empty() {
-                    parts.insert(designator.clone());
-                }
+        for net_name in self.netlist.part_nets(from) {
+            if ignore_power_nets && self.power.is_power_net(&net_name) {
+                continue;
             }
+            let Some(net_parts) = self.netlist.index().net_parts(&net_name) else {
+                continue;
+            };
+            parts.extend(
+                net_parts
+                    .iter()
+                    .filter(|designator| {
+                        to_filter(designator) && self.netlist.components.contains_key(designator)
+                    })
+                    .cloned(),
+            );
         }
         parts
     }
@@ -0,0 +1,348 @@
+use crate::config::ProjectConfig;
+use crate::diagnostics::{Diagnostic, Diagnostics, Location};
+use crate::rule::{Rule, RuleCategory};
+use crate::{Pcba, Severity};
+use ecad_file_format::netlist::{Netlist, PinType};
+use ecad_file_format::{Designator, NetName, PinId};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+const N: usize = PinType::ALL.len();
+
+/// Whether two pin types may be connected to the same net
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PinCompatibility {
+    Ok,
+    Warning,
+    Error,
+}
+
+/// Symmetric table of pin type pairs, similar to KiCad's pin conflicts map
+#[derive(Debug, Clone)]
+pub struct PinMatrix {
+    levels: [[PinCompatibility; N]; N],
+}
+
+/// Pins of two types that should not be on one net
+#[derive(Debug, Clone, PartialEq)]
+pub struct PinConflict {
+    pub net: NetName,
+    pub types: (PinType, PinType),
+    pub level: PinCompatibility,
+    /// Pins of both types on the net, sorted
+    pub pins: Vec<(Designator, PinId)>,
+}
+
+/// Checks every net's pin types against the pin matrix from the project config
+pub struct PinMatrixRule;
+
+impl Rule for PinMatrixRule {
+    fn id(&self) -> &'static str {
+        "connectivity.pin_matrix"
+    }
+
+    fn category(&self) -> RuleCategory {
+        RuleCategory::Connectivity
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn run(&self, pcba: &Pcba, diagnostics: &mut Diagnostics) {
+        let matrix = PinMatrix::from_config(&pcba.config);
+        let severity = self.default_severity();
+        diagnostics.extend(
+            find_pin_conflicts(&pcba.netlist, &matrix)
+                .into_iter()
+                .map(|conflict| conflict.to_diagnostic(severity)),
+        );
+    }
+}
+
+impl PinMatrix {
+    /// Default table with `[[pin_matrix]]` overrides from the project config applied in order
+    pub fn from_config(config: &ProjectConfig) -> Self {
+        let mut matrix = PinMatrix::default();
+        for pin_override in &config.pin_matrix {
+            let [a, b] = pin_override.pins;
+            matrix.set(a, b, pin_override.level);
+        }
+        matrix
+    }
+
+    pub fn get(&self, a: PinType, b: PinType) -> PinCompatibility {
+        self.levels[a as usize][b as usize]
+    }
+
+    pub fn set(&mut self, a: PinType, b: PinType, level: PinCompatibility) {
+        self.levels[a as usize][b as usize] = level;
+        self.levels[b as usize][a as usize] = level;
+    }
+}
+
+/// KiCad's default pin conflicts map, except that unspecified pins are allowed with anything but
+/// unconnected ones, as many netlist formats do not carry pin types at all.
+///
+/// Push-pull outputs together with any other driver are allowed as well, these are reported by
+/// [crate::general::OutputContentionRule], which also knows about OR-ing diodes.
+impl Default for PinMatrix {
+    fn default() -> Self {
+        use PinCompatibility::{Error as E, Ok as O, Warning as W};
+        // input, output, bidirectional, tri-state, passive, unspecified, power in, power out,
+        // open collector, open emitter, unconnected
+        const KICAD: [[PinCompatibility; 11]; 11] = [
+            [O, O, O, O, O, O, O, O, O, O, E],
+            [O, E, O, W, O, O, O, E, E, E, E],
+            [O, O, O, O, O, O, O, W, O, W, E],
+            [O, W, O, O, O, O, W, E, W, W, E],
+            [O, O, O, O, O, O, O, O, O, O, E],
+            [O, O, O, O, O, O, O, O, O, O, E],
+            [O, O, O, W, O, O, O, O, O, O, E],
+            [O, E, W, E, O, O, O, E, E, E, E],
+            [O, E, O, W, O, O, O, E, O, O, E],
+            [O, E, W, W, O, O, O, E, O, O, E],
+            [E, E, E, E, E, E, E, E, E, E, E],
+        ];
+        let kicad_type = |ty: PinType| match ty {
+            PinType::DigitalInput | PinType::AnalogInput => 0,
+            PinType::DigitalOutput | PinType::AnalogOutput => 1,
+            PinType::DigitalIO | PinType::AnalogIO => 2,
+            PinType::TriState => 3,
+            PinType::Passive | PinType::PowerIO => 4,
+            PinType::Unspecified => 5,
+            PinType::PowerIn | PinType::PowerUnspecified => 6,
+            PinType::PowerOut => 7,
+            PinType::OpenCollector => 8,
+            PinType::OpenEmitter => 9,
+            PinType::Unconnected => 10,
+        };
+        let mut matrix = PinMatrix {
+            levels: [[O; N]; N],
+        };
+        for a in PinType::ALL {
+            for b in PinType::ALL {
+                matrix.levels[a as usize][b as usize] = KICAD[kicad_type(a)][kicad_type(b)];
+            }
+        }
+        let push_pull = [
+            PinType::DigitalOutput,
+            PinType::AnalogOutput,
+            PinType::PowerOut,
+        ];
+        let drivers = [
+            PinType::OpenCollector,
+            PinType::OpenEmitter,
+            PinType::TriState,
+        ];
+        for a in push_pull {
+            for b in push_pull.into_iter().chain(drivers) {
+                matrix.set(a, b, O);
+            }
+        }
+        matrix
+    }
+}
+
+/// Returns one conflict per net and pair of pin types, sorted by net name.
+/// Pins of the same part are not checked against each other, e.g. several VOUT pins of a regulator.
+pub fn find_pin_conflicts(netlist: &Netlist, matrix: &PinMatrix) -> Vec<PinConflict> {
+    let mut conflicts = vec![];
+    for (net_name, net) in netlist.nets() {
+        let pins = net
+            .nodes
+            .iter()
+            .filter_map(|node| {
+                let ty = netlist.pin(&node.designator, &node.pin_id)?.default_mode.ty;
+                Some((&node.designator, &node.pin_id, ty))
+            })
+            .collect::<Vec<_>>();
+        let mut by_types: BTreeMap<(usize, usize), Vec<(Designator, PinId)>> = BTreeMap::new();
+        for (i, (designator_a, pin_a, ty_a)) in pins.iter().enumerate() {
+            for (designator_b, pin_b, ty_b) in &pins[i + 1..] {
+                if designator_a == designator_b || matrix.get(*ty_a, *ty_b) == PinCompatibility::Ok
+                {
+                    continue;
+                }
+                let key = if (*ty_a as usize) <= (*ty_b as usize) {
+                    (*ty_a as usize, *ty_b as usize)
+                } else {
+                    (*ty_b as usize, *ty_a as usize)
+                };
+                let found = by_types.entry(key).or_default();
+                for (designator, pin) in [(designator_a, pin_a), (designator_b, pin_b)] {
+                    if !found.iter().any(|(d, p)| &d == designator && &p == pin) {
+                        found.push(((*designator).clone(), (*pin).clone()));
+                    }
+                }
+            }
+        }
+        for ((a, b), mut found) in by_types {
+            let types = (PinType::ALL[a], PinType::ALL[b]);
+            found.sort_by(|a, b| (&a.0.0, &a.1.0).cmp(&(&b.0.0, &b.1.0)));
+            conflicts.push(PinConflict {
+                net: net_name.clone(),
+                types,
+                level: matrix.get(types.0, types.1),
+                pins: found,
+            });
+        }
+    }
+    conflicts.sort_by(|a, b| {
+        a.net.0.cmp(&b.net.0).then(
+            (a.types.0 as usize, a.types.1 as usize).cmp(&(b.types.0 as usize, b.types.1 as usize)),
+        )
+    });
+    conflicts
+}
+
+impl PinConflict {
+    /// Error cells of the matrix get the rule's severity, warning cells at most a warning
+    pub fn to_diagnostic(self, severity: Severity) -> Diagnostic {
+        let severity = match self.level {
+            PinCompatibility::Error => severity,
+            _ => severity.min(Severity::Warning),
+        };
+        let (a, b) = self.types;
+        let message = format!("{a:?} pin connected to {b:?} pin");
+        let mut diagnostic =
+            Diagnostic::new("CON003", severity, message).at(Location::Net(self.net));
+        for (designator, pin_id) in self.pins {
+            diagnostic = diagnostic.at(Location::Node(designator, pin_id));
+        }
+        diagnostic
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PinCompatibility, PinMatrix, find_pin_conflicts};
+    use crate::config::ProjectConfig;
+    use crate::{Pcba, Severity};
+    use ecad_file_format::netlist::{Netlist, PinType};
+    use ecad_file_format::test_util::NetlistBuilder;
+
+    fn netlist() -> Netlist {
+        let builder = PinType::ALL
+            .into_iter()
+            .fold(NetlistBuilder::new(), |builder, ty| {
+                builder.lib_part(&format!("{ty:?}"), &[("1", ty)])
+            });
+        [
+            ("U1", PinType::PowerOut),
+            ("U2", PinType::PowerOut),
+            ("U3", PinType::PowerIn),
+            ("U4", PinType::DigitalOutput),
+            ("U5", PinType::Unconnected),
+            ("U6", PinType::DigitalOutput),
+            ("U7", PinType::TriState),
+            ("U8", PinType::PowerIn),
+        ]
+        .into_iter()
+        .fold(builder, |builder, (designator, ty)| {
+            builder.part(designator, "", &format!("{ty:?}"))
+        })
+        .net("+5V", &[("U1", "1"), ("U2", "1"), ("U3", "1")])
+        .net("NC", &[("U4", "1"), ("U5", "1")])
+        .net("BUS", &[("U6", "1"), ("U7", "1"), ("U8", "1")])
+        .build()
+    }
+
+    #[test]
+    fn pin_conflicts_are_found_with_default_matrix() {
+        let matrix = PinMatrix::default();
+        assert_eq!(
+            matrix.get(PinType::TriState, PinType::PowerIn),
+            PinCompatibility::Warning
+        );
+        assert_eq!(
+            matrix.get(PinType::PowerIn, PinType::TriState),
+            matrix.get(PinType::TriState, PinType::PowerIn)
+        );
+        // left to the output contention rule
+        assert_eq!(
+            matrix.get(PinType::PowerOut, PinType::PowerOut),
+            PinCompatibility::Ok
+        );
+        assert_eq!(
+            matrix.get(PinType::OpenCollector, PinType::DigitalOutput),
+            PinCompatibility::Ok
+        );
+
+        let conflicts = find_pin_conflicts(&netlist(), &matrix)
+            .into_iter()
+            .map(|c| {
+                let pins = c.pins.iter().map(|(d, _)| d.0.as_str()).collect::<Vec<_>>();
+                (c.net.0, c.types, c.level, pins.join(","))
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            conflicts,
+            [
+                (
+                    "BUS".to_string(),
+                    (PinType::PowerIn, PinType::TriState),
+                    PinCompatibility::Warning,
+                    "U7,U8".to_string()
+                ),
+                (
+                    "NC".to_string(),
+                    (PinType::DigitalOutput, PinType::Unconnected),
+                    PinCompatibility::Error,
+                    "U4,U5".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn pin_matrix_is_overridden_from_config() {
+        let config = ProjectConfig::from_toml(
+            "[[pin_matrix]]\npins = [\"power_in\", \"tri_state\"]\nlevel = \"ok\"\n\n\
+             [[pin_matrix]]\npins = [\"tri_state\", \"digital_output\"]\nlevel = \"error\"",
+        )
+        .unwrap();
+        let pcba = Pcba::with_config(netlist(), config).unwrap();
+        let mut found = pcba
+            .diagnostics
+            .with_code("CON003")
+            .map(|d| (d.severity, d.message.as_str()))
+            .collect::<Vec<_>>();
+        found.sort_by_key(|(_, message)| *message);
+        assert_eq!(
+            found,
+            [
+                (
+                    Severity::Error,
+                    "DigitalOutput pin connected to TriState pin"
+                ),
+                (
+                    Severity::Error,
+                    "DigitalOutput pin connected to Unconnected pin"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn output_contention_is_reported_once() {
+        let pcba = Pcba::new(netlist());
+        let mut found = pcba
+            .diagnostics
+            .iter()
+            .filter(|d| d.code == "CON002" || d.code == "CON003")
+            .map(|d| (d.locations[0].to_string(), d.code))
+            .collect::<Vec<_>>();
+        found.sort();
+        assert_eq!(
+            found,
+            [
+                ("net +5V".to_string(), "CON002"),
+                ("net BUS".to_string(), "CON002"),
+                ("net BUS".to_string(), "CON003"),
+                ("net NC".to_string(), "CON003"),
+            ]
+        );
+    }
+}
@@ -1,9 +1,15 @@
 use crate::Pcba;
+use crate::config::{PartClass, ProjectConfig};
+use crate::power::rail_name::parse_rail_voltage;
 use ecad_file_format::netlist::{Netlist, PinType};
 use ecad_file_format::{Designator, NetName};
-use regex::Regex;
 use std::collections::{HashMap, HashSet};
-use std::fmt::{Debug, Formatter};
+use std::fmt::{Debug, Display, Formatter};
+
+pub mod feedback;
+pub mod propagation;
+pub mod rail_name;
+pub mod tree;
 
 #[derive(Debug)]
 pub struct Power {
@@ -13,61 +19,65 @@ pub struct Power {
 
 pub struct PowerRail {
     pub voltage: Option<Volt>,
+    /// How the voltage was found, None if it is not known
+    pub source: Option<VoltageSource>,
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Volt(pub f32);
 
-/// If strict is true, then only power nets containing +xVy will be picked up
-pub fn derive_power_structure(netlist: &Netlist, strict: bool) -> Power {
+#[derive(Debug, Clone, PartialEq)]
+pub enum VoltageSource {
+    /// Parsed from the net name, see [rail_name]
+    Name,
+    /// Same voltage as another rail, connected through a ferrite, tie, inductor or load switch
+    Bridge { from: NetName, through: Designator },
+    /// Output of a fixed voltage regulator, from its value
+    Regulator(Designator),
+    /// Output of an adjustable regulator, from its feedback divider, see [feedback]
+    Feedback(Designator),
+}
+
+/// Power rails are nets with a voltage in their name (see [rail_name]), nets matching power
+/// patterns and nets with power pins on them
+pub fn derive_power_structure(netlist: &Netlist, config: &ProjectConfig) -> Power {
     // 0 tie, current sense tie, pwr switch IC, pwr FET to other power nets
     // sources: LDOs, DC-DCs, ICs, connectors
 
     let mut power_rails = HashMap::new();
 
-    let re_mvn = if strict {
-        Regex::new(".*\\+(\\d+)V(\\d+).*")
-    } else {
-        Regex::new(".*(\\d+)V(\\d+).*")
-    }
-    .unwrap();
-    for name in netlist.nets.keys() {
-        if let Some(c) = re_mvn.captures(name.0.as_str()) {
-            let integer: u32 = c.get(1).unwrap().as_str().parse().unwrap();
-            let fractional_str = c.get(2).unwrap().as_str();
-            let fractional: u32 = fractional_str.parse().unwrap();
-            let voltage =
-                integer as f32 + (fractional as f32 / (fractional_str.len() as f32 * 10.0));
+    for name in netlist.nets().keys() {
+        if config.nets.is_ground(name) {
+            continue;
+        }
+        if let Some(voltage) = parse_rail_voltage(&name.0, &config.nets) {
             power_rails.insert(
                 name.clone(),
                 PowerRail {
-                    voltage: Some(Volt(voltage)),
+                    voltage: Some(voltage),
+                    source: Some(VoltageSource::Name),
                 },
             );
             continue;
         }
 
-        if name.0.contains("+V") {
-            if !power_rails.contains_key(name) {
-                power_rails.insert(name.clone(), PowerRail { voltage: None });
-            }
-            continue;
-        }
-
-        if name.0.contains("VDD") || name.0.contains("VCC") {
-            if !power_rails.contains_key(name) {
-                power_rails.insert(name.clone(), PowerRail { voltage: None });
-            }
+        if config.nets.is_power(name) && !power_rails.contains_key(name) {
+            power_rails.insert(
+                name.clone(),
+                PowerRail {
+                    voltage: None,
+                    source: None,
+                },
+            );
         }
     }
 
-    let mut ground_nets = HashSet::new();
-    for name in netlist.nets.keys() {
-        let n = name.0.as_str();
-        if n.contains("GND") || n.contains("VSS") || n.contains("VEE") || n.starts_with("ISO") {
-            ground_nets.insert(name.clone());
-        }
-    }
+    let ground_nets: HashSet<NetName> = netlist
+        .nets()
+        .keys()
+        .filter(|name| config.nets.is_ground(name))
+        .cloned()
+        .collect();
 
     let nets_with_power_pins = netlist.find_nets_with_pin_types(&[
         PinType::PowerIn,
@@ -78,7 +88,13 @@ pub fn derive_power_structure(netlist: &Netlist, strict: bool) -> Power {
     for net in nets_with_power_pins {
         // do not replace nets with voltage in their name, also ignore ground nets
         if !power_rails.contains_key(&net) && !ground_nets.contains(&net) {
-            power_rails.insert(net, PowerRail { voltage: None });
+            power_rails.insert(
+                net,
+                PowerRail {
+                    voltage: None,
+                    source: None,
+                },
+            );
         }
     }
 
@@ -90,7 +106,13 @@ pub fn derive_power_structure(netlist: &Netlist, strict: bool) -> Power {
 
 pub fn find_switching_nodes(pcba: &Pcba) -> HashSet<NetName> {
     let mut switching_nodes = HashSet::new();
-    let chains = pcba.find_part_chains(&[Designator::is_ic, Designator::is_inductor][..], false);
+    let classes = &pcba.config.designators;
+    let is_ic = |d: &Designator| classes.is(d, PartClass::Ic);
+    let is_inductor = |d: &Designator| classes.is(d, PartClass::Inductor);
+    let chains = pcba.find_part_chains(
+        &[&is_ic as &dyn Fn(&Designator) -> bool, &is_inductor][..],
+        false,
+    );
     for chain in &chains {
         // nets will contain power and ground rails if inductor is connected to them and to an IC, but power rails
         // will also contain LX/SW nets that were picked up through pin type == power if it was set.
@@ -134,6 +156,19 @@ impl Power {
     }
 }
 
+impl Display for VoltageSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VoltageSource::Name => write!(f, "its name"),
+            VoltageSource::Bridge { from, through } => {
+                write!(f, "{} through {}", from.0, through.0)
+            }
+            VoltageSource::Regulator(regulator) => write!(f, "{} output", regulator.0),
+            VoltageSource::Feedback(regulator) => write!(f, "{} feedback divider", regulator.0),
+        }
+    }
+}
+
 impl Debug for PowerRail {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         if let Some(voltage) = self.voltage {
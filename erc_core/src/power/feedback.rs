@@ -0,0 +1,260 @@
+//! Output voltage of adjustable regulators from the resistor divider on their feedback pin.
+//!
+//! The top resistor goes from the output rail to the feedback net, the bottom one from the
+//! feedback net to ground, resistors in parallel are combined. The reference voltage comes from
+//! one of `power.reference_fields` of the regulator, or from `power.reference_voltages` by value.
+
+use crate::config::{PartClass, ProjectConfig};
+use crate::power::rail_name::parse_voltage_token;
+use crate::power::tree::{PowerNodeKind, PowerTree};
+use crate::power::{Power, Volt};
+use ecad_file_format::netlist::Netlist;
+use ecad_file_format::passive_value::Ohm;
+use ecad_file_format::{Designator, NetName};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeedbackDivider {
+    pub regulator: Designator,
+    /// Output rail the divider is connected to
+    pub rail: NetName,
+    pub feedback_net: NetName,
+    /// True for `ADJ` pins, which regulate the voltage between the output and the pin
+    pub output_referenced: bool,
+    /// Resistors between the output rail and the feedback net
+    pub top: Vec<Designator>,
+    /// Resistors between the feedback net and ground
+    pub bottom: Vec<Designator>,
+    pub top_resistance: Ohm,
+    pub bottom_resistance: Ohm,
+    /// None if the regulator has no reference voltage field and is not in the part database
+    pub reference: Option<Volt>,
+}
+
+impl FeedbackDivider {
+    /// Expected output voltage, rounded to millivolts
+    pub fn output_voltage(&self) -> Option<Volt> {
+        let reference = self.reference?.0;
+        let ratio = if self.output_referenced {
+            self.bottom_resistance.0 / self.top_resistance.0
+        } else {
+            self.top_resistance.0 / self.bottom_resistance.0
+        };
+        let voltage = reference * (1.0 + ratio);
+        Some(Volt((voltage * 1000.0).round() / 1000.0))
+    }
+}
+
+/// Looks at every regulator and DC-DC converter in the power tree that has a feedback pin with
+/// resistors to one of its output rails and to ground
+pub fn find_feedback_dividers(
+    netlist: &Netlist,
+    config: &ProjectConfig,
+    power: &Power,
+    tree: &PowerTree,
+) -> Vec<FeedbackDivider> {
+    let mut dividers = vec![];
+    let regulators = tree.nodes.iter().filter(|node| {
+        matches!(
+            node.kind,
+            PowerNodeKind::LinearRegulator | PowerNodeKind::DcDcConverter
+        )
+    });
+    for node in regulators {
+        for (pin_id, feedback_net) in netlist.index().part_nodes(&node.designator) {
+            let Some(pin) = netlist.pin(&node.designator, pin_id) else {
+                continue;
+            };
+            let pin_name = pin.name.0.as_str();
+            if !config
+                .power
+                .feedback_pins
+                .iter()
+                .any(|re| re.is_match(pin_name))
+            {
+                continue;
+            }
+            let divider = node.outputs.iter().find_map(|rail| {
+                find_divider(netlist, config, power, &node.designator, rail, feedback_net)
+            });
+            if let Some(mut divider) = divider {
+                divider.output_referenced = pin_name.eq_ignore_ascii_case("ADJ");
+                dividers.push(divider);
+            }
+        }
+    }
+    dividers.sort_by(|a, b| a.regulator.0.cmp(&b.regulator.0));
+    dividers
+}
+
+fn find_divider(
+    netlist: &Netlist,
+    config: &ProjectConfig,
+    power: &Power,
+    regulator: &Designator,
+    rail: &NetName,
+    feedback_net: &NetName,
+) -> Option<FeedbackDivider> {
+    let is_resistor = |d: &Designator| config.designators.is(d, PartClass::Resistor);
+    let mut top = netlist
+        .find_net_chains(rail, &[is_resistor], feedback_net)
+        .into_iter()
+        .map(|chain| chain[0].1.clone())
+        .collect::<Vec<_>>();
+    let mut bottom = power
+        .ground_nets
+        .iter()
+        .flat_map(|ground| netlist.find_net_chains(feedback_net, &[is_resistor], ground))
+        .map(|chain| chain[0].1.clone())
+        .collect::<Vec<_>>();
+    if top.is_empty() || bottom.is_empty() {
+        return None;
+    }
+    top.sort_by(|a, b| a.0.cmp(&b.0));
+    bottom.sort_by(|a, b| a.0.cmp(&b.0));
+    Some(FeedbackDivider {
+        regulator: regulator.clone(),
+        rail: rail.clone(),
+        feedback_net: feedback_net.clone(),
+        output_referenced: false,
+        top_resistance: parallel_resistance(netlist, &top)?,
+        bottom_resistance: parallel_resistance(netlist, &bottom)?,
+        top,
+        bottom,
+        reference: reference_voltage(netlist, config, regulator),
+    })
+}
+
+fn parallel_resistance(netlist: &Netlist, resistors: &[Designator]) -> Option<Ohm> {
+    let mut conductance = 0.0;
+    for resistor in resistors {
+        let ohms = netlist.resistance(resistor).ok()?;
+        if ohms.0 <= 0.0 {
+            return None;
+        }
+        conductance += 1.0 / ohms.0;
+    }
+    Some(Ohm(1.0 / conductance))
+}
+
+/// From a component field, e.g. "0.8V" or "0.8", otherwise from the value's longest known prefix
+fn reference_voltage(
+    netlist: &Netlist,
+    config: &ProjectConfig,
+    regulator: &Designator,
+) -> Option<Volt> {
+    let component = netlist.components.get(regulator)?;
+    for field in &config.power.reference_fields {
+        let Some((_, value)) = component
+            .fields
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(field))
+        else {
+            continue;
+        };
+        let value = value.trim().replace(' ', "").to_uppercase();
+        if let Some(voltage) = value.parse().ok().map(Volt) {
+            return Some(voltage);
+        }
+        if let Some(voltage) = parse_voltage_token(&value) {
+            return Some(voltage);
+        }
+    }
+    let value = component.value.to_uppercase();
+    config
+        .power
+        .reference_voltages
+        .iter()
+        .filter(|(prefix, _)| value.starts_with(&prefix.to_uppercase()))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, voltage)| Volt(*voltage))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Pcba;
+    use crate::power::{Volt, VoltageSource};
+    use ecad_file_format::netlist::{Netlist, PinType};
+    use ecad_file_format::test_util::{NetlistBuilder, component};
+    use ecad_file_format::{Designator, NetName};
+
+    fn netlist() -> Netlist {
+        let mut u1 = component("TLV75901", "LDO_FB");
+        u1.fields.insert("VREF".to_string(), "0.55 V".to_string());
+        NetlistBuilder::new()
+            .lib_part(
+                "LDO_FB",
+                &[
+                    ("VIN", PinType::PowerIn),
+                    ("VOUT", PinType::PowerOut),
+                    ("FB", PinType::AnalogInput),
+                ],
+            )
+            .lib_part(
+                "LDO_ADJ",
+                &[
+                    ("VIN", PinType::PowerIn),
+                    ("VOUT", PinType::PowerOut),
+                    ("ADJ", PinType::AnalogInput),
+                ],
+            )
+            .part("R1", "100k", "R")
+            .part("R2", "22k", "R")
+            .part("R3", "240", "R")
+            .part("R4", "390", "R")
+            .part("R5", "100k", "R")
+            .part("R6", "10k", "R")
+            .part("R7", "10k", "R")
+            .component("U1", u1)
+            .part("U2", "LM317", "LDO_ADJ")
+            .part("U3", "TPS7A2001", "LDO_FB")
+            .net("+12V", &[("U1", "1"), ("U2", "1"), ("U3", "1")])
+            .net("+3V3", &[("U1", "2"), ("R1", "1")])
+            .net("FB1", &[("U1", "3"), ("R1", "2"), ("R2", "1")])
+            .net("VDD_ADJ", &[("U2", "2"), ("R3", "1")])
+            .net("ADJ2", &[("U2", "3"), ("R3", "2"), ("R4", "1")])
+            .net("VDD_X", &[("U3", "2"), ("R5", "1")])
+            .net("FB3", &[("U3", "3"), ("R5", "2"), ("R6", "1"), ("R7", "1")])
+            .net("GND", &[("R2", "2"), ("R4", "2"), ("R6", "2"), ("R7", "2")])
+            .build()
+    }
+
+    #[test]
+    fn output_voltage_is_found_from_feedback_divider() {
+        let pcba = Pcba::new(netlist());
+        let dividers = pcba
+            .feedback_dividers
+            .iter()
+            .map(|d| {
+                let top = d.top.iter().map(|r| r.0.as_str()).collect::<Vec<_>>();
+                let bottom = d.bottom.iter().map(|r| r.0.as_str()).collect::<Vec<_>>();
+                (d.regulator.0.as_str(), top, bottom, d.output_voltage())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            dividers,
+            [
+                ("U1", vec!["R1"], vec!["R2"], Some(Volt(3.05))),
+                ("U2", vec!["R3"], vec!["R4"], Some(Volt(3.281))),
+                ("U3", vec!["R5"], vec!["R6", "R7"], None),
+            ]
+        );
+
+        let rail = &pcba.power.power_rails[&NetName("VDD_ADJ".into())];
+        assert_eq!(rail.voltage, Some(Volt(3.281)));
+        assert_eq!(
+            rail.source,
+            Some(VoltageSource::Feedback(Designator("U2".into())))
+        );
+        let conflicts = pcba
+            .diagnostics
+            .with_code("PWR001")
+            .map(|d| d.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            conflicts,
+            [
+                "warning PWR001: rail is 3.3 V from its name, but 3.05 V from U1 feedback divider at rail +3V3"
+            ]
+        );
+    }
+}
@@ -0,0 +1,361 @@
+//! Voltages of rails that do not have one in their name, e.g. `VDDA_MCU` behind a ferrite bead
+//! from `+3V3`, or the output of a load switch.
+
+use crate::config::{PartClass, ProjectConfig};
+use crate::diagnostics::{Diagnostic, Diagnostics, Location};
+use crate::power::feedback::FeedbackDivider;
+use crate::power::tree::{PowerNodeKind, PowerTree};
+use crate::power::{Power, Volt, VoltageSource};
+use crate::rule::{Rule, RuleCategory};
+use crate::{Pcba, Severity};
+use ecad_file_format::netlist::Netlist;
+use ecad_file_format::{Designator, NetName};
+use regex::Regex;
+use std::collections::{HashSet, VecDeque};
+use std::sync::OnceLock;
+
+/// Relative difference of voltages that are still the same rail, e.g. after a current sense shunt
+const VOLTAGE_TOLERANCE: f32 = 0.02;
+
+/// Rail that got a different voltage from another source than the one it already had
+#[derive(Debug, Clone, PartialEq)]
+pub struct VoltageConflict {
+    pub rail: NetName,
+    pub voltage: Volt,
+    pub source: VoltageSource,
+    /// Voltage that was not applied
+    pub other_voltage: Volt,
+    pub other_source: VoltageSource,
+}
+
+/// Reports rails that are connected to rails with a different voltage.
+///
+/// Voltages are propagated in `Pcba::new` whether the rule is enabled or not, the rule only
+/// reports `Pcba::voltage_conflicts`.
+pub struct RailVoltageRule;
+
+impl Rule for RailVoltageRule {
+    fn id(&self) -> &'static str {
+        "power.rail_voltage"
+    }
+
+    fn category(&self) -> RuleCategory {
+        RuleCategory::Power
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn run(&self, pcba: &Pcba, diagnostics: &mut Diagnostics) {
+        let severity = self.default_severity();
+        diagnostics.extend(
+            pcba.voltage_conflicts
+                .iter()
+                .cloned()
+                .map(|conflict| conflict.to_diagnostic(severity)),
+        );
+    }
+}
+
+/// Part that carries the voltage of one rail to another one
+struct Bridge {
+    from: NetName,
+    to: NetName,
+    through: Designator,
+}
+
+/// Carries known voltages to rails without one, breadth first, so that the closest source wins.
+///
+/// Ferrites, inductors and resistors up to `thresholds.max_tie_resistance` work in both
+/// directions, load switches from their input to their output rails in the power tree. Fixed
+/// voltage regulators and DC-DC converters set their output rails from their value, e.g.
+/// "AMS1117-3.3", "TPS62162-3.3" or "LM7805", adjustable ones from their feedback divider.
+pub fn propagate_voltages(
+    netlist: &Netlist,
+    config: &ProjectConfig,
+    power: &mut Power,
+    tree: &PowerTree,
+    dividers: &[FeedbackDivider],
+) -> Vec<VoltageConflict> {
+    let mut bridges = find_ties(netlist, config, power);
+    let mut regulators = vec![];
+    for node in &tree.nodes {
+        match node.kind {
+            PowerNodeKind::LoadSwitch => {
+                for from in &node.inputs {
+                    for to in &node.outputs {
+                        bridges.push(Bridge {
+                            from: from.clone(),
+                            to: to.clone(),
+                            through: node.designator.clone(),
+                        });
+                    }
+                }
+            }
+            PowerNodeKind::LinearRegulator | PowerNodeKind::DcDcConverter => {
+                if let Some(voltage) = fixed_output_voltage(netlist, &node.designator) {
+                    for to in &node.outputs {
+                        regulators.push((to.clone(), node.designator.clone(), voltage));
+                    }
+                }
+            }
+            PowerNodeKind::Connector | PowerNodeKind::Battery => {}
+        }
+    }
+    let mut conflicts = vec![];
+    let mut queue = power
+        .power_rails
+        .iter()
+        .filter(|(_, rail)| rail.voltage.is_some())
+        .map(|(net_name, _)| net_name.clone())
+        .collect::<Vec<_>>();
+    queue.sort_by(|a, b| a.0.cmp(&b.0));
+    let mut queue = VecDeque::from(queue);
+
+    for (rail, regulator, voltage) in regulators {
+        let source = VoltageSource::Regulator(regulator);
+        assign(power, &rail, voltage, source, &mut conflicts, &mut queue);
+    }
+    for divider in dividers {
+        let Some(voltage) = divider.output_voltage() else {
+            continue;
+        };
+        let source = VoltageSource::Feedback(divider.regulator.clone());
+        assign(
+            power,
+            &divider.rail,
+            voltage,
+            source,
+            &mut conflicts,
+            &mut queue,
+        );
+    }
+    while let Some(from) = queue.pop_front() {
+        let Some(voltage) = power.power_rails.get(&from).and_then(|rail| rail.voltage) else {
+            continue;
+        };
+        for bridge in bridges.iter().filter(|bridge| bridge.from == from) {
+            let source = VoltageSource::Bridge {
+                from: from.clone(),
+                through: bridge.through.clone(),
+            };
+            assign(
+                power,
+                &bridge.to,
+                voltage,
+                source,
+                &mut conflicts,
+                &mut queue,
+            );
+        }
+    }
+
+    // the same bridge between two rails with different voltages is found from both sides
+    let mut seen = HashSet::new();
+    conflicts.retain(|conflict| {
+        let key = match &conflict.other_source {
+            VoltageSource::Bridge { from, through } => {
+                let mut rails = [from.0.clone(), conflict.rail.0.clone()];
+                rails.sort();
+                (rails, through.0.clone())
+            }
+            VoltageSource::Regulator(regulator) | VoltageSource::Feedback(regulator) => (
+                [conflict.rail.0.clone(), String::new()],
+                regulator.0.clone(),
+            ),
+            VoltageSource::Name => ([conflict.rail.0.clone(), String::new()], String::new()),
+        };
+        seen.insert(key)
+    });
+    conflicts.sort_by(|a, b| a.rail.0.cmp(&b.rail.0));
+    conflicts
+}
+
+fn assign(
+    power: &mut Power,
+    net_name: &NetName,
+    voltage: Volt,
+    source: VoltageSource,
+    conflicts: &mut Vec<VoltageConflict>,
+    queue: &mut VecDeque<NetName>,
+) {
+    let Some(rail) = power.power_rails.get_mut(net_name) else {
+        return;
+    };
+    match (rail.voltage, &rail.source) {
+        (None, _) => {
+            rail.voltage = Some(voltage);
+            rail.source = Some(source);
+            queue.push_back(net_name.clone());
+        }
+        (Some(existing), Some(existing_source)) if !is_same_voltage(existing, voltage) => {
+            conflicts.push(VoltageConflict {
+                rail: net_name.clone(),
+                voltage: existing,
+                source: existing_source.clone(),
+                other_voltage: voltage,
+                other_source: source,
+            });
+        }
+        _ => {}
+    }
+}
+
+fn is_same_voltage(a: Volt, b: Volt) -> bool {
+    (a.0 - b.0).abs() <= VOLTAGE_TOLERANCE * a.0.abs().max(b.0.abs())
+}
+
+/// Ferrites, inductors and low value resistors between two rails, in both directions
+fn find_ties(netlist: &Netlist, config: &ProjectConfig, power: &Power) -> Vec<Bridge> {
+    let mut bridges = vec![];
+    let mut designators = netlist.components.keys().collect::<Vec<_>>();
+    designators.sort_by(|a, b| a.0.cmp(&b.0));
+    for designator in designators {
+        let is_tie = match config.designators.class_of(designator) {
+            Some(PartClass::Ferrite | PartClass::Inductor) => true,
+            Some(PartClass::Resistor) => netlist
+                .resistance(designator)
+                .is_ok_and(|ohms| ohms.0 <= config.thresholds.max_tie_resistance.0),
+            _ => false,
+        };
+        if !is_tie {
+            continue;
+        }
+        let nets = netlist.part_nets(designator);
+        if nets.len() != 2 || !nets.iter().all(|net| power.power_rails.contains_key(net)) {
+            continue;
+        }
+        let mut nets = nets.into_iter().collect::<Vec<_>>();
+        nets.sort_by(|a, b| a.0.cmp(&b.0));
+        for (from, to) in [(&nets[0], &nets[1]), (&nets[1], &nets[0])] {
+            bridges.push(Bridge {
+                from: from.clone(),
+                to: to.clone(),
+                through: designator.clone(),
+            });
+        }
+    }
+    bridges
+}
+
+/// Output voltage from a fixed regulator's value, e.g. "AMS1117-3.3", "AP2112K-1V8" or "LM7805"
+fn fixed_output_voltage(netlist: &Netlist, designator: &Designator) -> Option<Volt> {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    let re = RE.get_or_init(|| {
+        Regex::new(r"(?i)-(\d{1,2})[.V](\d{1,2})|^(?:LM|L|MC|UA)?78[LM]?(\d{2})").unwrap()
+    });
+    let value = &netlist.components.get(designator)?.value;
+    let c = re.captures(value)?;
+    if let (Some(integer), Some(fraction)) = (c.get(1), c.get(2)) {
+        let voltage = format!("{}.{}", integer.as_str(), fraction.as_str());
+        voltage.parse().ok().map(Volt)
+    } else {
+        c.get(3)?.as_str().parse().ok().map(Volt)
+    }
+}
+
+impl VoltageConflict {
+    pub fn to_diagnostic(self, severity: Severity) -> Diagnostic {
+        let message = format!(
+            "rail is {} V from {}, but {} V from {}",
+            self.voltage.0, self.source, self.other_voltage.0, self.other_source
+        );
+        let mut diagnostic =
+            Diagnostic::new("PWR001", severity, message).at(Location::Rail(self.rail));
+        match self.other_source {
+            VoltageSource::Bridge { from, through } => {
+                diagnostic = diagnostic
+                    .related_to(Location::Part(through))
+                    .related_to(Location::Rail(from));
+            }
+            VoltageSource::Regulator(regulator) | VoltageSource::Feedback(regulator) => {
+                diagnostic = diagnostic.related_to(Location::Part(regulator));
+            }
+            VoltageSource::Name => {}
+        }
+        diagnostic
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Pcba;
+    use crate::power::VoltageSource;
+    use ecad_file_format::netlist::{Netlist, PinType};
+    use ecad_file_format::test_util::NetlistBuilder;
+    use ecad_file_format::{Designator, NetName};
+
+    fn netlist() -> Netlist {
+        NetlistBuilder::new()
+            .lib_part(
+                "REG",
+                &[("VIN", PinType::PowerIn), ("VOUT", PinType::PowerOut)],
+            )
+            .lib_part(
+                "BUCK",
+                &[("VIN", PinType::PowerIn), ("SW", PinType::Passive)],
+            )
+            .part("FB1", "", "Passive")
+            .part("FB2", "", "Passive")
+            .part("L1", "2u2", "Passive")
+            .part("R1", "0R", "Passive")
+            .part("R2", "10k", "Passive")
+            .part("U1", "TPS22918", "REG")
+            .part("U2", "AMS1117-3.3", "REG")
+            .part("U3", "TPS62162-3.3", "BUCK")
+            .net("+3V3", &[("FB1", "1"), ("R2", "1"), ("FB2", "1")])
+            .net("VDDA_MCU", &[("FB1", "2"), ("R1", "1")])
+            .net("AVDD_REF", &[("R1", "2")])
+            .net("VDD_X", &[("R2", "2")])
+            .net("+1V8", &[("FB2", "2")])
+            .net("+5V", &[("U1", "1"), ("U2", "1"), ("U3", "1")])
+            .net("VCC_SWITCHED", &[("U1", "2")])
+            .net("VDD_LDO", &[("U2", "2")])
+            .net("SW", &[("U3", "2"), ("L1", "1")])
+            .net("VDD_BUCK", &[("L1", "2")])
+            .build()
+    }
+
+    #[test]
+    fn voltages_are_propagated() {
+        let pcba = Pcba::new(netlist());
+        let rail = |name: &str| &pcba.power.power_rails[&NetName(name.into())];
+        let voltage = |name: &str| rail(name).voltage.map(|v| v.0);
+
+        assert_eq!(voltage("VDDA_MCU"), Some(3.3));
+        assert_eq!(
+            rail("VDDA_MCU").source,
+            Some(VoltageSource::Bridge {
+                from: NetName("+3V3".into()),
+                through: Designator("FB1".into())
+            })
+        );
+        assert_eq!(voltage("AVDD_REF"), Some(3.3));
+        assert_eq!(voltage("VDD_X"), None);
+        assert_eq!(voltage("VCC_SWITCHED"), Some(5.0));
+        assert_eq!(voltage("VDD_LDO"), Some(3.3));
+        assert_eq!(
+            rail("VDD_LDO").source,
+            Some(VoltageSource::Regulator(Designator("U2".into())))
+        );
+        assert_eq!(voltage("VDD_BUCK"), Some(3.3));
+        assert_eq!(
+            rail("VDD_BUCK").source,
+            Some(VoltageSource::Regulator(Designator("U3".into())))
+        );
+        assert_eq!(voltage("+1V8"), Some(1.8));
+
+        let conflicts = pcba
+            .diagnostics
+            .with_code("PWR001")
+            .map(|d| d.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            conflicts,
+            [
+                "warning PWR001: rail is 3.3 V from its name, but 1.8 V from +1V8 through FB2 at rail +3V3"
+            ]
+        );
+    }
+}
@@ -0,0 +1,164 @@
+//! Voltage of a power rail from its net name.
+//!
+//! A name is split into tokens by `_` and spaces, after dropping the hierarchical sheet path.
+//! A voltage token is an optional sign (`+`, `-`, or `P` / `N` / `V` followed by a digit) and one of:
+//! * `3V3`, `1V05`, `12V0` - `V` as the decimal point
+//! * `3.3V`, `3,3V`, `3P3V` - decimal point and a trailing `V`
+//! * `5V`, `12V`
+//! * `900MV` - millivolts
+//!
+//! followed by up to 4 letters, e.g. `+5VA` or `5VSB`. Names like `VBUS` get their voltage from
+//! `nets.rail_voltages`. Any token from `nets.signal_words` makes the net a signal, e.g. `EN_3V3`.
+//!
+//! With `nets.strict_voltage_names`, tokens before the voltage must look like a rail themselves
+//! (start with `V` or match one of the `nets.power` patterns), so that `VCC_3V3` is a rail and
+//! `UART_TX_3V3` is not.
+
+use crate::config::NetPatterns;
+use crate::power::Volt;
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// Returns voltage of a power rail, or None if the name does not contain one
+pub fn parse_rail_voltage(name: &str, nets: &NetPatterns) -> Option<Volt> {
+    let name = name.rsplit('/').next().unwrap_or(name).to_uppercase();
+    if nets.has_signal_word(&name) {
+        return None;
+    }
+    let tokens = name
+        .split(['_', ' '])
+        .filter(|token| !token.is_empty())
+        .collect::<Vec<_>>();
+    for (i, token) in tokens.iter().enumerate() {
+        if let Some((_, voltage)) = nets
+            .rail_voltages
+            .iter()
+            .find(|(known, _)| known.eq_ignore_ascii_case(token))
+        {
+            return Some(Volt(*voltage));
+        }
+        let Some(voltage) = parse_voltage_token(token) else {
+            continue;
+        };
+        let after_rail_tokens = tokens[..i].iter().all(|before| {
+            before.starts_with('V') || nets.power.iter().any(|re| re.is_match(before))
+        });
+        if !nets.strict_voltage_names || after_rail_tokens {
+            return Some(voltage);
+        }
+    }
+    None
+}
+
+/// Parses one upper case token, e.g. "+3V3", "1V05", "3.3V" or "900MV"
+pub fn parse_voltage_token(token: &str) -> Option<Volt> {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    let re = RE.get_or_init(|| {
+        Regex::new(r"^(?:(\d+)V(\d+)|(\d+)[.,P](\d+)V|(\d+)V|(\d+)MV)[A-Z]{0,4}$").unwrap()
+    });
+    let (sign, value) = match token.as_bytes() {
+        [b'+', ..] => (1.0, &token[1..]),
+        [b'-', ..] => (-1.0, &token[1..]),
+        [b'P' | b'V', digit, ..] if digit.is_ascii_digit() => (1.0, &token[1..]),
+        [b'N', digit, ..] if digit.is_ascii_digit() => (-1.0, &token[1..]),
+        _ => (1.0, token),
+    };
+    let c = re.captures(value)?;
+    let voltage: f32 = if let (Some(integer), Some(fraction)) = (c.get(1), c.get(2)) {
+        format!("{}.{}", integer.as_str(), fraction.as_str())
+            .parse()
+            .ok()?
+    } else if let (Some(integer), Some(fraction)) = (c.get(3), c.get(4)) {
+        format!("{}.{}", integer.as_str(), fraction.as_str())
+            .parse()
+            .ok()?
+    } else if let Some(integer) = c.get(5) {
+        integer.as_str().parse().ok()?
+    } else {
+        c.get(6)?.as_str().parse::<f32>().ok()? / 1000.0
+    };
+    Some(Volt(sign * voltage))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_rail_voltage, parse_voltage_token};
+    use crate::config::{NetPatterns, ProjectConfig};
+
+    fn assert_parsed(nets: &NetPatterns, cases: &[(&str, Option<f32>)]) {
+        for (name, expected) in cases {
+            let parsed = parse_rail_voltage(name, nets).map(|v| v.0);
+            match (parsed, expected) {
+                (Some(parsed), Some(expected)) => {
+                    assert!((parsed - expected).abs() < 1e-4, "{name}: {parsed}")
+                }
+                (None, None) => {}
+                _ => panic!("{name}: expected {expected:?}, got {parsed:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn rail_names_are_parsed() {
+        let nets = NetPatterns::default();
+        assert_parsed(
+            &nets,
+            &[
+                ("+3V3", Some(3.3)),
+                ("+1V05", Some(1.05)),
+                ("+1V8", Some(1.8)),
+                ("+12V0", Some(12.0)),
+                ("+0V9", Some(0.9)),
+                ("3.3V", Some(3.3)),
+                ("3,3V", Some(3.3)),
+                ("3P3V", Some(3.3)),
+                ("5V", Some(5.0)),
+                ("-12V", Some(-12.0)),
+                ("+5VA", Some(5.0)),
+                ("5VSB", Some(5.0)),
+                ("P3V3", Some(3.3)),
+                ("N5V", Some(-5.0)),
+                ("V3V3", Some(3.3)),
+                ("VCC_3V3", Some(3.3)),
+                ("AVDD_1V8", Some(1.8)),
+                ("VDD_900MV", Some(0.9)),
+                ("1V8_IO", Some(1.8)),
+                ("3v3_aux", Some(3.3)),
+                ("/Power/+3V3", Some(3.3)),
+                ("VBUS", Some(5.0)),
+                ("USB_VBUS", Some(5.0)),
+                ("VBAT", None),
+                ("GND", None),
+                ("I2C_SCL", None),
+                ("UART_TX_3V3", None),
+                ("EN_3V3", None),
+                ("3V3_PG", None),
+                ("VBUS_DET", None),
+                ("PA5", None),
+                ("R5V", None),
+                ("CH1V", None),
+            ],
+        );
+    }
+
+    #[test]
+    fn rail_names_are_configurable() {
+        let config = ProjectConfig::from_toml(
+            "[nets]\nstrict_voltage_names = false\nsignal_words = [\"MON\"]\n\
+             [nets.rail_voltages]\nVSYS = 3.7",
+        )
+        .unwrap();
+        assert_parsed(
+            &config.nets,
+            &[
+                ("UART_TX_3V3", Some(3.3)),
+                ("EN_3V3", Some(3.3)),
+                ("VSYS", Some(3.7)),
+                ("VBUS", None),
+                ("VSYS_MON", None),
+            ],
+        );
+        assert!(parse_voltage_token("3V3X").is_some());
+        assert!(parse_voltage_token("3V3XYZAB").is_none());
+    }
+}
@@ -0,0 +1,369 @@
+//! Directed power tree: sources and converters between rails, and the ICs each rail supplies.
+//!
+//! Batteries and input connectors are sources. ICs with `PowerIn` pins on one rail and
+//! `PowerOut` pins on another are linear regulators, or load switches if they match
+//! `power.load_switch`. ICs on a switching node are DC-DC converters, their output is the rail on
+//! the other side of the inductor. Ferrites and ties between rails are not part of the tree.
+
+use crate::config::{PartClass, ProjectConfig};
+use crate::power::Power;
+use ecad_file_format::netlist::{Netlist, PinType};
+use ecad_file_format::{Designator, NetName};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Default)]
+pub struct PowerTree {
+    /// Sources and converters, sorted by designator
+    pub nodes: Vec<PowerNode>,
+    pub rails: HashMap<NetName, RailBranch>,
+}
+
+/// Part that puts power on one or more rails
+#[derive(Debug, Clone, PartialEq)]
+pub struct PowerNode {
+    pub designator: Designator,
+    pub kind: PowerNodeKind,
+    /// Rails the node takes its power from, empty for sources
+    pub inputs: Vec<NetName>,
+    pub outputs: Vec<NetName>,
+    /// Nets between a DC-DC converter and its inductors
+    pub switching_nodes: Vec<NetName>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PowerNodeKind {
+    Connector,
+    Battery,
+    LinearRegulator,
+    DcDcConverter,
+    LoadSwitch,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RailBranch {
+    /// Sources and converters with an output on the rail
+    pub fed_by: Vec<Designator>,
+    /// ICs supplied by the rail, including converters and load switches taking their input from it
+    pub loads: Vec<Designator>,
+}
+
+impl PowerNodeKind {
+    pub fn is_source(&self) -> bool {
+        matches!(self, PowerNodeKind::Connector | PowerNodeKind::Battery)
+    }
+}
+
+impl PowerTree {
+    pub fn node(&self, designator: &Designator) -> Option<&PowerNode> {
+        self.nodes
+            .iter()
+            .find(|node| &node.designator == designator)
+    }
+
+    /// Sources and converters that feed the rail directly
+    pub fn fed_by(&self, rail: &NetName) -> impl Iterator<Item = &PowerNode> {
+        self.rails
+            .get(rail)
+            .map(|branch| branch.fed_by.as_slice())
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|designator| self.node(designator))
+    }
+
+    /// ICs that hang off the rail
+    pub fn loads(&self, rail: &NetName) -> &[Designator] {
+        self.rails
+            .get(rail)
+            .map(|branch| branch.loads.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Sources the rail is powered from, following converters upstream, sorted by designator
+    pub fn sources_of(&self, rail: &NetName) -> Vec<&PowerNode> {
+        let mut sources = vec![];
+        let mut visited = HashSet::new();
+        let mut stack = vec![rail];
+        while let Some(rail) = stack.pop() {
+            if !visited.insert(rail) {
+                continue;
+            }
+            for node in self.fed_by(rail) {
+                if node.kind.is_source() {
+                    if !sources.contains(&node) {
+                        sources.push(node);
+                    }
+                } else {
+                    stack.extend(node.inputs.iter());
+                }
+            }
+        }
+        sources.sort_by(|a, b| a.designator.0.cmp(&b.designator.0));
+        sources
+    }
+}
+
+/// Connectors are only sources for rails that no battery or converter feeds, otherwise they take
+/// power off the board.
+pub fn build_power_tree(
+    netlist: &Netlist,
+    config: &ProjectConfig,
+    power: &Power,
+    switching_nodes: &HashSet<NetName>,
+) -> PowerTree {
+    let mut designators = netlist.components.keys().collect::<Vec<_>>();
+    designators.sort_by(|a, b| a.0.cmp(&b.0));
+    let rails_of = |designator: &Designator| {
+        let mut rails = netlist
+            .part_nets(designator)
+            .into_iter()
+            .filter(|net| power.power_rails.contains_key(net))
+            .collect::<Vec<_>>();
+        rails.sort_by(|a, b| a.0.cmp(&b.0));
+        rails
+    };
+
+    let mut nodes = vec![];
+    let mut connectors = vec![];
+    for designator in &designators {
+        match config.designators.class_of(designator) {
+            Some(PartClass::Battery) => nodes.push(PowerNode {
+                designator: (*designator).clone(),
+                kind: PowerNodeKind::Battery,
+                inputs: vec![],
+                outputs: rails_of(designator),
+                switching_nodes: vec![],
+            }),
+            Some(PartClass::Connector) => connectors.push(*designator),
+            Some(PartClass::Ic) => {
+                if let Some(node) = converter(netlist, config, power, switching_nodes, designator) {
+                    nodes.push(node);
+                }
+            }
+            _ => {}
+        }
+    }
+    let fed = nodes
+        .iter()
+        .flat_map(|node| node.outputs.iter().cloned())
+        .collect::<HashSet<_>>();
+    for designator in connectors {
+        let outputs = rails_of(designator)
+            .into_iter()
+            .filter(|rail| !fed.contains(rail))
+            .collect::<Vec<_>>();
+        if !outputs.is_empty() {
+            nodes.push(PowerNode {
+                designator: designator.clone(),
+                kind: PowerNodeKind::Connector,
+                inputs: vec![],
+                outputs,
+                switching_nodes: vec![],
+            });
+        }
+    }
+    nodes.sort_by(|a, b| a.designator.0.cmp(&b.designator.0));
+
+    let mut rails: HashMap<NetName, RailBranch> = power
+        .power_rails
+        .keys()
+        .map(|rail| (rail.clone(), RailBranch::default()))
+        .collect();
+    for node in &nodes {
+        for output in &node.outputs {
+            if let Some(branch) = rails.get_mut(output) {
+                branch.fed_by.push(node.designator.clone());
+            }
+        }
+    }
+    for designator in designators
+        .into_iter()
+        .filter(|d| config.designators.is(d, PartClass::Ic))
+    {
+        for rail in rails_of(designator) {
+            let Some(branch) = rails.get_mut(&rail) else {
+                continue;
+            };
+            if !branch.fed_by.contains(designator) {
+                branch.loads.push(designator.clone());
+            }
+        }
+    }
+    PowerTree { nodes, rails }
+}
+
+/// Regulator, DC-DC converter or load switch, None for ICs that only take power
+fn converter(
+    netlist: &Netlist,
+    config: &ProjectConfig,
+    power: &Power,
+    switching_nodes: &HashSet<NetName>,
+    designator: &Designator,
+) -> Option<PowerNode> {
+    let (inputs, mut outputs) = power_pin_rails(netlist, designator, power);
+    let mut own_switching_nodes = netlist
+        .part_nets(designator)
+        .into_iter()
+        .filter(|net| switching_nodes.contains(net))
+        .collect::<Vec<_>>();
+    own_switching_nodes.sort_by(|a, b| a.0.cmp(&b.0));
+    for switching_node in &own_switching_nodes {
+        let inductors = netlist
+            .index()
+            .net_parts(switching_node)
+            .into_iter()
+            .flatten()
+            .filter(|d| config.designators.is(d, PartClass::Inductor));
+        for inductor in inductors {
+            for net in netlist.part_nets(inductor) {
+                if power.power_rails.contains_key(&net) && !outputs.contains(&net) {
+                    outputs.push(net);
+                }
+            }
+        }
+    }
+    // a boost converter's inductor goes back to its input
+    outputs.retain(|output| !inputs.contains(output));
+    outputs.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let kind = if !own_switching_nodes.is_empty() {
+        PowerNodeKind::DcDcConverter
+    } else if outputs.is_empty() {
+        return None;
+    } else if is_load_switch(netlist, designator, config) {
+        PowerNodeKind::LoadSwitch
+    } else {
+        PowerNodeKind::LinearRegulator
+    };
+    Some(PowerNode {
+        designator: designator.clone(),
+        kind,
+        inputs,
+        outputs,
+        switching_nodes: own_switching_nodes,
+    })
+}
+
+/// Rails the part's `PowerIn` and `PowerOut` pins are on
+fn power_pin_rails(
+    netlist: &Netlist,
+    designator: &Designator,
+    power: &Power,
+) -> (Vec<NetName>, Vec<NetName>) {
+    let mut inputs = vec![];
+    let mut outputs = vec![];
+    for (pin_id, net_name) in netlist.index().part_nodes(designator) {
+        if !power.power_rails.contains_key(net_name) {
+            continue;
+        }
+        let found = match netlist
+            .pin(designator, pin_id)
+            .map(|pin| pin.default_mode.ty)
+        {
+            Some(PinType::PowerIn) => &mut inputs,
+            Some(PinType::PowerOut) => &mut outputs,
+            _ => continue,
+        };
+        if !found.contains(net_name) {
+            found.push(net_name.clone());
+        }
+    }
+    (inputs, outputs)
+}
+
+/// Value or description matches one of `power.load_switch`
+fn is_load_switch(netlist: &Netlist, designator: &Designator, config: &ProjectConfig) -> bool {
+    let Some(component) = netlist.components.get(designator) else {
+        return false;
+    };
+    let lib_description = netlist
+        .lib_parts
+        .get(&component.lib_source)
+        .map(|lib_part| lib_part.description.as_str())
+        .unwrap_or_default();
+    config.power.load_switch.iter().any(|re| {
+        re.is_match(&component.value)
+            || re.is_match(&component.description)
+            || re.is_match(lib_description)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PowerNodeKind;
+    use crate::Pcba;
+    use ecad_file_format::netlist::{Netlist, PinType};
+    use ecad_file_format::test_util::NetlistBuilder;
+    use ecad_file_format::{Designator, NetName};
+
+    fn netlist() -> Netlist {
+        NetlistBuilder::new()
+            .lib_part(
+                "BUCK",
+                &[("VIN", PinType::PowerIn), ("SW", PinType::Passive)],
+            )
+            .lib_part(
+                "LDO",
+                &[("VIN", PinType::PowerIn), ("VOUT", PinType::PowerOut)],
+            )
+            .lib_part("MCU", &[("VDD", PinType::PowerIn)])
+            .part("BT1", "CR2032", "Passive")
+            .part("J1", "USB_C", "Passive")
+            .part("J2", "Header", "Passive")
+            .part("L1", "4u7", "Passive")
+            .part("U1", "TPS62160", "BUCK")
+            .part("U2", "AMS1117-1.8", "LDO")
+            .part("U3", "STM32", "MCU")
+            .part("U4", "Sensor", "MCU")
+            .net("VBUS", &[("J1", "1"), ("U1", "1")])
+            .net("SW", &[("U1", "2"), ("L1", "1")])
+            .net(
+                "+3V3",
+                &[("L1", "2"), ("U2", "1"), ("U3", "1"), ("J2", "1")],
+            )
+            .net("+1V8", &[("U2", "2"), ("U4", "1")])
+            .net("VBAT", &[("BT1", "1")])
+            .net("GND", &[("J1", "2"), ("J2", "2"), ("BT1", "2")])
+            .build()
+    }
+
+    #[test]
+    fn power_tree_is_built() {
+        let pcba = Pcba::new(netlist());
+        let tree = &pcba.power_tree;
+        let rail = |name: &str| NetName(name.into());
+        let designators = |nodes: Vec<&super::PowerNode>| {
+            nodes
+                .iter()
+                .map(|node| node.designator.0.clone())
+                .collect::<Vec<_>>()
+        };
+
+        let kinds = tree
+            .nodes
+            .iter()
+            .map(|node| (node.designator.0.as_str(), node.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            [
+                ("BT1", PowerNodeKind::Battery),
+                ("J1", PowerNodeKind::Connector),
+                ("U1", PowerNodeKind::DcDcConverter),
+                ("U2", PowerNodeKind::LinearRegulator),
+            ]
+        );
+        let buck = tree.node(&Designator("U1".into())).unwrap();
+        assert_eq!(buck.inputs, [rail("VBUS")]);
+        assert_eq!(buck.outputs, [rail("+3V3")]);
+        assert_eq!(buck.switching_nodes, [rail("SW")]);
+
+        assert_eq!(designators(tree.fed_by(&rail("+3V3")).collect()), ["U1"]);
+        assert_eq!(
+            tree.loads(&rail("+3V3")),
+            [Designator("U2".into()), Designator("U3".into())]
+        );
+        assert_eq!(tree.loads(&rail("+1V8")), [Designator("U4".into())]);
+        assert_eq!(designators(tree.sources_of(&rail("+1V8"))), ["J1"]);
+        assert_eq!(designators(tree.sources_of(&rail("VBAT"))), ["BT1"]);
+    }
+}
@@ -0,0 +1,598 @@
+mod html;
+mod junit;
+mod markdown;
+mod sarif;
+
+use crate::diagnostics::{Diagnostic, Location};
+use crate::i2c::{I2cBus, I2cNode};
+use crate::{Pcba, Severity};
+use anyhow::{Error, Result};
+use ecad_file_format::netlist::{Netlist, SourceLocation};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use std::str::FromStr;
+
+/// Incremented on changes that break existing consumers of the JSON report, adding fields is not one
+pub const REPORT_SCHEMA_VERSION: u32 = 1;
+
+/// JSON Schema of the JSON report
+pub const REPORT_SCHEMA: &str = include_str!("../schema/report.v1.schema.json");
+
+/// Analysis result in a form that is written to files, everything is sorted so that reports of the
+/// same design are identical
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub schema_version: u32,
+    pub tool: Tool,
+    /// Design files the netlist was loaded from
+    pub source_files: Vec<String>,
+    pub summary: Summary,
+    pub diagnostics: Vec<ReportDiagnostic>,
+    pub i2c_buses: Vec<ReportI2cBus>,
+    /// Buses connected directly or through ties, sharing the same pull-ups
+    pub i2c_direct_segments: Vec<Vec<String>>,
+    /// Buses that are the same logical bus, e.g. connected through voltage translators
+    pub i2c_same_bus_segments: Vec<Vec<String>>,
+    pub power_rails: Vec<ReportPowerRail>,
+    pub ground_nets: Vec<String>,
+    pub switching_nodes: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Tool {
+    pub name: &'static str,
+    pub version: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Summary {
+    pub total: usize,
+    pub waived: usize,
+    /// Diagnostics that are not waived and are at least `fail_on` severe
+    pub failed: usize,
+    pub fail_on: Severity,
+    /// Not waived diagnostics by severity
+    pub by_severity: BTreeMap<String, usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReportDiagnostic {
+    pub code: &'static str,
+    pub severity: Severity,
+    pub message: String,
+    pub locations: Vec<ReportLocation>,
+    pub related: Vec<ReportLocation>,
+    pub waiver: Option<ReportWaiver>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ReportLocation {
+    Part {
+        designator: String,
+        source: Option<ReportSource>,
+    },
+    Net {
+        net: String,
+    },
+    Node {
+        designator: String,
+        pin: String,
+        source: Option<ReportSource>,
+    },
+    Bus {
+        name: String,
+    },
+    Rail {
+        net: String,
+    },
+}
+
+/// Where a part or pin is in the design files, None fields are not known to the source format
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportSource {
+    /// Human-readable sheet path, e.g. "/MCU_H7/"
+    pub sheet_path: Option<String>,
+    /// File the sheet is stored in, relative to the root schematic
+    pub sheet_file: Option<String>,
+    /// X and Y coordinates on the sheet in mm
+    pub position: Option<[f32; 2]>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReportWaiver {
+    pub author: String,
+    pub reason: String,
+    pub expires: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReportI2cBus {
+    pub name: String,
+    pub scl_net: String,
+    pub sda_net: String,
+    pub pull_up: Option<ReportPullUp>,
+    pub nodes: Vec<ReportI2cNode>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReportPullUp {
+    pub scl: String,
+    pub sda: String,
+    pub net: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReportI2cNode {
+    /// device, voltage_translator, connector, tie, test_point or unknown
+    pub kind: &'static str,
+    pub parts: Vec<String>,
+    /// Bus on the other side of a translator or a tie
+    pub other_side: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReportPowerRail {
+    pub net: String,
+    pub voltage: Option<f32>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ReportFormat {
+    Json,
+    Sarif,
+    Junit,
+    Markdown,
+    Html,
+}
+
+impl Report {
+    pub fn new(pcba: &Pcba, fail_on: Severity) -> Self {
+        let mut diagnostics = pcba.diagnostics.iter().collect::<Vec<_>>();
+        diagnostics.sort_by(|a, b| {
+            b.severity
+                .cmp(&a.severity)
+                .then(a.code.cmp(b.code))
+                .then(a.message.cmp(&b.message))
+        });
+        let mut by_severity = BTreeMap::new();
+        for d in pcba.diagnostics.active() {
+            *by_severity.entry(d.severity.to_string()).or_default() += 1;
+        }
+        let summary = Summary {
+            total: pcba.diagnostics.len(),
+            waived: pcba.diagnostics.len() - pcba.diagnostics.active().count(),
+            failed: pcba
+                .diagnostics
+                .active()
+                .filter(|d| d.severity.is_at_least(fail_on))
+                .count(),
+            fail_on,
+            by_severity,
+        };
+
+        let mut i2c_buses = pcba
+            .i2c_buses
+            .by_name
+            .values()
+            .map(ReportI2cBus::from)
+            .collect::<Vec<_>>();
+        i2c_buses.sort_by(|a, b| a.name.cmp(&b.name));
+        let mut power_rails = pcba
+            .power
+            .power_rails
+            .iter()
+            .map(|(net, rail)| ReportPowerRail {
+                net: net.0.clone(),
+                voltage: rail.voltage.map(|v| v.0),
+            })
+            .collect::<Vec<_>>();
+        power_rails.sort_by(|a, b| a.net.cmp(&b.net));
+
+        Report {
+            schema_version: REPORT_SCHEMA_VERSION,
+            tool: Tool {
+                name: "better_erc",
+                version: env!("CARGO_PKG_VERSION"),
+            },
+            source_files: vec![],
+            summary,
+            diagnostics: diagnostics
+                .into_iter()
+                .map(|d| ReportDiagnostic::new(d, &pcba.netlist))
+                .collect(),
+            i2c_buses,
+            i2c_direct_segments: sorted_segments(&pcba.i2c_buses.direct_segments),
+            i2c_same_bus_segments: sorted_segments(&pcba.i2c_buses.same_bus_segments),
+            power_rails,
+            ground_nets: sorted(pcba.power.ground_nets.iter().map(|n| n.0.clone())),
+            switching_nodes: sorted(pcba.switching_nodes.iter().map(|n| n.0.clone())),
+        }
+    }
+
+    pub fn render(&self, format: ReportFormat) -> Result<String> {
+        Ok(match format {
+            ReportFormat::Json => self.to_json()?,
+            ReportFormat::Sarif => sarif::render(self)?,
+            ReportFormat::Junit => junit::render(self),
+            ReportFormat::Markdown => markdown::render(self),
+            ReportFormat::Html => html::render(self),
+        })
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).map_err(|e| Error::msg(format!("{e}")))
+    }
+
+    /// Writes the report in a format chosen by file extension
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let format = ReportFormat::from_path(path).ok_or_else(|| {
+            Error::msg(format!(
+                "{}: unknown report format, use .json, .sarif, .xml, .md or .html",
+                path.display()
+            ))
+        })?;
+        std::fs::write(path, self.render(format)?)
+            .map_err(|e| Error::msg(format!("{}: {e}", path.display())))
+    }
+}
+
+impl ReportFormat {
+    /// .json, .sarif (or .sarif.json), .xml for JUnit, .md and .html
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?.to_lowercase();
+        if name.ends_with(".sarif") || name.ends_with(".sarif.json") {
+            Some(ReportFormat::Sarif)
+        } else if name.ends_with(".json") {
+            Some(ReportFormat::Json)
+        } else if name.ends_with(".xml") {
+            Some(ReportFormat::Junit)
+        } else if name.ends_with(".md") {
+            Some(ReportFormat::Markdown)
+        } else if name.ends_with(".html") || name.ends_with(".htm") {
+            Some(ReportFormat::Html)
+        } else {
+            None
+        }
+    }
+}
+
+impl FromStr for ReportFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "json" => ReportFormat::Json,
+            "sarif" => ReportFormat::Sarif,
+            "junit" => ReportFormat::Junit,
+            "markdown" | "md" => ReportFormat::Markdown,
+            "html" => ReportFormat::Html,
+            _ => return Err(Error::msg(format!("unknown report format \"{s}\""))),
+        })
+    }
+}
+
+impl ReportDiagnostic {
+    /// Part and pin locations get their sheet and position from the netlist
+    pub fn new(d: &Diagnostic, netlist: &Netlist) -> Self {
+        let location = |l: &Location| ReportLocation::new(l, netlist);
+        ReportDiagnostic {
+            code: d.code,
+            severity: d.severity,
+            message: d.message.clone(),
+            locations: d.locations.iter().map(location).collect(),
+            related: d.related.iter().map(location).collect(),
+            waiver: d.waived_by.as_ref().map(|w| ReportWaiver {
+                author: w.author.clone(),
+                reason: w.reason.clone(),
+                expires: w.expires.map(|date| date.to_string()),
+            }),
+        }
+    }
+}
+
+impl ReportLocation {
+    pub fn new(location: &Location, netlist: &Netlist) -> Self {
+        match location {
+            Location::Part(designator) => ReportLocation::Part {
+                designator: designator.0.clone(),
+                source: netlist
+                    .component_location(designator)
+                    .map(ReportSource::from),
+            },
+            Location::Net(net) => ReportLocation::Net { net: net.0.clone() },
+            Location::Node(designator, pin) => ReportLocation::Node {
+                designator: designator.0.clone(),
+                pin: pin.0.clone(),
+                source: netlist
+                    .node_location(designator, pin)
+                    .map(ReportSource::from),
+            },
+            Location::Bus(name) => ReportLocation::Bus { name: name.clone() },
+            Location::Rail(net) => ReportLocation::Rail { net: net.0.clone() },
+        }
+    }
+}
+
+impl ReportLocation {
+    /// Short human readable form, e.g. "R1", "U1.5", "net SCL" or "bus I2C1"
+    pub fn label(&self) -> String {
+        match self {
+            ReportLocation::Part { designator, .. } => designator.clone(),
+            ReportLocation::Net { net } => format!("net {net}"),
+            ReportLocation::Node {
+                designator, pin, ..
+            } => format!("{designator}.{pin}"),
+            ReportLocation::Bus { name } => format!("bus {name}"),
+            ReportLocation::Rail { net } => format!("rail {net}"),
+        }
+    }
+
+    /// Sheet and position of parts and pins, if the source format has them
+    pub fn source(&self) -> Option<&ReportSource> {
+        match self {
+            ReportLocation::Part { source, .. } | ReportLocation::Node { source, .. } => {
+                source.as_ref()
+            }
+            _ => None,
+        }
+    }
+}
+
+impl From<&SourceLocation> for ReportSource {
+    fn from(location: &SourceLocation) -> Self {
+        ReportSource {
+            sheet_path: location
+                .sheet_path
+                .clone()
+                .or_else(|| location.sheet_tstamps.clone()),
+            sheet_file: location.sheet_file.clone(),
+            position: location.position.map(|(x, y)| [x, y]),
+        }
+    }
+}
+
+/// Same form as the netlist's source location, e.g. "sheet /MCU/ (mcu.kicad_sch) at (10, 20)"
+impl Display for ReportSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "sheet {}", self.sheet_path.as_deref().unwrap_or("?"))?;
+        if let Some(file) = &self.sheet_file {
+            write!(f, " ({file})")?;
+        }
+        if let Some([x, y]) = self.position {
+            write!(f, " at ({x}, {y})")?;
+        }
+        Ok(())
+    }
+}
+
+impl ReportDiagnostic {
+    pub fn locations_label(&self) -> String {
+        self.locations
+            .iter()
+            .map(ReportLocation::label)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Same as `locations_label`, followed by where the first part or pin is in the design
+    pub fn locations_label_with_source(&self) -> String {
+        let label = self.locations_label();
+        match self.locations.iter().find_map(ReportLocation::source) {
+            Some(source) => format!("{label} ({source})"),
+            None => label,
+        }
+    }
+}
+
+impl From<&I2cBus> for ReportI2cBus {
+    fn from(bus: &I2cBus) -> Self {
+        ReportI2cBus {
+            name: bus.derived_name.clone(),
+            scl_net: bus.scl_net.0.clone(),
+            sda_net: bus.sda_net.0.clone(),
+            pull_up: bus.pull_up.as_ref().map(|p| ReportPullUp {
+                scl: p.scl.0.clone(),
+                sda: p.sda.0.clone(),
+                net: p.v_net.0.clone(),
+            }),
+            nodes: bus.nodes.iter().map(ReportI2cNode::from).collect(),
+        }
+    }
+}
+
+impl From<&I2cNode> for ReportI2cNode {
+    fn from(node: &I2cNode) -> Self {
+        let (kind, parts, other_side) = match node {
+            I2cNode::Device(d) => ("device", vec![d], None),
+            I2cNode::VoltageTranslator { part, other_side } => {
+                ("voltage_translator", vec![part], Some(other_side))
+            }
+            I2cNode::VoltageTranslatorDiscrete {
+                scl_fet,
+                sda_fet,
+                other_side,
+            } => (
+                "voltage_translator",
+                vec![scl_fet, sda_fet],
+                Some(other_side),
+            ),
+            I2cNode::Connector(d) => ("connector", vec![d], None),
+            I2cNode::Tie {
+                scl_tie,
+                sda_tie,
+                other_side,
+            } => ("tie", vec![scl_tie, sda_tie], Some(other_side)),
+            I2cNode::TestPoint(d) => ("test_point", vec![d], None),
+            I2cNode::Unknown(d) => ("unknown", vec![d], None),
+        };
+        ReportI2cNode {
+            kind,
+            parts: parts.into_iter().map(|d| d.0.clone()).collect(),
+            other_side: other_side.cloned(),
+        }
+    }
+}
+
+/// Escapes text for XML and HTML, both in element content and in attribute values
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn sorted(items: impl Iterator<Item = String>) -> Vec<String> {
+    let mut items = items.collect::<Vec<_>>();
+    items.sort();
+    items
+}
+
+fn sorted_segments(segments: &[HashSet<String>]) -> Vec<Vec<String>> {
+    let mut segments = segments
+        .iter()
+        .map(|segment| sorted(segment.iter().cloned()))
+        .collect::<Vec<_>>();
+    segments.sort();
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{REPORT_SCHEMA, REPORT_SCHEMA_VERSION, Report, ReportFormat};
+    use crate::waiver::Waivers;
+    use crate::{Pcba, Severity};
+    use ecad_file_format::netlist::SourceLocation;
+    use ecad_file_format::test_util::{NetlistBuilder, component};
+    use std::path::Path;
+
+    fn pcba() -> Pcba {
+        let mut r3 = component("", "R");
+        r3.location = SourceLocation {
+            sheet_path: Some("/Sensors/".into()),
+            sheet_file: Some("sensors.kicad_sch".into()),
+            position: Some((25.5, 50.75)),
+            ..Default::default()
+        };
+        let netlist = NetlistBuilder::new()
+            .part("U1", "TMP117", "X")
+            .part("R1", "1k", "R")
+            .part("R2", "1k", "R")
+            .component("R3", r3)
+            .net("SCL", &[("U1", "1"), ("R1", "1")])
+            .net("SDA", &[("U1", "2"), ("R2", "1")])
+            .net(
+                "+3V3",
+                &[("U1", "3"), ("R1", "2"), ("R2", "2"), ("R3", "1")],
+            )
+            .net("GND", &[("U1", "4"), ("R3", "2")])
+            .build();
+        let mut pcba = Pcba::new(netlist);
+        let waivers = Waivers::from_toml(
+            "[[waiver]]\ncode = \"STY003\"\nauthor = \"J. Doe\"\nreason = \"<populated later>\"",
+        )
+        .unwrap();
+        pcba.apply_waivers(&waivers);
+        pcba
+    }
+
+    #[test]
+    fn reports_are_rendered_in_all_formats() {
+        let mut report = Report::new(&pcba(), Severity::Warning);
+        report.source_files = vec!["board.net".into()];
+        assert_eq!(report.summary.waived, 1);
+        assert!(report.summary.failed >= 1);
+        assert_eq!(report.i2c_buses.len(), 1);
+        assert_eq!(report.i2c_buses[0].pull_up.as_ref().unwrap().net, "+3V3");
+        assert!((report.power_rails[0].voltage.unwrap() - 3.3).abs() < 1e-3);
+
+        let json: serde_json::Value =
+            serde_json::from_str(&report.render(ReportFormat::Json).unwrap()).unwrap();
+        assert_eq!(json["schema_version"], REPORT_SCHEMA_VERSION);
+        assert_eq!(json["i2c_buses"][0]["name"], "I2C");
+        let pull_up_range = json["diagnostics"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|d| d["code"] == "I2C005")
+            .unwrap();
+        assert_eq!(pull_up_range["severity"], "warning");
+        assert_eq!(pull_up_range["locations"][0]["kind"], "bus");
+        let no_value = json["diagnostics"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|d| d["code"] == "STY003")
+            .unwrap();
+        assert_eq!(
+            no_value["locations"][0]["source"]["sheet_path"],
+            "/Sensors/"
+        );
+        let schema: serde_json::Value = serde_json::from_str(REPORT_SCHEMA).unwrap();
+        let required = schema["required"].as_array().unwrap();
+        assert!(
+            required
+                .iter()
+                .all(|key| json.get(key.as_str().unwrap()).is_some())
+        );
+
+        let sarif: serde_json::Value =
+            serde_json::from_str(&report.render(ReportFormat::Sarif).unwrap()).unwrap();
+        assert_eq!(sarif["version"], "2.1.0");
+        let results = sarif["runs"][0]["results"].as_array().unwrap();
+        assert_eq!(results.len(), report.diagnostics.len());
+        let waived = results.iter().find(|r| r["ruleId"] == "STY003").unwrap();
+        assert_eq!(waived["suppressions"][0]["kind"], "external");
+        let location = &waived["locations"][0];
+        assert_eq!(
+            location["physicalLocation"]["artifactLocation"]["uri"],
+            "sensors.kicad_sch"
+        );
+        assert_eq!(
+            location["physicalLocation"]["properties"]["position"],
+            serde_json::json!([25.5, 50.75])
+        );
+        assert_eq!(
+            location["logicalLocations"][0]["fullyQualifiedName"],
+            "/Sensors/R3"
+        );
+        let unlocated = results.iter().find(|r| r["ruleId"] == "I2C005").unwrap();
+        assert_eq!(
+            unlocated["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+            "board.net"
+        );
+
+        let junit = report.render(ReportFormat::Junit).unwrap();
+        assert!(junit.contains("<testsuite name=\"better_erc\""));
+        assert!(junit.contains("<skipped message=\"&lt;populated later&gt;\"/>"));
+        assert!(junit.contains("<failure"));
+
+        let markdown = report.render(ReportFormat::Markdown).unwrap();
+        assert!(markdown.contains("| warning | I2C005 |"));
+        assert!(markdown.contains("## I2C buses"));
+
+        let i2c = report.diagnostics.iter_mut().find(|d| d.code == "I2C005");
+        i2c.unwrap().severity = Severity::SevereWarning;
+        let html = report.render(ReportFormat::Html).unwrap();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("&lt;populated later&gt;"));
+        assert!(html.contains("<tr class=\"severe_warning\">"));
+        assert!(html.contains("R3 (sheet /Sensors/ (sensors.kicad_sch) at (25.5, 50.75))"));
+
+        assert_eq!(
+            ReportFormat::from_path(Path::new("out/erc.sarif.json")),
+            Some(ReportFormat::Sarif)
+        );
+        assert_eq!(ReportFormat::from_path(Path::new("erc.txt")), None);
+    }
+}
@@ -0,0 +1,123 @@
+use super::{Report, escape};
+use std::fmt::Write;
+
+const STYLE: &str = "body{font-family:sans-serif;margin:2em}\
+table{border-collapse:collapse;margin-bottom:1.5em}\
+th,td{border:1px solid #ccc;padding:4px 8px;text-align:left;vertical-align:top}\
+th{background:#f0f0f0}\
+.error,.severe_warning{color:#b00020}.warning{color:#a15c00}\
+.waived{color:#888}";
+
+/// Standalone page without external resources, so that it can be attached to a design review
+pub(super) fn render(report: &Report) -> String {
+    let mut html = String::new();
+    let summary = &report.summary;
+    writeln!(html, "<!DOCTYPE html>").unwrap();
+    writeln!(
+        html,
+        "<html><head><meta charset=\"utf-8\"><title>ERC report</title>"
+    )
+    .unwrap();
+    writeln!(html, "<style>{STYLE}</style></head><body>").unwrap();
+    writeln!(html, "<h1>ERC report</h1>").unwrap();
+    if !report.source_files.is_empty() {
+        writeln!(
+            html,
+            "<p>Design: {}</p>",
+            escape(&report.source_files.join(", "))
+        )
+        .unwrap();
+    }
+    writeln!(
+        html,
+        "<p>{} diagnostic(s), {} waived, {} at or above {}.</p>",
+        summary.total, summary.waived, summary.failed, summary.fail_on
+    )
+    .unwrap();
+
+    writeln!(html, "<h2>Diagnostics</h2>").unwrap();
+    writeln!(
+        html,
+        "<table><tr><th>Severity</th><th>Code</th><th>Message</th><th>Location</th><th>Waiver</th></tr>"
+    )
+    .unwrap();
+    for d in &report.diagnostics {
+        let (class, waiver) = match &d.waiver {
+            Some(w) => ("waived".to_string(), format!("{} ({})", w.reason, w.author)),
+            // same as the serialized form, e.g. severe_warning
+            None => (d.severity.to_string().replace(' ', "_"), String::new()),
+        };
+        writeln!(
+            html,
+            "<tr class=\"{class}\"><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            d.severity,
+            d.code,
+            escape(&d.message),
+            escape(&d.locations_label_with_source()),
+            escape(&waiver)
+        )
+        .unwrap();
+    }
+    writeln!(html, "</table>").unwrap();
+
+    writeln!(html, "<h2>I2C buses</h2>").unwrap();
+    writeln!(
+        html,
+        "<table><tr><th>Bus</th><th>SCL</th><th>SDA</th><th>Pull-ups</th><th>Nodes</th></tr>"
+    )
+    .unwrap();
+    for bus in &report.i2c_buses {
+        let pull_up = bus
+            .pull_up
+            .as_ref()
+            .map(|p| format!("{}, {} to {}", p.scl, p.sda, p.net))
+            .unwrap_or_else(|| "-".into());
+        let nodes = bus
+            .nodes
+            .iter()
+            .map(|n| format!("{} {}", n.kind, n.parts.join("/")))
+            .collect::<Vec<_>>()
+            .join(", ");
+        writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            escape(&bus.name),
+            escape(&bus.scl_net),
+            escape(&bus.sda_net),
+            escape(&pull_up),
+            escape(&nodes)
+        )
+        .unwrap();
+    }
+    writeln!(html, "</table>").unwrap();
+
+    writeln!(html, "<h2>Power</h2>").unwrap();
+    writeln!(html, "<table><tr><th>Rail</th><th>Voltage</th></tr>").unwrap();
+    for rail in &report.power_rails {
+        let voltage = rail
+            .voltage
+            .map(|v| format!("{v} V"))
+            .unwrap_or_else(|| "?".into());
+        writeln!(
+            html,
+            "<tr><td>{}</td><td>{voltage}</td></tr>",
+            escape(&rail.net)
+        )
+        .unwrap();
+    }
+    writeln!(html, "</table>").unwrap();
+    writeln!(
+        html,
+        "<p>Ground nets: {}</p>",
+        escape(&report.ground_nets.join(", "))
+    )
+    .unwrap();
+    writeln!(
+        html,
+        "<p>Switching nodes: {}</p>",
+        escape(&report.switching_nodes.join(", "))
+    )
+    .unwrap();
+    writeln!(html, "</body></html>").unwrap();
+    html
+}
@@ -0,0 +1,59 @@
+use super::{Report, escape};
+use std::fmt::Write;
+
+/// One test case per diagnostic, failed if it is at least `fail_on` severe, skipped if waived
+pub(super) fn render(report: &Report) -> String {
+    let mut xml = String::new();
+    let summary = &report.summary;
+    writeln!(xml, r#"<?xml version="1.0" encoding="UTF-8"?>"#).unwrap();
+    writeln!(
+        xml,
+        r#"<testsuites name="better_erc" tests="{}" failures="{}" skipped="{}">"#,
+        summary.total, summary.failed, summary.waived
+    )
+    .unwrap();
+    writeln!(
+        xml,
+        r#"  <testsuite name="better_erc" tests="{}" failures="{}" skipped="{}">"#,
+        summary.total, summary.failed, summary.waived
+    )
+    .unwrap();
+    for d in &report.diagnostics {
+        let name = match d.locations_label() {
+            label if label.is_empty() => d.code.to_string(),
+            label => format!("{} {label}", d.code),
+        };
+        write!(
+            xml,
+            r#"    <testcase classname="{}" name="{}""#,
+            d.code,
+            escape(&name)
+        )
+        .unwrap();
+        if let Some(waiver) = &d.waiver {
+            writeln!(xml, ">").unwrap();
+            writeln!(
+                xml,
+                r#"      <skipped message="{}"/>"#,
+                escape(&waiver.reason)
+            )
+            .unwrap();
+            writeln!(xml, "    </testcase>").unwrap();
+        } else if d.severity.is_at_least(summary.fail_on) {
+            writeln!(xml, ">").unwrap();
+            writeln!(
+                xml,
+                r#"      <failure message="{}" type="{}"/>"#,
+                escape(&d.message),
+                d.severity
+            )
+            .unwrap();
+            writeln!(xml, "    </testcase>").unwrap();
+        } else {
+            writeln!(xml, "/>").unwrap();
+        }
+    }
+    writeln!(xml, "  </testsuite>").unwrap();
+    writeln!(xml, "</testsuites>").unwrap();
+    xml
+}
@@ -0,0 +1,109 @@
+use super::Report;
+use std::fmt::Write;
+
+pub(super) fn render(report: &Report) -> String {
+    let mut md = String::new();
+    let summary = &report.summary;
+    writeln!(md, "# ERC report\n").unwrap();
+    if !report.source_files.is_empty() {
+        writeln!(md, "Design: {}\n", report.source_files.join(", ")).unwrap();
+    }
+    writeln!(
+        md,
+        "{} diagnostic(s), {} waived, {} at or above {}.\n",
+        summary.total, summary.waived, summary.failed, summary.fail_on
+    )
+    .unwrap();
+
+    writeln!(md, "## Diagnostics\n").unwrap();
+    if report.diagnostics.is_empty() {
+        writeln!(md, "None.\n").unwrap();
+    } else {
+        writeln!(md, "| Severity | Code | Message | Location | Waiver |").unwrap();
+        writeln!(md, "|---|---|---|---|---|").unwrap();
+        for d in &report.diagnostics {
+            let waiver = d
+                .waiver
+                .as_ref()
+                .map(|w| format!("{} ({})", w.reason, w.author))
+                .unwrap_or_default();
+            writeln!(
+                md,
+                "| {} | {} | {} | {} | {} |",
+                d.severity,
+                d.code,
+                cell(&d.message),
+                cell(&d.locations_label_with_source()),
+                cell(&waiver)
+            )
+            .unwrap();
+        }
+        writeln!(md).unwrap();
+    }
+
+    writeln!(md, "## I2C buses\n").unwrap();
+    if report.i2c_buses.is_empty() {
+        writeln!(md, "None.\n").unwrap();
+    } else {
+        writeln!(md, "| Bus | SCL | SDA | Pull-ups | Nodes |").unwrap();
+        writeln!(md, "|---|---|---|---|---|").unwrap();
+        for bus in &report.i2c_buses {
+            let pull_up = bus
+                .pull_up
+                .as_ref()
+                .map(|p| format!("{}, {} to {}", p.scl, p.sda, p.net))
+                .unwrap_or_else(|| "-".into());
+            let nodes = bus
+                .nodes
+                .iter()
+                .map(|n| format!("{} {}", n.kind, n.parts.join("/")))
+                .collect::<Vec<_>>()
+                .join(", ");
+            writeln!(
+                md,
+                "| {} | {} | {} | {} | {} |",
+                cell(&bus.name),
+                cell(&bus.scl_net),
+                cell(&bus.sda_net),
+                cell(&pull_up),
+                cell(&nodes)
+            )
+            .unwrap();
+        }
+        writeln!(md).unwrap();
+        for segment in &report.i2c_same_bus_segments {
+            writeln!(md, "- Same bus: {}", segment.join(", ")).unwrap();
+        }
+        if !report.i2c_same_bus_segments.is_empty() {
+            writeln!(md).unwrap();
+        }
+    }
+
+    writeln!(md, "## Power\n").unwrap();
+    writeln!(md, "| Rail | Voltage |").unwrap();
+    writeln!(md, "|---|---|").unwrap();
+    for rail in &report.power_rails {
+        let voltage = rail
+            .voltage
+            .map(|v| format!("{v} V"))
+            .unwrap_or_else(|| "?".into());
+        writeln!(md, "| {} | {voltage} |", cell(&rail.net)).unwrap();
+    }
+    writeln!(md).unwrap();
+    writeln!(md, "Ground nets: {}\n", list(&report.ground_nets)).unwrap();
+    writeln!(md, "Switching nodes: {}", list(&report.switching_nodes)).unwrap();
+    md
+}
+
+/// Table cells cannot contain pipes or line breaks
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+fn list(items: &[String]) -> String {
+    if items.is_empty() {
+        "none".into()
+    } else {
+        items.join(", ")
+    }
+}
@@ -0,0 +1,117 @@
+use super::{Report, ReportLocation};
+use crate::Severity;
+use anyhow::{Error, Result};
+use serde_json::{Value, json};
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// SARIF 2.1.0 log with one run, diagnostics are attached to the sheet file of their first part
+/// or pin, or to the first design file when that is not known, so that code review tools can show
+/// them on the changed schematic. Schematic positions have no line and column, they are in the
+/// location message and the physical location's properties instead.
+pub(super) fn render(report: &Report) -> Result<String> {
+    let rules = report
+        .diagnostics
+        .iter()
+        .map(|d| d.code)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(|code| json!({ "id": code }))
+        .collect::<Vec<_>>();
+    let artifact = report.source_files.first();
+    let results = report
+        .diagnostics
+        .iter()
+        .map(|d| {
+            let logical_locations = d
+                .locations
+                .iter()
+                .map(|location| {
+                    let (name, kind) = match location {
+                        ReportLocation::Part { designator, .. } => (designator.clone(), "element"),
+                        ReportLocation::Node {
+                            designator, pin, ..
+                        } => (format!("{designator}.{pin}"), "member"),
+                        ReportLocation::Net { net } | ReportLocation::Rail { net } => {
+                            (net.clone(), "variable")
+                        }
+                        ReportLocation::Bus { name } => (name.clone(), "namespace"),
+                    };
+                    let sheet = location.source().and_then(|s| s.sheet_path.as_deref());
+                    match sheet {
+                        Some(sheet) => json!({
+                            "name": name,
+                            "fullyQualifiedName": format!("{sheet}{name}"),
+                            "kind": kind,
+                        }),
+                        None => json!({ "name": name, "kind": kind }),
+                    }
+                })
+                .collect::<Vec<_>>();
+            let mut location = json!({ "logicalLocations": logical_locations });
+            let source = d.locations.iter().find_map(ReportLocation::source);
+            let sheet_file = source.and_then(|s| s.sheet_file.as_deref());
+            let uri = match (artifact, sheet_file) {
+                (Some(artifact), Some(sheet_file)) => Some(sheet_uri(artifact, sheet_file)),
+                (None, Some(sheet_file)) => Some(sheet_file.replace('\\', "/")),
+                (artifact, None) => artifact.cloned(),
+            };
+            if let Some(uri) = uri {
+                location["physicalLocation"] = json!({ "artifactLocation": { "uri": uri } });
+                if let Some([x, y]) = source.and_then(|s| s.position) {
+                    location["physicalLocation"]["properties"] = json!({ "position": [x, y] });
+                }
+            }
+            if let Some(source) = source {
+                location["message"] = json!({ "text": source.to_string() });
+            }
+            let mut result = json!({
+                "ruleId": d.code,
+                "level": level(d.severity),
+                "message": { "text": d.message },
+                "locations": [location],
+            });
+            if let Some(waiver) = &d.waiver {
+                result["suppressions"] = json!([{
+                    "kind": "external",
+                    "justification": format!("{} ({})", waiver.reason, waiver.author),
+                }]);
+            }
+            result
+        })
+        .collect::<Vec<Value>>();
+    let log = json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": report.tool.name,
+                    "version": report.tool.version,
+                    "rules": rules,
+                }
+            },
+            "artifacts": report
+                .source_files
+                .iter()
+                .map(|file| json!({ "location": { "uri": file } }))
+                .collect::<Vec<_>>(),
+            "results": results,
+        }]
+    });
+    serde_json::to_string_pretty(&log).map_err(|e| Error::msg(format!("{e}")))
+}
+
+/// Sheet files are relative to the design file the netlist was loaded from
+fn sheet_uri(artifact: &str, sheet_file: &str) -> String {
+    let dir = Path::new(artifact).parent().unwrap_or(Path::new(""));
+    dir.join(sheet_file).to_string_lossy().replace('\\', "/")
+}
+
+fn level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error | Severity::SevereWarning => "error",
+        Severity::Warning => "warning",
+        Severity::Info | Severity::Suggestion => "note",
+    }
+}
@@ -0,0 +1,240 @@
+use crate::config::ProjectConfig;
+use crate::diagnostics::Diagnostics;
+use crate::general::{ConnectivityCompletenessRule, OutputContentionRule, UndrivenInputRule};
+use crate::i2c::I2cRule;
+use crate::pin_matrix::PinMatrixRule;
+use crate::power::propagation::RailVoltageRule;
+use crate::style::{ESeriesRule, MosfetPinNamesRule, ValueStyleRule};
+use crate::{Pcba, Severity};
+use anyhow::{Error, Result};
+
+/// One ERC check, run over an already analysed board (power structure, I2C buses, etc.)
+///
+/// The analysis is done in `Pcba::new` whatever rules are enabled, as later steps and the report
+/// depend on it. Rules that report problems found by the analysis, e.g. I2C pull-ups or rail
+/// voltage conflicts, only turn them into diagnostics, disabling them hides the problems but does
+/// not skip the analysis.
+pub trait Rule: Send + Sync {
+    /// Stable identifier, "category.name", used to enable or disable the rule
+    fn id(&self) -> &'static str;
+    fn category(&self) -> RuleCategory;
+    /// Severity of the rule's main findings, milder findings are emitted at a lower severity,
+    /// never above this one
+    fn default_severity(&self) -> Severity;
+    fn run(&self, pcba: &Pcba, diagnostics: &mut Diagnostics);
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum RuleCategory {
+    Style,
+    Connectivity,
+    Power,
+    I2c,
+}
+
+/// Set of rules to run, built-in ones are all registered and enabled by default
+pub struct RuleRegistry {
+    rules: Vec<RegisteredRule>,
+}
+
+struct RegisteredRule {
+    rule: Box<dyn Rule>,
+    enabled: bool,
+    severity: Option<Severity>,
+}
+
+impl RuleRegistry {
+    /// Registry without any rules
+    pub fn empty() -> Self {
+        RuleRegistry { rules: vec![] }
+    }
+
+    /// Adds an enabled rule, replacing a rule with the same id if there was one
+    pub fn register(&mut self, rule: Box<dyn Rule>) {
+        self.rules.retain(|r| r.rule.id() != rule.id());
+        self.rules.push(RegisteredRule {
+            rule,
+            enabled: true,
+            severity: None,
+        });
+    }
+
+    /// Built-in rules with enable and severity overrides from the project config
+    pub fn from_config(config: &ProjectConfig) -> Result<Self> {
+        let mut registry = RuleRegistry::default();
+        registry.configure(config)?;
+        Ok(registry)
+    }
+
+    /// Applies overrides from the project config, fails if a rule id is not registered
+    pub fn configure(&mut self, config: &ProjectConfig) -> Result<()> {
+        for (id, rule_override) in &config.rules {
+            let Some(registered) = self.rules.iter_mut().find(|r| r.rule.id() == id) else {
+                return Err(Error::msg(format!("rules: unknown rule \"{id}\"")));
+            };
+            if let Some(enabled) = rule_override.enabled {
+                registered.enabled = enabled;
+            }
+            if rule_override.severity.is_some() {
+                registered.severity = rule_override.severity;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns false if there is no rule with such id
+    pub fn set_enabled(&mut self, id: &str, enabled: bool) -> bool {
+        let mut found = false;
+        for registered in &mut self.rules {
+            if registered.rule.id() == id {
+                registered.enabled = enabled;
+                found = true;
+            }
+        }
+        found
+    }
+
+    /// Enables or disables all the rules in a category
+    pub fn set_category_enabled(&mut self, category: RuleCategory, enabled: bool) {
+        for registered in &mut self.rules {
+            if registered.rule.category() == category {
+                registered.enabled = enabled;
+            }
+        }
+    }
+
+    /// Overrides severity of all the rule's findings, returns false if there is no rule with such id
+    pub fn set_severity(&mut self, id: &str, severity: Option<Severity>) -> bool {
+        let mut found = false;
+        for registered in &mut self.rules {
+            if registered.rule.id() == id {
+                registered.severity = severity;
+                found = true;
+            }
+        }
+        found
+    }
+
+    pub fn is_enabled(&self, id: &str) -> bool {
+        self.rules.iter().any(|r| r.enabled && r.rule.id() == id)
+    }
+
+    /// All registered rules with their enabled state, in registration order
+    pub fn rules(&self) -> impl Iterator<Item = (&dyn Rule, bool)> {
+        self.rules.iter().map(|r| (r.rule.as_ref(), r.enabled))
+    }
+
+    pub fn run(&self, pcba: &Pcba, diagnostics: &mut Diagnostics) {
+        for registered in &self.rules {
+            if !registered.enabled {
+                continue;
+            }
+            let first = diagnostics.len();
+            registered.rule.run(pcba, diagnostics);
+            if let Some(severity) = registered.severity {
+                for diagnostic in &mut diagnostics.items[first..] {
+                    diagnostic.severity = severity;
+                }
+            }
+        }
+    }
+}
+
+impl Default for RuleRegistry {
+    fn default() -> Self {
+        let mut registry = RuleRegistry::empty();
+        registry.register(Box::new(ValueStyleRule));
+        registry.register(Box::new(MosfetPinNamesRule));
+        registry.register(Box::new(ESeriesRule));
+        registry.register(Box::new(I2cRule));
+        registry.register(Box::new(UndrivenInputRule));
+        registry.register(Box::new(OutputContentionRule));
+        registry.register(Box::new(ConnectivityCompletenessRule));
+        registry.register(Box::new(PinMatrixRule));
+        registry.register(Box::new(RailVoltageRule));
+        registry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Rule, RuleCategory, RuleRegistry};
+    use crate::config::ProjectConfig;
+    use crate::diagnostics::{Diagnostic, Diagnostics, Location};
+    use crate::{Pcba, Severity};
+    use ecad_file_format::Designator;
+    use ecad_file_format::netlist::Netlist;
+    use ecad_file_format::test_util::NetlistBuilder;
+
+    struct TooManyParts;
+
+    impl Rule for TooManyParts {
+        fn id(&self) -> &'static str {
+            "test.too_many_parts"
+        }
+
+        fn category(&self) -> RuleCategory {
+            RuleCategory::Style
+        }
+
+        fn default_severity(&self) -> Severity {
+            Severity::Info
+        }
+
+        fn run(&self, pcba: &Pcba, diagnostics: &mut Diagnostics) {
+            if !pcba.netlist.components.is_empty() {
+                let mut diagnostic =
+                    Diagnostic::new("TST001", self.default_severity(), "board has parts");
+                for designator in pcba.netlist.components.keys() {
+                    diagnostic = diagnostic.at(Location::Part(designator.clone()));
+                }
+                diagnostics.push(diagnostic);
+            }
+        }
+    }
+
+    fn netlist() -> Netlist {
+        NetlistBuilder::new().part("R1", "", "R").build()
+    }
+
+    #[test]
+    fn rules_can_be_disabled_and_added() {
+        let pcba = Pcba::new(netlist());
+        let no_value = pcba.diagnostics.with_code("STY003").next().unwrap();
+        assert_eq!(no_value.severity, Severity::Error);
+        assert_eq!(
+            no_value.locations,
+            [Location::Part(Designator("R1".into()))]
+        );
+        assert_eq!(no_value.to_string(), "error STY003: value is empty at R1");
+
+        let mut rules = RuleRegistry::default();
+        assert!(rules.set_enabled("style.value", false));
+        assert!(!rules.set_enabled("no.such_rule", false));
+        rules.register(Box::new(TooManyParts));
+        let pcba = Pcba::with_rules(netlist(), ProjectConfig::default(), &rules);
+        assert_eq!(pcba.diagnostics.len(), 1);
+        assert_eq!(pcba.diagnostics.items[0].code, "TST001");
+
+        rules.set_category_enabled(RuleCategory::Style, false);
+        assert!(!rules.is_enabled("test.too_many_parts"));
+        let pcba = Pcba::with_rules(netlist(), ProjectConfig::default(), &rules);
+        assert!(pcba.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn config_overrides_rules() {
+        let config =
+            ProjectConfig::from_toml("[rules.\"style.value\"]\nseverity = \"info\"").unwrap();
+        let pcba = Pcba::with_config(netlist(), config).unwrap();
+        let no_value = pcba.diagnostics.with_code("STY003").next().unwrap();
+        assert_eq!(no_value.severity, Severity::Info);
+
+        let config = ProjectConfig::from_toml("[rules.\"style.value\"]\nenabled = false").unwrap();
+        let pcba = Pcba::with_config(netlist(), config).unwrap();
+        assert!(pcba.diagnostics.is_empty());
+
+        let config = ProjectConfig::from_toml("[rules.\"style.typo\"]\nenabled = false").unwrap();
+        assert!(RuleRegistry::from_config(&config).is_err());
+    }
+}
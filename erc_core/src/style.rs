@@ -1,11 +1,17 @@
-use crate::Severity;
+use crate::config::{DesignatorClasses, PartClass, ProjectConfig};
+use crate::diagnostics::{Diagnostic, Diagnostics, Location};
+use crate::rule::{Rule, RuleCategory};
+use crate::{Pcba, Severity};
 use ecad_file_format::Designator;
-use ecad_file_format::netlist::Netlist;
-use ecad_file_format::passive_value::{PassiveValueParseWarning, parse_resistance_value};
+use ecad_file_format::netlist::{Component, Netlist};
+use ecad_file_format::passive_value::e_series::ESeries;
+use ecad_file_format::passive_value::{
+    PassiveValueParseWarning, parse_compound_capacitance, parse_compound_resistance,
+    parse_resistance_value,
+};
 
 #[derive(Debug)]
 pub struct StyleDiagnostic {
-    pub severity: Severity,
     pub designator: Designator,
     pub kind: StyleDiagnosticKind,
 }
@@ -17,13 +23,158 @@ pub enum StyleDiagnosticKind {
     NoValue,
     CalculateLaterValue,
     MosfetPinsNotNamed,
+    /// Value is not in the E-series implied by the part tolerance or selected for the project,
+    /// nearest standard values below and above are suggested
+    NotInESeries {
+        series: ESeries,
+        nearest: (f32, f32),
+    },
 }
 
-pub fn check_style(netlist: &Netlist, diagnostics: &mut Vec<StyleDiagnostic>) {
+impl StyleDiagnosticKind {
+    pub fn code(&self) -> &'static str {
+        match self {
+            StyleDiagnosticKind::WrongValue(_) => "STY001",
+            StyleDiagnosticKind::NonStandardValue(_) => "STY002",
+            StyleDiagnosticKind::NoValue => "STY003",
+            StyleDiagnosticKind::CalculateLaterValue => "STY004",
+            StyleDiagnosticKind::MosfetPinsNotNamed => "STY005",
+            StyleDiagnosticKind::NotInESeries { .. } => "STY006",
+        }
+    }
+
+    /// The rule's severity, at most a warning for values that are unfinished or written in an
+    /// unusual way
+    pub fn severity(&self, rule_severity: Severity) -> Severity {
+        match self {
+            StyleDiagnosticKind::NonStandardValue(_) | StyleDiagnosticKind::CalculateLaterValue => {
+                rule_severity.min(Severity::Warning)
+            }
+            _ => rule_severity,
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            StyleDiagnosticKind::WrongValue(e) => format!("value cannot be parsed: {e}"),
+            StyleDiagnosticKind::NonStandardValue(w) => {
+                format!("value is not written in a standard way: {w:?}")
+            }
+            StyleDiagnosticKind::NoValue => "value is empty".into(),
+            StyleDiagnosticKind::CalculateLaterValue => "value is yet to be calculated".into(),
+            StyleDiagnosticKind::MosfetPinsNotNamed => {
+                "MOSFET pins are not named G, S and D".into()
+            }
+            StyleDiagnosticKind::NotInESeries { series, nearest } => format!(
+                "value is not in {series:?}, nearest are {} and {}",
+                nearest.0, nearest.1
+            ),
+        }
+    }
+}
+
+impl StyleDiagnostic {
+    pub fn to_diagnostic(self, rule_severity: Severity) -> Diagnostic {
+        let severity = self.kind.severity(rule_severity);
+        Diagnostic::new(self.kind.code(), severity, self.kind.message())
+            .at(Location::Part(self.designator))
+    }
+}
+
+pub fn check_style(
+    netlist: &Netlist,
+    config: &ProjectConfig,
+    diagnostics: &mut Vec<StyleDiagnostic>,
+) {
+    check_values(netlist, &config.designators, diagnostics);
+    check_for_transistors_to_have_pin_names(netlist, &config.designators, diagnostics);
+    check_e_series(netlist, config, diagnostics);
+}
+
+/// Checks that values are present and, for resistors, parseable and written in a standard way
+pub struct ValueStyleRule;
+
+/// Checks that MOSFET pins are named G, S and D, so that other checks can tell them apart
+pub struct MosfetPinNamesRule;
+
+/// Checks that resistor and capacitor values are from the expected E-series
+pub struct ESeriesRule;
+
+impl Rule for ValueStyleRule {
+    fn id(&self) -> &'static str {
+        "style.value"
+    }
+
+    fn category(&self) -> RuleCategory {
+        RuleCategory::Style
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn run(&self, pcba: &Pcba, diagnostics: &mut Diagnostics) {
+        let mut found = Vec::new();
+        check_values(&pcba.netlist, &pcba.config.designators, &mut found);
+        let severity = self.default_severity();
+        diagnostics.extend(found.into_iter().map(|d| d.to_diagnostic(severity)));
+    }
+}
+
+impl Rule for MosfetPinNamesRule {
+    fn id(&self) -> &'static str {
+        "style.mosfet_pin_names"
+    }
+
+    fn category(&self) -> RuleCategory {
+        RuleCategory::Style
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn run(&self, pcba: &Pcba, diagnostics: &mut Diagnostics) {
+        let mut found = Vec::new();
+        check_for_transistors_to_have_pin_names(
+            &pcba.netlist,
+            &pcba.config.designators,
+            &mut found,
+        );
+        let severity = self.default_severity();
+        diagnostics.extend(found.into_iter().map(|d| d.to_diagnostic(severity)));
+    }
+}
+
+impl Rule for ESeriesRule {
+    fn id(&self) -> &'static str {
+        "style.e_series"
+    }
+
+    fn category(&self) -> RuleCategory {
+        RuleCategory::Style
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn run(&self, pcba: &Pcba, diagnostics: &mut Diagnostics) {
+        let mut found = Vec::new();
+        check_e_series(&pcba.netlist, &pcba.config, &mut found);
+        let severity = self.default_severity();
+        diagnostics.extend(found.into_iter().map(|d| d.to_diagnostic(severity)));
+    }
+}
+
+fn check_values(
+    netlist: &Netlist,
+    classes: &DesignatorClasses,
+    diagnostics: &mut Vec<StyleDiagnostic>,
+) {
     for (designator, component) in &netlist.components {
         if component.value.is_empty() {
             diagnostics.push(StyleDiagnostic {
-                severity: Severity::Error,
                 designator: designator.clone(),
                 kind: StyleDiagnosticKind::NoValue,
             });
@@ -31,7 +182,6 @@ pub fn check_style(netlist: &Netlist, diagnostics: &mut Vec<StyleDiagnostic>) {
         }
         if component.value.starts_with('?') || component.value.ends_with('?') {
             diagnostics.push(StyleDiagnostic {
-                severity: Severity::Warning,
                 designator: designator.clone(),
                 kind: StyleDiagnosticKind::CalculateLaterValue,
             });
@@ -40,12 +190,11 @@ pub fn check_style(netlist: &Netlist, diagnostics: &mut Vec<StyleDiagnostic>) {
         if component.value == "DNM" || component.value == "DNP" {
             continue;
         }
-        if designator.is_resistor() {
+        if classes.is(designator, PartClass::Resistor) {
             match parse_resistance_value(component.value.as_str()) {
                 Ok((_val, w)) => {
                     if let Some(w) = w {
                         diagnostics.push(StyleDiagnostic {
-                            severity: Severity::Warning,
                             designator: designator.clone(),
                             kind: StyleDiagnosticKind::NonStandardValue(w),
                         });
@@ -53,7 +202,6 @@ pub fn check_style(netlist: &Netlist, diagnostics: &mut Vec<StyleDiagnostic>) {
                 }
                 Err(e) => {
                     diagnostics.push(StyleDiagnostic {
-                        severity: Severity::Error,
                         designator: designator.clone(),
                         kind: StyleDiagnosticKind::WrongValue(format!("{}", e)),
                     });
@@ -61,15 +209,71 @@ pub fn check_style(netlist: &Netlist, diagnostics: &mut Vec<StyleDiagnostic>) {
             }
         }
     }
-    check_for_transistors_to_have_pin_names(netlist, diagnostics);
+}
+
+fn check_e_series(
+    netlist: &Netlist,
+    config: &ProjectConfig,
+    diagnostics: &mut Vec<StyleDiagnostic>,
+) {
+    let classes = &config.designators;
+    let project_series = config.thresholds.passive_e_series;
+    for (designator, component) in &netlist.components {
+        let (value, tolerance) = if classes.is(designator, PartClass::Resistor) {
+            let Ok((value, _)) = parse_compound_resistance(&component.value) else {
+                continue;
+            };
+            (value.value.0, value.tolerance)
+        } else if classes.is(designator, PartClass::Capacitor) {
+            let Ok((value, _)) = parse_compound_capacitance(&component.value) else {
+                continue;
+            };
+            (value.value.0, value.tolerance)
+        } else {
+            continue;
+        };
+        if value <= 0.0 {
+            continue;
+        }
+        let tolerance = tolerance.or_else(|| tolerance_field(component));
+        let Some(series) = project_series.or(tolerance.map(ESeries::for_tolerance)) else {
+            continue;
+        };
+        if series.contains(value) {
+            continue;
+        }
+        let Some(nearest) = series.nearest(value) else {
+            continue;
+        };
+        diagnostics.push(StyleDiagnostic {
+            designator: designator.clone(),
+            kind: StyleDiagnosticKind::NotInESeries { series, nearest },
+        });
+    }
+}
+
+/// Tolerance in percent from the "Tolerance" field, e.g. "1%" or "±5 %"
+fn tolerance_field(component: &Component) -> Option<f32> {
+    let (_, tolerance) = component
+        .fields
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("Tolerance"))?;
+    tolerance
+        .trim()
+        .trim_start_matches('±')
+        .trim_end_matches('%')
+        .trim()
+        .parse()
+        .ok()
 }
 
 fn check_for_transistors_to_have_pin_names(
     netlist: &Netlist,
+    classes: &DesignatorClasses,
     diagnostics: &mut Vec<StyleDiagnostic>,
 ) {
     for (designator, component) in &netlist.components {
-        if !designator.is_transistor() {
+        if !classes.is(designator, PartClass::Transistor) {
             continue;
         }
         let Some(lib_part) = netlist.lib_parts.get(&component.lib_source) else {
@@ -98,7 +302,6 @@ fn check_for_transistors_to_have_pin_names(
             }
             if !(gate_found && source_found && drain_found) {
                 diagnostics.push(StyleDiagnostic {
-                    severity: Severity::Warning,
                     designator: designator.clone(),
                     kind: StyleDiagnosticKind::MosfetPinsNotNamed,
                 });
@@ -109,9 +312,67 @@ fn check_for_transistors_to_have_pin_names(
 
 #[cfg(test)]
 mod tests {
-    use crate::style::check_style;
+    use crate::config::ProjectConfig;
+    use crate::style::{StyleDiagnosticKind, check_e_series, check_style};
+    use ecad_file_format::Designator;
+    use ecad_file_format::netlist::{Component, Netlist};
+    use ecad_file_format::passive_value::e_series::ESeries;
+    use ecad_file_format::test_util::component;
     use std::path::Path;
 
+    fn passive(value: &str, tolerance: Option<&str>) -> Component {
+        let mut passive = component(value, "R");
+        if let Some(tolerance) = tolerance {
+            passive
+                .fields
+                .insert("Tolerance".to_string(), tolerance.to_string());
+        }
+        passive
+    }
+
+    #[test]
+    fn values_outside_e_series_are_flagged() {
+        let mut netlist = Netlist::default();
+        for (designator, component) in [
+            ("R1", passive("4.87k 1%", None)),
+            ("R2", passive("4.87k", Some("5%"))),
+            ("R3", passive("5.1k", Some("1%"))),
+            ("R4", passive("4k8", None)),
+            ("C1", passive("4.7uF 10V X5R", Some("±10%"))),
+            ("C2", passive("5.6nF 20%", None)),
+        ] {
+            netlist
+                .components
+                .insert(Designator(designator.into()), component);
+        }
+
+        let mut config = ProjectConfig::default();
+        let mut diagnostics = Vec::new();
+        check_e_series(&netlist, &config, &mut diagnostics);
+        let mut flagged = diagnostics
+            .iter()
+            .map(|d| d.designator.0.as_str())
+            .collect::<Vec<_>>();
+        flagged.sort();
+        assert_eq!(flagged, ["C2", "R2"]);
+        let r2 = diagnostics.iter().find(|d| d.designator.0 == "R2").unwrap();
+        let StyleDiagnosticKind::NotInESeries { series, nearest } = r2.kind else {
+            panic!("unexpected diagnostic {:?}", r2.kind);
+        };
+        assert_eq!(series, ESeries::E24);
+        assert!((nearest.0 - 4700.0).abs() < 0.1 && (nearest.1 - 5100.0).abs() < 0.1);
+
+        config.thresholds.passive_e_series = Some(ESeries::E12);
+        let mut diagnostics = Vec::new();
+        check_e_series(&netlist, &config, &mut diagnostics);
+        let mut flagged = diagnostics
+            .iter()
+            .map(|d| d.designator.0.as_str())
+            .collect::<Vec<_>>();
+        flagged.sort();
+        assert_eq!(flagged, ["R1", "R2", "R3", "R4"]);
+    }
+
     #[test]
     fn able_to_find_style_diagnostics() {
         // let path =
@@ -127,12 +388,13 @@ mod tests {
         let netlist = ecad_file_format::load_altium_netlist(Path::new("/Users/roman/Downloads/test_projects/typec_sbu_serial_revb/typec_sbu_serial.NET.EDF"), Path::new("/Users/roman/Downloads/test_projects/typec_sbu_serial_revb/typec_sbu_serial.NET")).unwrap();
         println!("{:#?}", netlist);
 
+        let config = ProjectConfig::default();
         let mut diagnostics = Vec::new();
-        check_style(&netlist, &mut diagnostics);
+        check_style(&netlist, &config, &mut diagnostics);
         println!("{diagnostics:#?}");
 
         let mut diagnostics = Vec::new();
-        let buses = crate::i2c::find_i2c_buses(&netlist, &mut diagnostics);
+        let buses = crate::i2c::find_i2c_buses(&netlist, &config, &mut diagnostics);
         println!("buses: {buses:#?}");
         println!("diagnostics: {diagnostics:#?}");
     }
@@ -0,0 +1,288 @@
+use crate::Severity;
+use crate::diagnostics::{Diagnostic, Diagnostics, Location};
+use crate::util::glob_match;
+use anyhow::{Error, Result};
+use serde::{Deserialize, Deserializer};
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Intentional findings that should not fail the check, loaded from `better_erc_waivers.toml`:
+/// ```toml
+/// [[waiver]]
+/// code = "I2C005"
+/// bus = "I2C_FMP*"
+/// author = "J. Doe"
+/// reason = "1.5k pull-ups for fast-mode plus"
+/// expires = 2026-06-30
+/// ```
+/// Selectors are globs with `*` and `?`, a waiver applies to a diagnostic if its code matches and
+/// each of the given selectors matches at least one of the diagnostic's locations.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Waivers {
+    #[serde(rename = "waiver")]
+    pub waivers: Vec<Waiver>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Waiver {
+    pub code: String,
+    pub bus: Option<String>,
+    /// Also matches power rails
+    pub net: Option<String>,
+    /// Also matches nodes of the part
+    pub designator: Option<String>,
+    pub author: String,
+    pub reason: String,
+    /// Last day the waiver is in effect
+    #[serde(default, deserialize_with = "date")]
+    pub expires: Option<Date>,
+}
+
+/// Calendar date, ordered chronologically
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl Waivers {
+    pub const FILE_NAME: &'static str = "better_erc_waivers.toml";
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| Error::msg(format!("{}: {e}", path.display())))?;
+        Self::from_toml(&contents).map_err(|e| Error::msg(format!("{}: {e}", path.display())))
+    }
+
+    /// Loads `better_erc_waivers.toml` from the directory if there is one, otherwise there are no waivers
+    pub fn load_from_dir(dir: &Path) -> Result<Self> {
+        let path = dir.join(Self::FILE_NAME);
+        if path.exists() {
+            Self::load(&path)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    pub fn from_toml(contents: &str) -> Result<Self> {
+        let waivers: Waivers = toml::from_str(contents).map_err(|e| Error::msg(format!("{e}")))?;
+        for waiver in &waivers.waivers {
+            if waiver.reason.trim().is_empty() || waiver.author.trim().is_empty() {
+                return Err(Error::msg(format!(
+                    "waiver for {} must have an author and a reason",
+                    waiver.code
+                )));
+            }
+        }
+        Ok(waivers)
+    }
+
+    /// Marks matching diagnostics as waived and reports expired waivers and the ones that matched
+    /// nothing, so that they can be removed
+    pub fn apply(&self, diagnostics: &mut Diagnostics, today: Date) {
+        let mut findings = vec![];
+        for waiver in &self.waivers {
+            if waiver.is_expired(today) {
+                findings.push(Diagnostic::new(
+                    "WVR002",
+                    Severity::Warning,
+                    format!("waiver {waiver} has expired"),
+                ));
+                continue;
+            }
+            let mut used = false;
+            for diagnostic in &mut diagnostics.items {
+                if diagnostic.waived_by.is_none() && waiver.matches(diagnostic) {
+                    diagnostic.waived_by = Some(waiver.clone());
+                    used = true;
+                }
+            }
+            if !used {
+                findings.push(Diagnostic::new(
+                    "WVR001",
+                    Severity::Warning,
+                    format!("waiver {waiver} does not match anything"),
+                ));
+            }
+        }
+        diagnostics.extend(findings);
+    }
+}
+
+impl Waiver {
+    pub fn matches(&self, diagnostic: &Diagnostic) -> bool {
+        if !glob_match(&self.code, diagnostic.code) {
+            return false;
+        }
+        let matches_any = |selector: &Option<String>, name: fn(&Location) -> Option<&str>| {
+            let Some(pattern) = selector else {
+                return true;
+            };
+            diagnostic
+                .locations
+                .iter()
+                .filter_map(name)
+                .any(|name| glob_match(pattern, name))
+        };
+        matches_any(&self.bus, |location| match location {
+            Location::Bus(name) => Some(name),
+            _ => None,
+        }) && matches_any(&self.net, |location| match location {
+            Location::Net(net) | Location::Rail(net) => Some(&net.0),
+            _ => None,
+        }) && matches_any(&self.designator, |location| match location {
+            Location::Part(designator) | Location::Node(designator, _) => Some(&designator.0),
+            _ => None,
+        })
+    }
+
+    pub fn is_expired(&self, today: Date) -> bool {
+        self.expires.is_some_and(|expires| expires < today)
+    }
+}
+
+impl Date {
+    /// Current date in UTC
+    pub fn today() -> Self {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        Self::from_days_since_epoch((secs / 86_400) as i64)
+    }
+
+    /// Converts days since 1970-01-01 to a date in the proleptic Gregorian calendar
+    fn from_days_since_epoch(days: i64) -> Self {
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let day_of_era = z.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+        Date {
+            year: year as u16,
+            month: month as u8,
+            day: day as u8,
+        }
+    }
+}
+
+impl Display for Date {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl Display for Waiver {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.code)?;
+        for (name, selector) in [
+            ("bus", &self.bus),
+            ("net", &self.net),
+            ("designator", &self.designator),
+        ] {
+            if let Some(selector) = selector {
+                write!(f, " {name} {selector}")?;
+            }
+        }
+        write!(f, " by {}", self.author)?;
+        if let Some(expires) = self.expires {
+            write!(f, " (expires {expires})")?;
+        }
+        Ok(())
+    }
+}
+
+/// Expiry date as a TOML local date, e.g. `expires = 2026-06-30`
+fn date<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Date>, D::Error> {
+    use serde::de::Error as _;
+    let datetime = toml::value::Datetime::deserialize(deserializer)?;
+    match (datetime.date, datetime.time) {
+        (Some(date), None) => Ok(Some(Date {
+            year: date.year,
+            month: date.month,
+            day: date.day,
+        })),
+        _ => Err(D::Error::custom(format!(
+            "expected a date like 2026-06-30, found {datetime}"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Date, Waivers};
+    use crate::Severity;
+    use crate::diagnostics::{Diagnostic, Diagnostics, Location};
+    use crate::util::glob_match;
+    use ecad_file_format::Designator;
+    use std::path::Path;
+
+    #[test]
+    fn waivers_are_matched_and_reported() {
+        assert!(glob_match("I2C_*_3V?", "I2C_SENS_3V3"));
+        assert!(!glob_match("I2C_*_3V?", "I2C_SENS_3V"));
+        assert_eq!(
+            Date::from_days_since_epoch(20_000),
+            Date {
+                year: 2024,
+                month: 10,
+                day: 4
+            }
+        );
+
+        let waivers = Waivers::load(Path::new("test_input/better_erc_waivers.toml")).unwrap();
+        let mut diagnostics = Diagnostics::default();
+        diagnostics.push(
+            Diagnostic::new("I2C005", Severity::Warning, "pull-up value is out of range")
+                .at(Location::Bus("I2C_FMP1".into())),
+        );
+        diagnostics.push(
+            Diagnostic::new("I2C005", Severity::Warning, "pull-up value is out of range")
+                .at(Location::Bus("I2C2".into())),
+        );
+        diagnostics.push(
+            Diagnostic::new("STY003", Severity::Error, "value is empty")
+                .at(Location::Part(Designator("R12".into()))),
+        );
+        let today = Date {
+            year: 2026,
+            month: 1,
+            day: 15,
+        };
+        waivers.apply(&mut diagnostics, today);
+
+        let waived = diagnostics
+            .iter()
+            .filter(|d| d.waived_by.is_some())
+            .map(|d| d.locations[0].to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(waived, ["bus I2C_FMP1", "R12"]);
+        assert_eq!(diagnostics.with_code("WVR001").count(), 1);
+        assert_eq!(diagnostics.with_code("WVR002").count(), 1);
+        assert!(
+            diagnostics
+                .with_code("WVR002")
+                .all(|d| d.message.contains("expires 2025-12-31"))
+        );
+
+        assert!(
+            Waivers::from_toml("[[waiver]]\ncode = \"I2C005\"\nauthor = \"\"\nreason = \"x\"")
+                .is_err()
+        );
+        assert!(
+            Waivers::from_toml(
+                "[[waiver]]\ncode = \"I2C005\"\nauthor = \"a\"\nreason = \"x\"\nexpires = \"soon\""
+            )
+            .is_err()
+        );
+    }
+}
@@ -3,7 +3,12 @@ use std::path::{Path, PathBuf};
 use std::{fs, io};
 use subprocess::Exec;
 
+/// Exports netlists and pictures of the test schematics with kicad-cli, does nothing without it
 pub fn generate_netlists() {
+    if !kicad_cli_available() {
+        println!("kicad-cli not found, netlists are not generated");
+        return;
+    }
     let generated_netlists_folder = get_parent_folder("generated_netlists").unwrap();
     let generated_pictures_folder = get_parent_folder("generated_pictures").unwrap();
     println!("generated netlists folder: {:?}", generated_netlists_folder);
@@ -99,6 +104,22 @@ fn kicad_cli_path() -> &'static Path {
     Path::new("/Applications/KiCad/KiCad.app/Contents/MacOS/kicad-cli")
 }
 
+/// Elsewhere kicad-cli is expected on PATH
+#[cfg(not(target_os = "macos"))]
+fn kicad_cli_path() -> &'static Path {
+    Path::new("kicad-cli")
+}
+
+/// Tests comparing against kicad-cli netlists are skipped when it is not installed
+pub fn kicad_cli_available() -> bool {
+    Exec::cmd(kicad_cli_path())
+        .arg("version")
+        .stdout(subprocess::NullFile)
+        .stderr(subprocess::NullFile)
+        .join()
+        .is_ok_and(|status| status.success())
+}
+
 fn collect_schematics() -> Vec<PathBuf> {
     let path = get_parent_folder("sources").unwrap();
     fs::read_dir(path)